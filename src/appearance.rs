@@ -0,0 +1,84 @@
+//! Light/dark color scheme for the Slint windows
+//! The system appearance is read once at startup, `Config.theme` can override it
+
+use crate::config::ThemeMode;
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static SYSTEM_DARK: Lazy<bool> = Lazy::new(system_prefers_dark);
+
+/// Set when the theme setting changes, the main timer re-applies it to open windows
+static THEME_DIRTY: AtomicBool = AtomicBool::new(false);
+
+pub fn is_dark(mode: ThemeMode) -> bool {
+    match mode {
+        ThemeMode::System => *SYSTEM_DARK,
+        ThemeMode::Light => false,
+        ThemeMode::Dark => true,
+    }
+}
+
+/// Value for the `scheme` property of the Slint `Theme` global
+pub fn scheme(mode: ThemeMode) -> &'static str {
+    if is_dark(mode) { "dark" } else { "light" }
+}
+
+pub fn mark_theme_dirty() {
+    THEME_DIRTY.store(true, Ordering::Relaxed);
+}
+
+pub fn take_theme_dirty() -> bool {
+    THEME_DIRTY.swap(false, Ordering::Relaxed)
+}
+
+#[cfg(target_os = "macos")]
+fn system_prefers_dark() -> bool {
+    use core_foundation::base::{CFType, CFTypeRef, TCFType};
+    use core_foundation::string::{CFString, CFStringRef};
+
+    extern "C" {
+        static kCFPreferencesAnyApplication: CFStringRef;
+        fn CFPreferencesCopyAppValue(key: CFStringRef, application: CFStringRef) -> CFTypeRef;
+    }
+
+    // 浅色模式下没有这个键
+    let key = CFString::from_static_string("AppleInterfaceStyle");
+    unsafe {
+        let value = CFPreferencesCopyAppValue(key.as_concrete_TypeRef(), kCFPreferencesAnyApplication);
+        if value.is_null() {
+            return false;
+        }
+        CFType::wrap_under_create_rule(value)
+            .downcast::<CFString>()
+            .is_some_and(|style| style.to_string().eq_ignore_ascii_case("dark"))
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn system_prefers_dark() -> bool {
+    use windows::core::w;
+    use windows::Win32::Foundation::ERROR_SUCCESS;
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+
+    let mut value: u32 = 1;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            w!("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize"),
+            w!("AppsUseLightTheme"),
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut value as *mut u32 as *mut _),
+            Some(&mut size),
+        )
+    };
+    // 读不到时按浅色处理，和系统默认一致
+    status == ERROR_SUCCESS && value == 0
+}
+
+// 其他平台没有统一的接口，保持原来的深色界面
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn system_prefers_dark() -> bool {
+    true
+}
@@ -0,0 +1,121 @@
+//! Batch selections: split on a delimiter, translate items one by one, rejoin
+//! Delimiters and the whitespace around each item are kept as they were
+
+/// One item between delimiters, whitespace split off so it survives translation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment<'a> {
+    pub leading: &'a str,
+    pub core: &'a str,
+    pub trailing: &'a str,
+}
+
+impl Segment<'_> {
+    /// Empty or whitespace-only items are copied through untranslated
+    pub fn is_blank(&self) -> bool {
+        self.core.is_empty()
+    }
+}
+
+/// Turn the settings value into the real delimiter (`\n`, `\t` escapes), blank means newline
+pub fn parse_delimiter(setting: &str) -> String {
+    if setting.is_empty() {
+        return "\n".to_string();
+    }
+    let mut out = String::new();
+    let mut chars = setting.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+pub fn split_segments<'a>(text: &'a str, delimiter: &str) -> Vec<Segment<'a>> {
+    if delimiter.is_empty() {
+        return vec![make_segment(text)];
+    }
+    text.split(delimiter).map(make_segment).collect()
+}
+
+fn make_segment(piece: &str) -> Segment<'_> {
+    let core = piece.trim();
+    if core.is_empty() {
+        return Segment { leading: piece, core: "", trailing: "" };
+    }
+    let start = piece.len() - piece.trim_start().len();
+    let end = start + core.len();
+    Segment { leading: &piece[..start], core, trailing: &piece[end..] }
+}
+
+pub fn translatable_count(segments: &[Segment<'_>]) -> usize {
+    segments.iter().filter(|s| !s.is_blank()).count()
+}
+
+/// Rejoin with the original delimiter; `translated` holds one entry per non-blank segment, in order
+pub fn rejoin(segments: &[Segment<'_>], translated: &[String], delimiter: &str) -> String {
+    let mut translated = translated.iter();
+    segments
+        .iter()
+        .map(|seg| {
+            if seg.is_blank() {
+                return seg.leading.to_string();
+            }
+            let core = translated.next().map(|t| t.trim()).unwrap_or(seg.core);
+            format!("{}{}{}", seg.leading, core, seg.trailing)
+        })
+        .collect::<Vec<_>>()
+        .join(delimiter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upper(segments: &[Segment<'_>]) -> Vec<String> {
+        segments.iter().filter(|s| !s.is_blank()).map(|s| s.core.to_uppercase()).collect()
+    }
+
+    #[test]
+    fn test_parse_delimiter() {
+        assert_eq!(parse_delimiter(""), "\n");
+        assert_eq!(parse_delimiter("\\n"), "\n");
+        assert_eq!(parse_delimiter(";"), ";");
+        assert_eq!(parse_delimiter(" | "), " | ");
+        assert_eq!(parse_delimiter("\\t"), "\t");
+    }
+
+    #[test]
+    fn test_split_and_rejoin_custom_delimiter() {
+        let text = "apple; banana;cherry ";
+        let segments = split_segments(text, ";");
+        assert_eq!(translatable_count(&segments), 3);
+        assert_eq!(segments[1], Segment { leading: " ", core: "banana", trailing: "" });
+
+        // 分隔符和条目两侧的空白保持不变
+        assert_eq!(rejoin(&segments, &upper(&segments), ";"), "APPLE; BANANA;CHERRY ");
+
+        let segments = split_segments("a | b | c", "|");
+        assert_eq!(rejoin(&segments, &upper(&segments), "|"), "A | B | C");
+    }
+
+    #[test]
+    fn test_split_keeps_empty_and_trailing_segments() {
+        let text = "one||two|";
+        let segments = split_segments(text, "|");
+        assert_eq!(segments.len(), 4);
+        assert_eq!(translatable_count(&segments), 2);
+        assert_eq!(rejoin(&segments, &upper(&segments), "|"), "ONE||TWO|");
+
+        let text = "first line\n\n  second line\n";
+        let segments = split_segments(text, "\n");
+        assert_eq!(rejoin(&segments, &upper(&segments), "\n"), "FIRST LINE\n\n  SECOND LINE\n");
+    }
+}
@@ -1,11 +1,11 @@
 //! Cross-platform caret position detection
 //! Windows: Uses GetGUIThreadInfo to get text cursor position
-//! macOS: Uses mouse position as fallback (Accessibility API requires permissions)
+//! macOS: Uses the Accessibility selection bounds, mouse position when unavailable
 
 #[cfg(target_os = "windows")]
 mod windows_impl {
-    use windows::Win32::Foundation::{HWND, POINT, RECT};
-    use windows::Win32::Graphics::Gdi::ClientToScreen;
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM, POINT, RECT};
+    use windows::Win32::Graphics::Gdi::{ClientToScreen, EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFO};
     use windows::Win32::UI::WindowsAndMessaging::{
         GetCursorPos, GetForegroundWindow, GetGUIThreadInfo, GetWindowThreadProcessId,
         GetSystemMetrics, GUITHREADINFO, GUI_CARETBLINKING,
@@ -20,6 +20,29 @@ mod windows_impl {
         }
     }
 
+    pub fn monitor_rects() -> Vec<super::ScreenRect> {
+        unsafe extern "system" fn collect(monitor: HMONITOR, _: HDC, _: *mut RECT, data: LPARAM) -> BOOL {
+            let rects = &mut *(data.0 as *mut Vec<super::ScreenRect>);
+            let mut info = MONITORINFO { cbSize: std::mem::size_of::<MONITORINFO>() as u32, ..Default::default() };
+            if GetMonitorInfoW(monitor, &mut info).as_bool() {
+                let r = info.rcWork;
+                rects.push(super::ScreenRect {
+                    x: r.left as f32,
+                    y: r.top as f32,
+                    width: (r.right - r.left) as f32,
+                    height: (r.bottom - r.top) as f32,
+                });
+            }
+            true.into()
+        }
+
+        let mut rects: Vec<super::ScreenRect> = Vec::new();
+        unsafe {
+            let _ = EnumDisplayMonitors(None, None, Some(collect), LPARAM(&mut rects as *mut _ as isize));
+        }
+        rects
+    }
+
     pub fn is_our_process_foreground() -> bool {
         unsafe {
             let foreground = GetForegroundWindow();
@@ -114,14 +137,34 @@ mod macos_impl {
         (bounds.size.width as i32, bounds.size.height as i32)
     }
 
+    pub fn monitor_rects() -> Vec<super::ScreenRect> {
+        CGDisplay::active_displays()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|id| {
+                let b = CGDisplay::new(id).bounds();
+                super::ScreenRect {
+                    x: b.origin.x as f32,
+                    y: b.origin.y as f32,
+                    width: b.size.width as f32,
+                    height: b.size.height as f32,
+                }
+            })
+            .collect()
+    }
+
     pub fn is_our_process_foreground() -> bool {
         // macOS 下简化实现，总是返回 false 避免误判
         false
     }
 
     pub fn get_caret_position() -> (i32, i32) {
-        // macOS 获取光标位置需要 Accessibility 权限，这里使用鼠标位置作为替代
-        get_mouse_position()
+        if !ax::is_trusted() {
+            // 走启动时同一套权限提示，每次运行只弹一次
+            crate::input::report_keyboard_monitor_error("accessibility permission missing, caret falls back to mouse");
+            return get_mouse_position();
+        }
+        ax::selection_origin().unwrap_or_else(get_mouse_position)
     }
 
     fn get_mouse_position() -> (i32, i32) {
@@ -133,6 +176,95 @@ mod macos_impl {
         }
         (0, 0)
     }
+
+    /// Accessibility lookups: focused element -> selected range -> bounds of that range
+    mod ax {
+        use core_foundation::base::{CFRelease, CFTypeRef, TCFType};
+        use core_foundation::string::{CFString, CFStringRef};
+        use core_graphics::geometry::{CGPoint, CGRect, CGSize};
+        use std::ffi::c_void;
+
+        const AX_ERROR_SUCCESS: i32 = 0;
+        const AX_VALUE_CG_RECT_TYPE: u32 = 3;
+
+        #[link(name = "ApplicationServices", kind = "framework")]
+        extern "C" {
+            fn AXIsProcessTrusted() -> bool;
+            fn AXUIElementCreateSystemWide() -> CFTypeRef;
+            fn AXUIElementCopyAttributeValue(element: CFTypeRef, attribute: CFStringRef, value: *mut CFTypeRef) -> i32;
+            fn AXUIElementCopyParameterizedAttributeValue(
+                element: CFTypeRef,
+                attribute: CFStringRef,
+                parameter: CFTypeRef,
+                value: *mut CFTypeRef,
+            ) -> i32;
+            fn AXValueGetValue(value: CFTypeRef, value_type: u32, out: *mut c_void) -> bool;
+        }
+
+        /// Released on drop, every ref here comes from a Create/Copy call
+        struct Owned(CFTypeRef);
+
+        impl Owned {
+            fn new(raw: CFTypeRef) -> Option<Self> {
+                (!raw.is_null()).then_some(Self(raw))
+            }
+        }
+
+        impl Drop for Owned {
+            fn drop(&mut self) {
+                unsafe { CFRelease(self.0) }
+            }
+        }
+
+        pub fn is_trusted() -> bool {
+            unsafe { AXIsProcessTrusted() }
+        }
+
+        /// Top-left of the selection in global screen coordinates, None when the app exposes nothing
+        pub fn selection_origin() -> Option<(i32, i32)> {
+            unsafe {
+                let system = Owned::new(AXUIElementCreateSystemWide())?;
+                let focused = copy_attribute(&system, "AXFocusedUIElement")?;
+                let range = copy_attribute(&focused, "AXSelectedTextRange")?;
+                let bounds = copy_bounds_for_range(&focused, &range)?;
+
+                let mut rect = CGRect::new(&CGPoint::new(0.0, 0.0), &CGSize::new(0.0, 0.0));
+                if !AXValueGetValue(bounds.0, AX_VALUE_CG_RECT_TYPE, &mut rect as *mut CGRect as *mut c_void) {
+                    return None;
+                }
+                // 部分应用（如 Electron）返回全零矩形，当作拿不到处理
+                if rect.size.width <= 0.0 && rect.size.height <= 0.0 {
+                    return None;
+                }
+                Some((rect.origin.x as i32, rect.origin.y as i32))
+            }
+        }
+
+        unsafe fn copy_attribute(element: &Owned, name: &'static str) -> Option<Owned> {
+            let attribute = CFString::from_static_string(name);
+            let mut value: CFTypeRef = std::ptr::null();
+            let err = AXUIElementCopyAttributeValue(element.0, attribute.as_concrete_TypeRef(), &mut value);
+            if err != AX_ERROR_SUCCESS {
+                return None;
+            }
+            Owned::new(value)
+        }
+
+        unsafe fn copy_bounds_for_range(element: &Owned, range: &Owned) -> Option<Owned> {
+            let attribute = CFString::from_static_string("AXBoundsForRange");
+            let mut value: CFTypeRef = std::ptr::null();
+            let err = AXUIElementCopyParameterizedAttributeValue(
+                element.0,
+                attribute.as_concrete_TypeRef(),
+                range.0,
+                &mut value,
+            );
+            if err != AX_ERROR_SUCCESS {
+                return None;
+            }
+            Owned::new(value)
+        }
+    }
 }
 
 // 公共接口
@@ -147,29 +279,145 @@ pub fn get_screen_size() -> (i32, i32) {
     (1920, 1080)
 }
 
+/// Rectangle in screen coordinates (OS or logical, depending on the caller)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ScreenRect {
+    fn overlap(&self, other: &ScreenRect) -> (f32, f32) {
+        let w = (self.x + self.width).min(other.x + other.width) - self.x.max(other.x);
+        let h = (self.y + self.height).min(other.y + other.height) - self.y.max(other.y);
+        (w.max(0.0), h.max(0.0))
+    }
+
+    pub fn scaled(&self, factor: f32) -> ScreenRect {
+        ScreenRect { x: self.x / factor, y: self.y / factor, width: self.width / factor, height: self.height / factor }
+    }
+}
+
+/// Connected monitors in OS coordinates, the primary one (at the origin) first
+pub fn monitor_rects() -> Vec<ScreenRect> {
+    #[cfg(target_os = "windows")]
+    let mut rects = windows_impl::monitor_rects();
+
+    #[cfg(target_os = "macos")]
+    let mut rects = macos_impl::monitor_rects();
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut rects = {
+        let (width, height) = get_screen_size();
+        vec![ScreenRect { x: 0.0, y: 0.0, width: width as f32, height: height as f32 }]
+    };
+
+    rects.sort_by_key(|r| !(r.x == 0.0 && r.y == 0.0));
+    rects
+}
+
+/// Part of a window that must be on some monitor to still be reachable (roughly the title bar)
+const MIN_VISIBLE: (f32, f32) = (120.0, 40.0);
+
+/// Whether a window at `window` can still be grabbed on one of `monitors`
+pub fn is_on_screen(window: &ScreenRect, monitors: &[ScreenRect]) -> bool {
+    let need_w = MIN_VISIBLE.0.min(window.width);
+    let need_h = MIN_VISIBLE.1.min(window.height);
+    monitors.iter().any(|m| {
+        let (w, h) = window.overlap(m);
+        w >= need_w && h >= need_h
+    })
+}
+
+/// Top-left position that centers a window of `size` on `monitor`, never above or left of it
+pub fn centered_on(monitor: &ScreenRect, size: (f32, f32)) -> (f32, f32) {
+    let x = monitor.x + ((monitor.width - size.0) / 2.0).max(0.0);
+    let y = monitor.y + ((monitor.height - size.1) / 2.0).max(0.0);
+    (x, y)
+}
+
+/// Pull a saved window position back onto the closest monitor when it is no longer reachable
+/// (e.g. the monitor it was on got unplugged), positions that are still on screen are kept
+pub fn clamp_onto_screen(window: &ScreenRect, monitors: &[ScreenRect]) -> (f32, f32) {
+    if monitors.is_empty() || is_on_screen(window, monitors) {
+        return (window.x, window.y);
+    }
+    let center = (window.x + window.width / 2.0, window.y + window.height / 2.0);
+    let distance = |m: &ScreenRect| {
+        let dx = (m.x + m.width / 2.0) - center.0;
+        let dy = (m.y + m.height / 2.0) - center.1;
+        dx * dx + dy * dy
+    };
+    let nearest = monitors
+        .iter()
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+        .unwrap_or(&monitors[0]);
+    // 窗口比屏幕大时贴左上角
+    let x = window.x.min(nearest.x + nearest.width - window.width).max(nearest.x);
+    let y = window.y.min(nearest.y + nearest.height - window.height).max(nearest.y);
+    (x, y)
+}
+
+/// Factor from OS screen coordinates to Slint logical pixels.
+/// Windows reports physical pixels, macOS already reports points.
+pub fn os_to_logical_scale(window_scale: f32) -> f32 {
+    if cfg!(target_os = "macos") || window_scale <= 0.0 {
+        1.0
+    } else {
+        window_scale
+    }
+}
+
+/// Popup position in logical pixels.
+/// Cursor comes in OS coordinates, popup size is logical.
 pub fn calculate_popup_position(
     cursor_x: i32,
     cursor_y: i32,
-    popup_width: i32,
-    popup_height: i32,
-) -> (i32, i32) {
-    let (screen_width, screen_height) = get_screen_size();
+    popup_width: f32,
+    popup_height: f32,
+    scale: f32,
+) -> (f32, f32) {
+    let monitor = monitor_at((cursor_x as f32, cursor_y as f32), &monitor_rects()).unwrap_or_else(|| {
+        let (width, height) = get_screen_size();
+        ScreenRect { x: 0.0, y: 0.0, width: width as f32, height: height as f32 }
+    });
+    popup_position_logical((cursor_x, cursor_y), (popup_width, popup_height), &monitor, scale)
+}
+
+/// Monitor containing `point`, the nearest one when it falls into a gap between screens
+fn monitor_at(point: (f32, f32), monitors: &[ScreenRect]) -> Option<ScreenRect> {
+    let distance = |m: &ScreenRect| {
+        let dx = (m.x - point.0).max(point.0 - (m.x + m.width)).max(0.0);
+        let dy = (m.y - point.1).max(point.1 - (m.y + m.height)).max(0.0);
+        dx * dx + dy * dy
+    };
+    monitors.iter().min_by(|a, b| distance(a).total_cmp(&distance(b))).copied()
+}
 
-    let mut x = cursor_x - popup_width / 2;
-    let mut y = cursor_y - popup_height - 10;
+/// 所有计算都在逻辑坐标下进行，避免物理/逻辑混用
+fn popup_position_logical(cursor: (i32, i32), popup: (f32, f32), monitor: &ScreenRect, scale: f32) -> (f32, f32) {
+    let cursor_x = cursor.0 as f32 / scale;
+    let cursor_y = cursor.1 as f32 / scale;
+    let screen = monitor.scaled(scale);
+    let (popup_width, popup_height) = popup;
 
-    if x < 0 {
-        x = 0;
+    let mut x = cursor_x - popup_width / 2.0;
+    let mut y = cursor_y - popup_height - 10.0;
+
+    if x + popup_width > screen.x + screen.width {
+        x = screen.x + screen.width - popup_width;
     }
-    if x + popup_width > screen_width {
-        x = screen_width - popup_width;
+    if x < screen.x {
+        x = screen.x;
     }
 
-    if y < 0 {
-        y = cursor_y + 20;
+    if y < screen.y {
+        y = cursor_y + 20.0;
     }
-    if y + popup_height > screen_height {
-        y = screen_height - popup_height;
+    if y + popup_height > screen.y + screen.height {
+        y = screen.y + screen.height - popup_height;
     }
 
     (x, y)
@@ -207,4 +455,99 @@ mod tests {
         assert!(x >= -10000 && x <= 10000);
         assert!(y >= -10000 && y <= 10000);
     }
+
+    #[test]
+    fn test_popup_position_scaled() {
+        // 1.5 倍缩放：2880x1620 物理像素 = 1920x1080 逻辑像素
+        let screen = ScreenRect { x: 0.0, y: 0.0, width: 2880.0, height: 1620.0 };
+        let popup = (380.0, 220.0);
+
+        let (x, y) = popup_position_logical((1500, 900), popup, &screen, 1.5);
+        assert_eq!((x, y), (810.0, 370.0));
+
+        // 靠近右上角：贴右边缘，放到光标下方
+        let (x, y) = popup_position_logical((2850, 30), popup, &screen, 1.5);
+        assert_eq!((x, y), (1540.0, 40.0));
+
+        // 靠近右下角，不超出屏幕
+        let (x, y) = popup_position_logical((2870, 1610), popup, &screen, 1.5);
+        assert!(x + popup.0 <= 1920.0 && y + popup.1 <= 1080.0);
+    }
+
+    #[test]
+    fn test_window_on_screen_validation() {
+        let monitors = [
+            ScreenRect { x: 0.0, y: 0.0, width: 1920.0, height: 1040.0 },
+            ScreenRect { x: 1920.0, y: 0.0, width: 2560.0, height: 1400.0 },
+        ];
+        let at = |x: f32, y: f32| ScreenRect { x, y, width: 760.0, height: 700.0 };
+
+        assert!(is_on_screen(&at(100.0, 100.0), &monitors));
+        // 跨两块屏幕也算可见
+        assert!(is_on_screen(&at(1500.0, 200.0), &monitors));
+        // 只露出一条窄边不算
+        assert!(!is_on_screen(&at(1900.0 + 2560.0, 100.0), &monitors));
+        // 原来的副屏已断开
+        assert!(!is_on_screen(&at(2500.0, 300.0), &monitors[..1]));
+        assert!(!is_on_screen(&at(-900.0, 100.0), &monitors));
+        assert!(!is_on_screen(&at(100.0, 100.0), &[]));
+    }
+
+    #[test]
+    fn test_centered_on_monitor() {
+        let monitor = ScreenRect { x: 1920.0, y: 0.0, width: 2560.0, height: 1400.0 };
+        assert_eq!(centered_on(&monitor, (760.0, 700.0)), (1920.0 + 900.0, 350.0));
+        // 窗口比屏幕大时贴左上角
+        let small = ScreenRect { x: 0.0, y: 0.0, width: 640.0, height: 480.0 };
+        assert_eq!(centered_on(&small, (760.0, 700.0)), (0.0, 0.0));
+        assert_eq!(small.scaled(2.0), ScreenRect { x: 0.0, y: 0.0, width: 320.0, height: 240.0 });
+    }
+
+    #[test]
+    fn test_clamp_saved_position_onto_screen() {
+        let monitors = [
+            ScreenRect { x: 0.0, y: 0.0, width: 1920.0, height: 1040.0 },
+            ScreenRect { x: 1920.0, y: 0.0, width: 2560.0, height: 1400.0 },
+        ];
+        let at = |x: f32, y: f32| ScreenRect { x, y, width: 380.0, height: 220.0 };
+
+        // 还在屏幕上的位置保持不动
+        assert_eq!(clamp_onto_screen(&at(2500.0, 900.0), &monitors), (2500.0, 900.0));
+        // 副屏拔掉后拉回主屏右边缘
+        assert_eq!(clamp_onto_screen(&at(2500.0, 900.0), &monitors[..1]), (1540.0, 820.0));
+        // 跑到左上方外面
+        assert_eq!(clamp_onto_screen(&at(-5000.0, -300.0), &monitors), (0.0, 0.0));
+        assert_eq!(clamp_onto_screen(&at(-5000.0, -300.0), &[]), (-5000.0, -300.0));
+    }
+
+    #[test]
+    fn test_popup_position_unscaled_matches_physical() {
+        let screen = ScreenRect { x: 0.0, y: 0.0, width: 1920.0, height: 1080.0 };
+        let (x, y) = popup_position_logical((1000, 600), (380.0, 220.0), &screen, 1.0);
+        assert_eq!((x, y), (810.0, 370.0));
+    }
+
+    #[test]
+    fn test_popup_stays_on_the_selection_monitor() {
+        let monitors = [
+            ScreenRect { x: 0.0, y: 0.0, width: 1920.0, height: 1080.0 },
+            ScreenRect { x: -1280.0, y: 0.0, width: 1280.0, height: 1024.0 },
+            ScreenRect { x: 1920.0, y: -200.0, width: 2560.0, height: 1440.0 },
+        ];
+        let popup = (380.0, 220.0);
+
+        // 左侧负坐标屏幕的左上角：贴左边缘，放到光标下方，不会被拉回主屏
+        let left = monitor_at((-1270.0, 5.0), &monitors).unwrap();
+        assert_eq!(left, monitors[1]);
+        assert_eq!(popup_position_logical((-1270, 5), popup, &left, 1.0), (-1280.0, 25.0));
+
+        // 右侧屏幕的顶部比主屏高
+        let right = monitor_at((4400.0, -150.0), &monitors).unwrap();
+        assert_eq!(right, monitors[2]);
+        assert_eq!(popup_position_logical((4400, -150), popup, &right, 1.0), (4100.0, -130.0));
+
+        // 落在屏幕之间的空隙里，取最近的那块
+        assert_eq!(monitor_at((-100.0, 1050.0), &monitors), Some(monitors[1]));
+        assert_eq!(monitor_at((0.0, 0.0), &[]), None);
+    }
 }
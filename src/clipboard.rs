@@ -6,6 +6,96 @@ use arboard::Clipboard;
 use std::thread;
 use std::time::Duration;
 
+// 剪贴板被其他程序占用时通常很快就会释放，短暂重试即可
+const WRITE_RETRY_ATTEMPTS: u32 = 3;
+const WRITE_RETRY_DELAY_MS: u64 = 40;
+
+/// Plain text plus the HTML flavor when the app that copied it offered one
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClipboardContent {
+    pub text: String,
+    pub html: Option<String>,
+}
+
+impl ClipboardContent {
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self { text: text.into(), html: None }
+    }
+}
+
+/// Minimal clipboard access, so write paths can be exercised without a real clipboard
+pub trait ClipboardBackend {
+    fn get_text(&mut self) -> Result<String>;
+    fn set_text(&mut self, text: &str) -> Result<()>;
+
+    /// HTML flavor, None when the clipboard holds only plain text
+    fn get_html(&mut self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// HTML with `alt_text` as the plain-text flavor, backends without HTML keep just the text
+    fn set_html(&mut self, _html: &str, alt_text: &str) -> Result<()> {
+        self.set_text(alt_text)
+    }
+}
+
+/// The OS clipboard via arboard
+pub struct SystemClipboard;
+
+impl ClipboardBackend for SystemClipboard {
+    fn get_text(&mut self) -> Result<String> {
+        let mut clipboard = Clipboard::new()?;
+        Ok(clipboard.get_text()?)
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        let mut clipboard = Clipboard::new()?;
+        clipboard.set_text(text)?;
+        Ok(())
+    }
+
+    fn get_html(&mut self) -> Result<Option<String>> {
+        // 各平台没有 HTML 时报的错不一样，一律当作没有
+        let html = Clipboard::new()?.get().html().ok();
+        Ok(html.filter(|html| !html.trim().is_empty()))
+    }
+
+    fn set_html(&mut self, html: &str, alt_text: &str) -> Result<()> {
+        let mut clipboard = Clipboard::new()?;
+        clipboard.set_html(html, Some(alt_text))?;
+        Ok(())
+    }
+}
+
+/// Write text, retrying a few times while the clipboard is locked by another app
+pub fn set_text_with_retry(backend: &mut impl ClipboardBackend, text: &str) -> Result<()> {
+    write_with_retry(backend, |b| b.set_text(text))
+}
+
+/// Write text and, when present, the HTML flavor with the same retries
+pub fn set_content_with_retry(backend: &mut impl ClipboardBackend, content: &ClipboardContent) -> Result<()> {
+    match &content.html {
+        Some(html) => write_with_retry(backend, |b| b.set_html(html, &content.text)),
+        None => set_text_with_retry(backend, &content.text),
+    }
+}
+
+fn write_with_retry<B: ClipboardBackend>(backend: &mut B, mut write: impl FnMut(&mut B) -> Result<()>) -> Result<()> {
+    let mut attempt = 1;
+    loop {
+        match write(backend) {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt >= WRITE_RETRY_ATTEMPTS => {
+                return Err(e.context(format!("Clipboard write failed after {} attempts", attempt)));
+            }
+            Err(_) => {
+                thread::sleep(Duration::from_millis(WRITE_RETRY_DELAY_MS));
+                attempt += 1;
+            }
+        }
+    }
+}
+
 /// Guard that saves clipboard content on creation and restores it on drop
 pub struct ClipboardGuard {
     original_text: Option<String>,
@@ -65,15 +155,25 @@ impl Default for ClipboardGuard {
     }
 }
 
-/// Capture selected text using Ctrl+C with clipboard protection
+/// Whether the copy after Ctrl+C is just the old clipboard again (nothing was selected)
+/// Some apps trim or convert line endings on copy, so compare trimmed text with unified newlines
+pub fn same_selection(copied: &str, original: &str) -> bool {
+    copied == original || normalize_for_compare(copied) == normalize_for_compare(original)
+}
+
+fn normalize_for_compare(text: &str) -> String {
+    text.trim().replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Capture selected text using the copy shortcut with clipboard protection
 pub fn capture_selected_text() -> Result<String> {
-    use crate::input::send_ctrl_c;
+    use crate::input::send_copy;
 
     // Create guard to save and restore clipboard
     let guard = ClipboardGuard::new();
 
-    // Send Ctrl+C to copy selected text
-    send_ctrl_c();
+    // Send the copy shortcut to copy selected text
+    send_copy();
 
     // Wait a bit for clipboard to update
     thread::sleep(Duration::from_millis(100));
@@ -83,7 +183,7 @@ pub fn capture_selected_text() -> Result<String> {
 
     // Check if we got the same text as before (nothing was selected)
     if let Some(ref original) = guard.original_text {
-        if &text == original {
+        if same_selection(&text, original) {
             anyhow::bail!("No text selected");
         }
     }
@@ -95,27 +195,28 @@ pub fn capture_selected_text() -> Result<String> {
     Ok(text)
 }
 
-/// Paste text and restore original clipboard
-pub fn paste_and_restore(text: &str, original: Option<String>) -> Result<()> {
-    use crate::input::send_ctrl_v;
+/// Paste content and restore original clipboard, `restore_delay` gives the target app time to read it
+/// Both the plain and HTML flavors go back, so formatted text copied earlier survives the round trip
+pub fn paste_and_restore(content: &ClipboardContent, original: Option<ClipboardContent>, restore_delay: Duration) -> Result<()> {
+    use crate::input::send_paste;
 
-    let mut clipboard = Clipboard::new()?;
+    let mut clipboard = SystemClipboard;
 
     // Set the translation result to clipboard
-    clipboard.set_text(text)?;
+    set_content_with_retry(&mut clipboard, content)?;
 
     // Small delay before paste
     thread::sleep(Duration::from_millis(50));
 
-    // Send Ctrl+V to paste
-    send_ctrl_v();
+    // Send the paste shortcut to paste
+    send_paste();
 
     // Wait for paste to complete
-    thread::sleep(Duration::from_millis(100));
+    thread::sleep(restore_delay);
 
     // Restore original clipboard content
-    if let Some(original_text) = original {
-        clipboard.set_text(&original_text)?;
+    if let Some(original) = original {
+        set_content_with_retry(&mut clipboard, &original)?;
     }
 
     Ok(())
@@ -123,18 +224,46 @@ pub fn paste_and_restore(text: &str, original: Option<String>) -> Result<()> {
 
 /// Simple clipboard operations without protection
 pub mod simple {
+    use super::{set_content_with_retry, set_text_with_retry, ClipboardBackend, ClipboardContent, SystemClipboard};
     use anyhow::Result;
     use arboard::Clipboard;
+    use std::io::Cursor;
+
+    /// Image on the clipboard as PNG, None when it holds no image
+    pub fn get_image() -> Result<Option<Vec<u8>>> {
+        let image = match Clipboard::new()?.get_image() {
+            Ok(image) => image,
+            Err(arboard::Error::ContentNotAvailable) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let rgba = image::RgbaImage::from_raw(image.width as u32, image.height as u32, image.bytes.into_owned())
+            .ok_or_else(|| anyhow::anyhow!("Clipboard image has an unexpected size"))?;
+        let mut png = Vec::new();
+        rgba.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)?;
+        Ok(Some(png))
+    }
 
     pub fn get_text() -> Result<String> {
-        let mut clipboard = Clipboard::new()?;
-        Ok(clipboard.get_text()?)
+        SystemClipboard.get_text()
     }
 
     pub fn set_text(text: &str) -> Result<()> {
-        let mut clipboard = Clipboard::new()?;
-        clipboard.set_text(text)?;
-        Ok(())
+        set_text_with_retry(&mut SystemClipboard, text)
+    }
+
+    pub fn get_html() -> Result<Option<String>> {
+        SystemClipboard.get_html()
+    }
+
+    /// Text and HTML flavor together, for putting the clipboard back later
+    pub fn get_content() -> Result<ClipboardContent> {
+        let text = SystemClipboard.get_text()?;
+        let html = get_html().unwrap_or(None);
+        Ok(ClipboardContent { text, html })
+    }
+
+    pub fn set_content(content: &ClipboardContent) -> Result<()> {
+        set_content_with_retry(&mut SystemClipboard, content)
     }
 }
 
@@ -142,10 +271,75 @@ pub mod simple {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_same_selection_ignores_copy_normalization() {
+        assert!(same_selection("hello world", "hello world"));
+        assert!(same_selection("hello world\n", "hello world"));
+        assert!(same_selection("line one\r\nline two\r\n", "line one\nline two"));
+        assert!(same_selection("  indented\r", "indented"));
+    }
+
+    #[test]
+    fn test_same_selection_detects_real_changes() {
+        assert!(!same_selection("hello world", "hello there"));
+        // 内部空白不同算不同内容
+        assert!(!same_selection("a  b", "a b"));
+        assert!(!same_selection("line one\n\nline two", "line one\nline two"));
+    }
+
+    /// Clipboard that rejects the first `failures` writes
+    struct FakeClipboard {
+        failures: u32,
+        writes: u32,
+        text: String,
+    }
+
+    impl ClipboardBackend for FakeClipboard {
+        fn get_text(&mut self) -> Result<String> {
+            Ok(self.text.clone())
+        }
+
+        fn set_text(&mut self, text: &str) -> Result<()> {
+            self.writes += 1;
+            if self.writes <= self.failures {
+                anyhow::bail!("clipboard locked");
+            }
+            self.text = text.to_string();
+            Ok(())
+        }
+    }
+
     #[test]
     fn test_clipboard_guard_creation() {
         let guard = ClipboardGuard::new();
         // Just verify it doesn't panic
         drop(guard);
     }
+
+    #[test]
+    fn test_set_text_retries_transient_lock() {
+        let mut fake = FakeClipboard { failures: 2, writes: 0, text: String::new() };
+        set_text_with_retry(&mut fake, "hello").unwrap();
+        assert_eq!(fake.writes, 3);
+        assert_eq!(fake.get_text().unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_set_content_without_html_support_keeps_text() {
+        let mut fake = FakeClipboard { failures: 1, writes: 0, text: String::new() };
+        let content = ClipboardContent { text: "plain".to_string(), html: Some("<b>plain</b>".to_string()) };
+        set_content_with_retry(&mut fake, &content).unwrap();
+        assert_eq!(fake.writes, 2);
+        assert_eq!(fake.text, "plain");
+        assert_eq!(fake.get_html().unwrap(), None);
+    }
+
+    #[test]
+    fn test_set_text_surfaces_persistent_failure() {
+        let mut fake = FakeClipboard { failures: u32::MAX, writes: 0, text: "old".to_string() };
+        let err = set_text_with_retry(&mut fake, "hello").unwrap_err();
+        assert_eq!(fake.writes, WRITE_RETRY_ATTEMPTS);
+        assert!(format!("{:#}", err).contains("clipboard locked"));
+        assert_eq!(fake.text, "old");
+    }
 }
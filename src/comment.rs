@@ -0,0 +1,147 @@
+//! "Insert as comment" for code editors
+//! Detects the comment syntax of the selection and places the translation below it in the same style
+
+/// Comment syntax of a selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentStyle {
+    /// Every non-empty line starts with the marker
+    Line(&'static str),
+    Block { open: &'static str, close: &'static str },
+}
+
+/// Longer markers first so `///` is not read as `//`
+const LINE_MARKERS: [&str; 5] = ["///", "//!", "//", "#", "--"];
+const BLOCK_MARKERS: [(&str, &str); 2] = [("/*", "*/"), ("<!--", "-->")];
+
+/// Comment style of the whole selection, None when it isn't entirely a comment
+pub fn detect_style(text: &str) -> Option<CommentStyle> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    for (open, close) in BLOCK_MARKERS {
+        if trimmed.starts_with(open) && trimmed.ends_with(close) && trimmed.len() >= open.len() + close.len() {
+            return Some(CommentStyle::Block { open, close });
+        }
+    }
+    let mut lines = trimmed.lines().map(str::trim_start).filter(|l| !l.is_empty());
+    let first = lines.next()?;
+    let marker = LINE_MARKERS.into_iter().find(|m| first.starts_with(m))?;
+    lines
+        .all(|l| l.starts_with(marker))
+        .then_some(CommentStyle::Line(marker))
+}
+
+/// Comment body without markers, one leading space per line removed
+pub fn strip_markers(text: &str, style: CommentStyle) -> String {
+    let text = text.trim();
+    match style {
+        CommentStyle::Line(marker) => text
+            .lines()
+            .map(|l| {
+                let l = l.trim_start();
+                let l = l.strip_prefix(marker).unwrap_or(l);
+                l.strip_prefix(' ').unwrap_or(l).trim_end()
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        CommentStyle::Block { open, close } => {
+            let inner = text.strip_prefix(open).unwrap_or(text);
+            let inner = inner.strip_suffix(close).unwrap_or(inner);
+            // 去掉 Javadoc 风格每行开头的 *
+            inner
+                .lines()
+                .map(|l| {
+                    let l = l.trim();
+                    let l = l.strip_prefix('*').unwrap_or(l);
+                    l.strip_prefix(' ').unwrap_or(l)
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+                .trim()
+                .to_string()
+        }
+    }
+}
+
+/// Wrap `body` in the comment style, every line prefixed with `indent`
+pub fn wrap(body: &str, style: CommentStyle, indent: &str) -> String {
+    let lines: Vec<&str> = body.trim().lines().map(str::trim_end).collect();
+    match style {
+        CommentStyle::Line(marker) => lines
+            .iter()
+            .map(|l| if l.is_empty() { format!("{}{}", indent, marker) } else { format!("{}{} {}", indent, marker, l) })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        CommentStyle::Block { open, close } if lines.len() <= 1 => {
+            format!("{}{} {} {}", indent, open, lines.first().copied().unwrap_or(""), close)
+        }
+        CommentStyle::Block { open, close } => {
+            let mut out = vec![format!("{}{}", indent, open)];
+            out.extend(lines.iter().map(|l| format!("{}{}", indent, l).trim_end().to_string()));
+            out.push(format!("{}{}", indent, close));
+            out.join("\n")
+        }
+    }
+}
+
+/// Original selection followed by the translation as a comment on a new line
+/// None when the selection isn't a comment
+pub fn insert_below(source: &str, translated: &str) -> Option<String> {
+    let style = detect_style(source)?;
+    // 模型有时会保留注释符号，先去掉再重新包装
+    let body = if detect_style(translated) == Some(style) {
+        strip_markers(translated, style)
+    } else {
+        translated.trim().to_string()
+    };
+    if body.is_empty() {
+        return None;
+    }
+
+    let first_line = source.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+    let indent = &first_line[..first_line.len() - first_line.trim_start().len()];
+    let newline = if source.contains("\r\n") { "\r\n" } else { "\n" };
+    let head = source.trim_end_matches(['\r', '\n']).replace("\r\n", "\n");
+
+    let mut out = format!("{}\n{}", head, wrap(&body, style, indent));
+    if source.ends_with('\n') {
+        out.push('\n');
+    }
+    Some(if newline == "\n" { out } else { out.replace('\n', newline) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_line_styles() {
+        let slash = CommentStyle::Line("//");
+        assert_eq!(wrap("读取配置文件", slash, "    "), "    // 读取配置文件");
+        assert_eq!(wrap("第一行\n\n第二行", slash, ""), "// 第一行\n//\n// 第二行");
+
+        let hash = CommentStyle::Line("#");
+        assert_eq!(wrap("安装依赖", hash, ""), "# 安装依赖");
+        assert_eq!(detect_style("# install deps\n# then build"), Some(hash));
+        assert_eq!(detect_style("/// Returns the value"), Some(CommentStyle::Line("///")));
+        assert_eq!(detect_style("let x = 1; // note"), None);
+    }
+
+    #[test]
+    fn test_wrap_block_style() {
+        let block = CommentStyle::Block { open: "/*", close: "*/" };
+        assert_eq!(detect_style("/* open the socket */"), Some(block));
+        assert_eq!(wrap("打开套接字", block, ""), "/* 打开套接字 */");
+        assert_eq!(wrap("第一行\n第二行", block, "  "), "  /*\n  第一行\n  第二行\n  */");
+        assert_eq!(strip_markers("/**\n * Parse input.\n * Returns None.\n */", block), "Parse input.\nReturns None.");
+    }
+
+    #[test]
+    fn test_insert_below_keeps_indent_and_strips_echoed_markers() {
+        let source = "    // open the file\n";
+        assert_eq!(insert_below(source, "// 打开文件").unwrap(), "    // open the file\n    // 打开文件\n");
+        assert_eq!(insert_below("# a\r\n# b", "甲\n乙").unwrap(), "# a\r\n# b\r\n# 甲\r\n# 乙");
+        assert_eq!(insert_below("plain text", "纯文本"), None);
+    }
+}
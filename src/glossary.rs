@@ -0,0 +1,135 @@
+//! Fixed terminology (source term -> forced target term)
+//! LLM providers get the matching entries in the system prompt, Google/DeepL results are patched afterwards
+
+/// Whole-word, case-insensitive match of `term` in `text` starting at byte `from`
+/// Word boundaries only apply to Latin-like edges, CJK terms match anywhere
+fn find_term(text: &str, term: &str, from: usize) -> Option<(usize, usize)> {
+    let term_chars: Vec<char> = term.chars().collect();
+    let (first, last) = (*term_chars.first()?, *term_chars.last()?);
+    for (start, _) in text[from..].char_indices().map(|(i, c)| (i + from, c)) {
+        let mut rest = text[start..].char_indices();
+        let mut matched = true;
+        for t in &term_chars {
+            match rest.next() {
+                Some((_, c)) if same_char(c, *t) => {}
+                _ => {
+                    matched = false;
+                    break;
+                }
+            }
+        }
+        if !matched {
+            continue;
+        }
+        let end = rest.next().map(|(i, _)| start + i).unwrap_or(text.len());
+        let before = text[..start].chars().next_back();
+        let after = text[end..].chars().next();
+        let left_ok = !is_word_char(first) || !before.is_some_and(is_word_char);
+        let right_ok = !is_word_char(last) || !after.is_some_and(is_word_char);
+        if left_ok && right_ok {
+            return Some((start, end));
+        }
+    }
+    None
+}
+
+fn same_char(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+/// Letters/digits that take part in word boundaries (CJK has no spaces between words)
+fn is_word_char(c: char) -> bool {
+    (c.is_alphanumeric() || c == '_') && (c as u32) < 0x2E80
+}
+
+pub fn contains_term(text: &str, term: &str) -> bool {
+    find_term(text, term, 0).is_some()
+}
+
+/// Entries whose source term occurs in `text`
+pub fn relevant_entries<'a>(glossary: &'a [(String, String)], text: &str) -> Vec<&'a (String, String)> {
+    glossary.iter().filter(|(source, _)| contains_term(text, source)).collect()
+}
+
+/// System prompt addition for the entries found in `text`, None when nothing matches
+pub fn prompt_section(glossary: &[(String, String)], text: &str) -> Option<String> {
+    let entries = relevant_entries(glossary, text);
+    if entries.is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = entries.iter().map(|(s, t)| format!("- {} -> {}", s, t)).collect();
+    Some(format!(
+        "## 术语表\n以下术语必须严格使用指定译法，不得改动：\n{}",
+        lines.join("\n")
+    ))
+}
+
+/// Replace source terms left in a machine translation with their forced target terms
+pub fn apply(translated: &str, glossary: &[(String, String)]) -> String {
+    let mut out = translated.to_string();
+    for (source, target) in glossary {
+        let mut from = 0;
+        while let Some((start, end)) = find_term(&out, source, from) {
+            out.replace_range(start..end, target);
+            from = start + target.len();
+        }
+    }
+    out
+}
+
+/// One `source = target` pair per line, blank or malformed lines are skipped
+pub fn parse_lines(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let (source, target) = line.split_once('=')?;
+            let (source, target) = (source.trim(), target.trim());
+            (!source.is_empty() && !target.is_empty()).then(|| (source.to_string(), target.to_string()))
+        })
+        .collect()
+}
+
+pub fn format_lines(glossary: &[(String, String)]) -> String {
+    glossary.iter().map(|(s, t)| format!("{} = {}", s, t)).collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glossary() -> Vec<(String, String)> {
+        vec![
+            ("NanoTrans".to_string(), "NanoTrans 翻译".to_string()),
+            ("pod".to_string(), "Pod".to_string()),
+            ("工作区".to_string(), "workspace".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_whole_word_case_insensitive_match() {
+        assert!(contains_term("Restart the POD now", "pod"));
+        assert!(!contains_term("Use an iPod", "pod"));
+        assert!(!contains_term("pods are ready", "pod"));
+        assert!(contains_term("打开工作区设置", "工作区"));
+
+        let glossary = glossary();
+        let found: Vec<&str> = relevant_entries(&glossary, "nanotrans on a pod").iter().map(|(s, _)| s.as_str()).collect();
+        assert_eq!(found, vec!["NanoTrans", "pod"]);
+        assert!(prompt_section(&glossary, "nothing relevant").is_none());
+        assert!(prompt_section(&glossary, "a pod").unwrap().contains("- pod -> Pod"));
+    }
+
+    #[test]
+    fn test_apply_replaces_leftover_terms() {
+        assert_eq!(apply("重启 pod 和另一个 POD", &glossary()), "重启 Pod 和另一个 Pod");
+        assert_eq!(apply("iPod 不受影响", &glossary()), "iPod 不受影响");
+        // 替换结果里再次出现原词时不会死循环
+        assert_eq!(apply("nanotrans", &glossary()), "NanoTrans 翻译");
+    }
+
+    #[test]
+    fn test_parse_and_format_lines() {
+        let parsed = parse_lines("NanoTrans = NanoTrans 翻译\n\n bad line \npod=Pod\n = empty");
+        assert_eq!(parsed, glossary()[..2].to_vec());
+        assert_eq!(parse_lines(&format_lines(&parsed)), parsed);
+    }
+}
@@ -0,0 +1,180 @@
+//! Local translation history
+//! One JSON entry per line in history.jsonl next to the config file, oldest lines are dropped past the size cap
+
+use crate::config::Config;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Upper bound for history.jsonl, a few thousand short translations
+pub const MAX_HISTORY_BYTES: usize = 512 * 1024;
+
+/// One successful translation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub source: String,
+    pub translated: String,
+    pub provider: String,
+    /// Unix seconds
+    pub timestamp: u64,
+}
+
+impl HistoryEntry {
+    pub fn new(source: &str, translated: &str, provider: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            translated: translated.to_string(),
+            provider: provider.to_string(),
+            timestamp: unix_now(),
+        }
+    }
+}
+
+pub fn history_path() -> Result<PathBuf> {
+    Ok(Config::config_path()?.with_file_name("history.jsonl"))
+}
+
+pub fn append(entry: &HistoryEntry) -> Result<()> {
+    append_to(&history_path()?, entry, MAX_HISTORY_BYTES)
+}
+
+/// Newest first, a missing file is an empty history
+pub fn load() -> Result<Vec<HistoryEntry>> {
+    load_from(&history_path()?)
+}
+
+pub fn clear() -> Result<()> {
+    clear_at(&history_path()?)
+}
+
+fn append_to(path: &Path, entry: &HistoryEntry, max_bytes: usize) -> Result<()> {
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    let existing = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    if existing.len() + line.len() <= max_bytes {
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(line.as_bytes())?;
+        return Ok(());
+    }
+    // 超出上限时从最旧的一行开始丢，只保留完整的行
+    let budget = max_bytes.saturating_sub(line.len());
+    let start = existing
+        .match_indices('\n')
+        .map(|(i, _)| i + 1)
+        .find(|&i| existing.len() - i <= budget)
+        .unwrap_or(existing.len());
+    let mut kept = existing[start..].to_string();
+    kept.push_str(&line);
+    fs::write(path, kept)?;
+    Ok(())
+}
+
+fn load_from(path: &Path) -> Result<Vec<HistoryEntry>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    // 写到一半的行直接跳过，不影响其它记录
+    let mut entries: Vec<HistoryEntry> = text
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    entries.reverse();
+    Ok(entries)
+}
+
+fn clear_at(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Compact age like "5m" or "3d" for the history list
+pub fn format_age(timestamp: u64, now: u64) -> String {
+    let secs = now.saturating_sub(timestamp);
+    match secs {
+        0..=59 => "<1m".to_string(),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86399 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(source: &str) -> HistoryEntry {
+        HistoryEntry {
+            source: source.to_string(),
+            translated: format!("{}-译文", source),
+            provider: "Google".to_string(),
+            timestamp: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_append_load_and_clear() {
+        let path = std::env::temp_dir().join(format!("nanotrans-history-test-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        assert!(load_from(&path).unwrap().is_empty());
+
+        append_to(&path, &entry("a"), MAX_HISTORY_BYTES).unwrap();
+        append_to(&path, &entry("b"), MAX_HISTORY_BYTES).unwrap();
+        let loaded = load_from(&path).unwrap();
+        assert_eq!(loaded, vec![entry("b"), entry("a")]);
+
+        clear_at(&path).unwrap();
+        assert!(!path.exists());
+        // 重复清空也不报错
+        clear_at(&path).unwrap();
+    }
+
+    #[test]
+    fn test_append_drops_oldest_past_cap() {
+        let path = std::env::temp_dir().join(format!("nanotrans-history-cap-test-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let line_len = serde_json::to_string(&entry("a")).unwrap().len() + 1;
+        let cap = line_len * 2;
+
+        for source in ["a", "b", "c"] {
+            append_to(&path, &entry(source), cap).unwrap();
+        }
+        let loaded = load_from(&path).unwrap();
+        assert_eq!(loaded, vec![entry("c"), entry("b")]);
+        assert!(fs::metadata(&path).unwrap().len() as usize <= cap);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_load_skips_broken_lines() {
+        let path = std::env::temp_dir().join(format!("nanotrans-history-broken-test-{}.jsonl", std::process::id()));
+        let good = serde_json::to_string(&entry("a")).unwrap();
+        fs::write(&path, format!("{}\n{{\"source\": \"cut\n", good)).unwrap();
+        assert_eq!(load_from(&path).unwrap(), vec![entry("a")]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(100, 130), "<1m");
+        assert_eq!(format_age(0, 300), "5m");
+        assert_eq!(format_age(0, 7200), "2h");
+        assert_eq!(format_age(0, 3 * 86400), "3d");
+        // 时钟回拨时不会溢出
+        assert_eq!(format_age(500, 100), "<1m");
+    }
+}
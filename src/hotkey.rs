@@ -4,6 +4,7 @@
 use anyhow::Result;
 use crossbeam_channel::Receiver;
 use global_hotkey::hotkey::{Code, HotKey, Modifiers};
+use std::time::{Duration, Instant};
 #[cfg(not(target_os = "macos"))]
 use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager};
 #[cfg(target_os = "macos")]
@@ -12,8 +13,32 @@ use crate::input;
 /// Default hotkey: Alt + Q
 pub const DEFAULT_HOTKEY: &str = "Alt+Q";
 
+/// Shortcuts the OS or nearly every app already uses, binding them would break them everywhere
 #[cfg(target_os = "macos")]
-pub type HotkeyEvent = ();
+const RESERVED_HOTKEYS: &[&str] = &[
+    "Cmd+C", "Cmd+V", "Cmd+X", "Cmd+Z", "Cmd+A", "Cmd+Q", "Cmd+W", "Cmd+H", "Cmd+M",
+    "Cmd+Tab", "Cmd+Space", "Ctrl+Space", "Cmd+Alt+Escape",
+];
+#[cfg(not(target_os = "macos"))]
+const RESERVED_HOTKEYS: &[&str] = &[
+    "Ctrl+C", "Ctrl+V", "Ctrl+X", "Ctrl+Z", "Ctrl+A", "Alt+F4", "Alt+Tab", "Alt+Escape",
+    "Ctrl+Alt+Delete", "Ctrl+Shift+Escape", "Win+L", "Win+D", "Win+E", "Win+R", "Win+Tab",
+];
+
+/// What a registered hotkey does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyAction {
+    Translate,
+    /// Translate the selection and paste it in place, no popup
+    Paste,
+    /// Read text from the image on the clipboard, then translate it
+    Ocr,
+    /// Translate into the target language of `Config::target_hotkeys[i]`
+    TranslateTo(usize),
+}
+
+#[cfg(target_os = "macos")]
+pub type HotkeyEvent = HotkeyAction;
 
 #[cfg(not(target_os = "macos"))]
 pub type HotkeyEvent = GlobalHotKeyEvent;
@@ -22,6 +47,7 @@ pub type HotkeyEvent = GlobalHotKeyEvent;
 #[cfg(target_os = "macos")]
 pub struct HotkeyManager {
     current_hotkey: String,
+    target_hotkey_count: usize,
 }
 
 /// Hotkey manager wrapper
@@ -31,6 +57,9 @@ pub struct HotkeyManager {
     translate_hotkey: HotKey,
     translate_hotkey_id: u32,
     current_hotkey: String,
+    /// Optional hotkeys (paste, OCR, per-language translate), each action bound at most once
+    extra_hotkeys: Vec<(HotkeyAction, HotKey)>,
+    target_hotkey_count: usize,
 }
 
 #[cfg(target_os = "macos")]
@@ -38,12 +67,17 @@ impl HotkeyManager {
     /// Create a new hotkey manager with the specified hotkey string
     pub fn new(hotkey_str: &str) -> Result<Self> {
         input::set_active_hotkey(hotkey_str)?;
-        Ok(Self { current_hotkey: hotkey_str.to_lowercase() })
+        Ok(Self { current_hotkey: hotkey_str.to_lowercase(), target_hotkey_count: 0 })
     }
 
-    /// Check if the event matches our translate hotkey
-    pub fn is_translate_hotkey(&self, _event: &HotkeyEvent) -> bool {
-        true
+    /// Action bound to the event, the monitor only reports hotkeys we registered
+    pub fn action_for(&self, event: &HotkeyEvent) -> Option<HotkeyAction> {
+        Some(*event)
+    }
+
+    /// Bind an optional hotkey, an empty string turns it off
+    pub fn set_extra_hotkey(&mut self, action: HotkeyAction, hotkey_str: &str) -> Result<()> {
+        input::set_extra_hotkey(action, hotkey_str)
     }
 
     /// Update the hotkey binding
@@ -74,12 +108,41 @@ impl HotkeyManager {
             translate_hotkey: hotkey,
             translate_hotkey_id: hotkey_id,
             current_hotkey: hotkey_str.to_lowercase(),
+            extra_hotkeys: Vec::new(),
+            target_hotkey_count: 0,
         })
     }
 
-    /// Check if the event matches our translate hotkey
-    pub fn is_translate_hotkey(&self, event: &HotkeyEvent) -> bool {
-        event.id == self.translate_hotkey_id
+    /// Action bound to the event, None for hotkeys we don't own
+    pub fn action_for(&self, event: &HotkeyEvent) -> Option<HotkeyAction> {
+        if event.id == self.translate_hotkey_id {
+            return Some(HotkeyAction::Translate);
+        }
+        self.extra_hotkeys.iter().find(|(_, h)| h.id() == event.id).map(|(action, _)| *action)
+    }
+
+    /// Bind an optional hotkey, an empty string turns it off
+    pub fn set_extra_hotkey(&mut self, action: HotkeyAction, hotkey_str: &str) -> Result<()> {
+        let new_hotkey = if hotkey_str.trim().is_empty() {
+            None
+        } else {
+            Some(parse_hotkey(hotkey_str)?)
+        };
+        let old_hotkey = self.extra_hotkeys.iter().find(|(a, _)| *a == action).map(|(_, h)| *h);
+        if new_hotkey.map(|h| h.id()) == old_hotkey.map(|h| h.id()) {
+            return Ok(());
+        }
+        if let Some(hotkey) = new_hotkey {
+            self.manager.register(hotkey)?;
+        }
+        if let Some(old) = old_hotkey {
+            self.manager.unregister(old)?;
+        }
+        self.extra_hotkeys.retain(|(a, _)| *a != action);
+        if let Some(hotkey) = new_hotkey {
+            self.extra_hotkeys.push((action, hotkey));
+        }
+        Ok(())
     }
 
     /// Update the hotkey binding
@@ -104,13 +167,44 @@ impl HotkeyManager {
     }
 }
 
+impl HotkeyManager {
+    /// Bind the per-language translate hotkeys, the i-th one reports `TranslateTo(i)`.
+    /// A bad entry doesn't stop the others, the first error is returned
+    pub fn set_target_hotkeys(&mut self, hotkeys: &[&str]) -> Result<()> {
+        // 先全部解绑，列表顺序变化时同一组合才能换到新的位置
+        for i in 0..self.target_hotkey_count {
+            let _ = self.set_extra_hotkey(HotkeyAction::TranslateTo(i), "");
+        }
+        let mut first_error = None;
+        for (i, hotkey) in hotkeys.iter().enumerate() {
+            if let Err(e) = self.set_extra_hotkey(HotkeyAction::TranslateTo(i), hotkey) {
+                first_error.get_or_insert(anyhow::anyhow!("{}: {}", hotkey, e));
+            }
+        }
+        self.target_hotkey_count = hotkeys.len();
+        first_error.map_or(Ok(()), Err)
+    }
+}
+
 #[cfg(not(target_os = "macos"))]
 impl Drop for HotkeyManager {
     fn drop(&mut self) {
         let _ = self.manager.unregister(self.translate_hotkey);
+        for (_, hotkey) in &self.extra_hotkeys {
+            let _ = self.manager.unregister(*hotkey);
+        }
     }
 }
 
+/// Whether `hotkey_str` is a system shortcut we shouldn't take over, modifier order doesn't matter
+pub fn is_reserved_hotkey(hotkey_str: &str) -> bool {
+    let Ok(hotkey) = parse_hotkey(hotkey_str) else { return false; };
+    RESERVED_HOTKEYS
+        .iter()
+        .filter_map(|reserved| parse_hotkey(reserved).ok())
+        .any(|reserved| reserved.id() == hotkey.id())
+}
+
 /// Parse a hotkey string like "Alt+Q" or "Ctrl+Shift+T" into a HotKey
 pub fn parse_hotkey(hotkey_str: &str) -> Result<HotKey> {
     let parts: Vec<&str> = hotkey_str.split('+').map(|s| s.trim()).collect();
@@ -219,12 +313,42 @@ fn parse_key_code(key: &str) -> Result<Code> {
         "left" => Code::ArrowLeft,
         "right" => Code::ArrowRight,
 
+        // Punctuation, named by US-layout position
+        "semicolon" => Code::Semicolon,
+        "equal" => Code::Equal,
+        "comma" => Code::Comma,
+        "minus" => Code::Minus,
+        "period" => Code::Period,
+        "slash" => Code::Slash,
+        "backquote" => Code::Backquote,
+        "bracketleft" => Code::BracketLeft,
+        "backslash" => Code::Backslash,
+        "bracketright" => Code::BracketRight,
+        "quote" => Code::Quote,
+
+        // Numpad
+        "numpad0" => Code::Numpad0,
+        "numpad1" => Code::Numpad1,
+        "numpad2" => Code::Numpad2,
+        "numpad3" => Code::Numpad3,
+        "numpad4" => Code::Numpad4,
+        "numpad5" => Code::Numpad5,
+        "numpad6" => Code::Numpad6,
+        "numpad7" => Code::Numpad7,
+        "numpad8" => Code::Numpad8,
+        "numpad9" => Code::Numpad9,
+
         _ => anyhow::bail!("Unknown key: {}", key),
     };
 
     Ok(code)
 }
 
+/// Whether a press at `now` repeats the one at `last` within `window` (key repeat, double press)
+pub fn is_bounce(last: Option<Instant>, now: Instant, window: Duration) -> bool {
+    last.is_some_and(|last| now.saturating_duration_since(last) < window)
+}
+
 /// Get the global hotkey event receiver
 pub fn hotkey_event_receiver() -> Receiver<HotkeyEvent> {
     #[cfg(target_os = "macos")]
@@ -256,11 +380,40 @@ mod tests {
         assert!(hotkey4.id() > 0);
     }
 
+    #[test]
+    fn test_is_reserved_hotkey() {
+        #[cfg(not(target_os = "macos"))]
+        {
+            assert!(is_reserved_hotkey("Ctrl+C"));
+            assert!(is_reserved_hotkey("alt+f4"));
+            assert!(is_reserved_hotkey("Delete+Alt+Ctrl"));
+        }
+        #[cfg(target_os = "macos")]
+        assert!(is_reserved_hotkey("Cmd+Q"));
+        assert!(!is_reserved_hotkey("Alt+Q"));
+        assert!(!is_reserved_hotkey("not a hotkey"));
+    }
+
+    #[test]
+    fn test_is_bounce() {
+        let start = Instant::now();
+        let window = Duration::from_millis(400);
+        assert!(!is_bounce(None, start, window));
+        assert!(is_bounce(Some(start), start + Duration::from_millis(150), window));
+        assert!(!is_bounce(Some(start), start + Duration::from_millis(400), window));
+        // 窗口为 0 时不去抖
+        assert!(!is_bounce(Some(start), start, Duration::ZERO));
+    }
+
     #[test]
     fn test_parse_key_code() {
         assert!(parse_key_code("a").is_ok());
         assert!(parse_key_code("F1").is_ok());
         assert!(parse_key_code("space").is_ok());
         assert!(parse_key_code("invalid").is_err());
+        assert!(parse_key_code("Semicolon").is_ok());
+        assert!(parse_key_code("Numpad7").is_ok());
+        // 本地化标签不能作为按键名
+        assert!(parse_key_code("Ü").is_err());
     }
 }
@@ -1,61 +1,287 @@
 //! Internationalization (I18N) support
-//! Provides UI text translations for Chinese and English
+//! Provides UI text translations for English, Chinese and Japanese
 
-use crate::config::UILanguage;
+use crate::config::{Config, UILanguage};
+use anyhow::Result;
 use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fs;
 use std::sync::RwLock;
+use tracing::{error, warn};
 
 /// Current active language
 static CURRENT_LANG: Lazy<RwLock<Lang>> = Lazy::new(|| RwLock::new(Lang::En));
+/// Strings in use, a built-in table or one merged with a locale file
+static CURRENT_TEXTS: Lazy<RwLock<&'static Texts>> = Lazy::new(|| RwLock::new(&TEXTS_EN));
+
+/// Folder next to the config file holding `<code>.json` locale files
+const LOCALES_DIR: &str = "locales";
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Lang {
     En,
     Zh,
+    Ja,
+}
+
+// 新增界面语言：在这里加一项，补上 Texts 表和 UILanguage 即可
+impl Lang {
+    /// Order in the settings list, after "Auto"
+    pub const ALL: [Lang; 3] = [Lang::En, Lang::Zh, Lang::Ja];
+
+    /// Locale prefix, e.g. `ja` matches `ja-JP`
+    fn code(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::Zh => "zh",
+            Lang::Ja => "ja",
+        }
+    }
+
+    /// Name in the language itself, for the settings list
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Lang::En => "English",
+            Lang::Zh => "中文",
+            Lang::Ja => "日本語",
+        }
+    }
+
+    fn texts(self) -> &'static Texts {
+        match self {
+            Lang::En => &TEXTS_EN,
+            Lang::Zh => &TEXTS_ZH,
+            Lang::Ja => &TEXTS_JA,
+        }
+    }
+
+    fn from_ui(ui_lang: &UILanguage) -> Option<Lang> {
+        match ui_lang {
+            UILanguage::Auto => None,
+            UILanguage::En => Some(Lang::En),
+            UILanguage::Zh => Some(Lang::Zh),
+            UILanguage::Ja => Some(Lang::Ja),
+        }
+    }
+
+    fn to_ui(self) -> UILanguage {
+        match self {
+            Lang::En => UILanguage::En,
+            Lang::Zh => UILanguage::Zh,
+            Lang::Ja => UILanguage::Ja,
+        }
+    }
+
+    /// Language for a locale like `zh_CN.UTF-8` or `ja-JP`, None when we have no table for it
+    pub fn from_locale(locale: &str) -> Option<Lang> {
+        let prefix = locale.split(['-', '_', '.']).next()?.to_ascii_lowercase();
+        Lang::ALL.into_iter().find(|lang| lang.code() == prefix)
+    }
 }
 
-/// All translatable UI strings
-pub struct Texts {
+/// Declares `Texts` plus name-based access for locale files, so a field is listed only once
+macro_rules! texts {
+    ($($field:ident),* $(,)?) => {
+        /// All translatable UI strings
+        #[derive(Clone, Copy)]
+        pub struct Texts {
+            $(pub $field: &'static str,)*
+        }
+
+        impl Texts {
+            /// Field names, also the keys of a locale file
+            #[cfg(test)]
+            const KEYS: &'static [&'static str] = &[$(stringify!($field)),*];
+
+            fn set(&mut self, key: &str, value: &'static str) -> bool {
+                match key {
+                    $(stringify!($field) => self.$field = value,)*
+                    _ => return false,
+                }
+                true
+            }
+        }
+    };
+}
+
+texts! {
     // Settings window
-    pub settings_title: &'static str,
-    pub global_hotkey: &'static str,
-    pub hotkey_placeholder: &'static str,
-    pub hotkey_recording: &'static str,
-    pub translation_provider: &'static str,
-    pub provider_settings: &'static str,
-    pub google_no_config: &'static str,
-    pub deepl_settings: &'static str,
-    pub api_key: &'static str,
-    pub api_key_placeholder: &'static str,
-    pub deepl_hint: &'static str,
-    pub api_settings: &'static str,
-    pub api_base_url: &'static str,
-    pub model: &'static str,
-    pub model_placeholder: &'static str,
-    pub prompt_settings: &'static str,
-    pub prompt_preset: &'static str,
-    pub prompt_add: &'static str,
-    pub prompt_delete: &'static str,
-    pub prompt_name: &'static str,
-    pub prompt_system: &'static str,
-    pub prompt_user: &'static str,
-    pub prompt_vars: &'static str,
-    pub cancel: &'static str,
-    pub save: &'static str,
-    pub ui_language: &'static str,
-    pub hotkey_log_title: &'static str,
-    pub hotkey_log_enable: &'static str,
-    pub hotkey_log_hint: &'static str,
+    settings_title,
+    global_hotkey,
+    hotkey_placeholder,
+    hotkey_recording,
+    hotkey_register_failed,
+    hotkey_reserved,
+    send_modifier,
+    send_modifier_auto,
+    translation_provider,
+    fallback_providers,
+    provider_settings,
+    provider_type,
+    provider_add,
+    provider_delete,
+    google_no_config,
+    google_smart_join,
+    deepl_settings,
+    api_key,
+    api_key_placeholder,
+    api_key_clear,
+    api_key_clear_confirm,
+    api_key_required,
+    provider_reset,
+    provider_reset_confirm,
+    deepl_hint,
+    libretranslate_hint,
+    deepl_formality,
+    formality_default,
+    formality_more,
+    formality_less,
+    formality_prefer_more,
+    formality_prefer_less,
+    deepl_preserve_formatting,
+    azure_hint,
+    api_settings,
+    api_base_url,
+    model,
+    model_placeholder,
+    prompt_settings,
+    prompt_preset,
+    prompt_add,
+    prompt_delete,
+    prompt_name,
+    prompt_system,
+    prompt_user,
+    prompt_temperature,
+    prompt_max_tokens,
+    prompt_response_json,
+    prompt_vars,
+    prompt_missing_text,
+    prompt_missing_json,
+    prompt_examples,
+    prompt_example_source,
+    prompt_example_target,
+    cancel,
+    save,
+    ui_language,
+    recenter_settings,
+    theme,
+    theme_system,
+    theme_light,
+    theme_dark,
+    hotkey_log_title,
+    hotkey_log_enable,
+    hotkey_log_hint,
+    history_enable,
+    popup_title,
+    auto_expand_long,
+    preview_long,
+    popup_size,
+    popup_no_activate,
+    popup_always_on_top,
+    dismiss_on_focus_loss,
+    auto_close,
+    auto_copy_result,
+    stream_responses,
+    show_raw_response,
+    network_title,
+    advanced_title,
+    backup_title,
+    export_api_keys,
+    settings_export,
+    settings_import,
+    settings_export_done,
+    settings_import_done,
+    reset_defaults,
+    reset_defaults_confirm,
+    reset_defaults_warning,
+    reset_defaults_done,
+    input_delays,
+    retry_settings,
+    cache_capacity,
+    max_requests_per_hour,
+    max_chunk_chars,
+    proxy_url,
+    retry_empty_result,
+    request_timeout,
+    org_project,
+    extra_headers,
+    header_name,
+    header_value,
+    auto_pick_title,
+    auto_pick_enable,
+    auto_pick_partner,
+    auto_pick_strategy,
+    pick_paragraphs,
+    pick_length,
+    show_alternative,
+    raw_response,
+    batch_title,
+    glossary_title,
+    skip_title,
+    skip_hint,
+    notify_skipped,
+    selection_skipped,
+    glossary_hint,
+    batch_translate,
+    batch_delimiter,
+    multi_target_title,
+    multi_target,
+    target_langs,
+    secondary_lang,
+    price_per_1k,
+    usage_estimate,
+    usage_estimate_cost,
+    usage_reported,
+    usage_reported_cost,
+    usage_session,
+    prompt_import,
+    prompt_export,
+    prompt_export_done,
+    prompt_import_done,
+    prompt_import_issues,
+    prompt_io_failed,
+    provider_test,
+    provider_testing,
+    provider_test_ok,
+    provider_test_failed,
+    fetch_models,
+    models_fetching,
+    models_fetch_failed,
 
     // Popup window
-    pub translating: &'static str,
-    pub copy: &'static str,
-    pub apply: &'static str,
-    pub hint_apply: &'static str,
+    translating,
+    copy,
+    apply,
+    hint_apply,
+    clipboard_error,
+    paste_failed,
+    recognizing_image,
+    no_text_in_image,
+    bullet_list,
+    expand,
+    insert_comment,
+    swap_direction,
+    undo_swap,
+    speak,
+    stop_speaking,
+    copy_json,
+    preview_label,
+    translate_full,
+
+    // Review window
+    review_source,
+    review_translation,
+    retranslate,
+    close,
+
+    // History window
+    history_title,
+    history_empty,
+    history_clear,
 
     // Tray menu
-    pub tray_settings: &'static str,
-    pub tray_exit: &'static str,
+    tray_settings,
+    tray_exit,
 }
 
 const TEXTS_EN: Texts = Texts {
@@ -63,13 +289,36 @@ const TEXTS_EN: Texts = Texts {
     global_hotkey: "Global Hotkey",
     hotkey_placeholder: "Click and press keys...",
     hotkey_recording: "Press hotkey...",
+    hotkey_register_failed: "Could not register {hotkey}, it may be taken by another app. Kept {previous}. ({error})",
+    hotkey_reserved: "{hotkey} is a system shortcut, kept {previous}.",
+    send_modifier: "Copy/paste key",
+    send_modifier_auto: "Auto (Cmd on macOS, Ctrl elsewhere)",
     translation_provider: "Translation Provider",
+    fallback_providers: "Fallbacks",
     provider_settings: "Provider Settings",
+    provider_type: "Provider Type",
+    provider_add: "Add",
+    provider_delete: "Delete",
     google_no_config: "Google Translate - no config needed",
+    google_smart_join: "Keep sentence breaks between segments",
     deepl_settings: "DeepL Settings",
     api_key: "API Key",
     api_key_placeholder: "Enter your API key",
+    api_key_clear: "Clear",
+    api_key_clear_confirm: "Confirm?",
+    api_key_required: "This provider needs an API key before it can translate",
+    provider_reset: "Reset",
+    provider_reset_confirm: "Confirm?",
     deepl_hint: "Get your free API key at deepl.com/pro-api",
+    libretranslate_hint: "Self-hosted server, the API key is only needed if the server requires one",
+    deepl_formality: "Formality",
+    formality_default: "Default",
+    formality_more: "More formal",
+    formality_less: "Less formal",
+    formality_prefer_more: "Prefer more formal",
+    formality_prefer_less: "Prefer less formal",
+    deepl_preserve_formatting: "Preserve formatting (punctuation and casing)",
+    azure_hint: "API Base is the resource endpoint, Model is the deployment name",
     api_settings: "API Settings",
     api_base_url: "API Base URL",
     model: "Model",
@@ -81,18 +330,129 @@ const TEXTS_EN: Texts = Texts {
     prompt_name: "Preset Name",
     prompt_system: "System Template",
     prompt_user: "User Template",
-    prompt_vars: "Vars: {{target_lang_name}} {{target_lang_code}} {{text}}",
+    prompt_temperature: "Temperature (blank = default)",
+    prompt_max_tokens: "Max output tokens (blank = default)",
+    prompt_response_json: "JSON answer, translation field:",
+    prompt_vars: "Vars: {{text}} {{target_lang_name}} {{target_lang_code}} {{source_lang_name}} {{source_lang_code}} {{app_name}} {{datetime}}",
+    prompt_missing_text: "The user template has no {{text}}; the selected text will be appended after it",
+    prompt_missing_json: "JSON answers need the word \"JSON\" in the system or user template, OpenAI rejects the request otherwise",
+    prompt_examples: "Examples (sent before the text)",
+    prompt_example_source: "Source",
+    prompt_example_target: "Translation",
     cancel: "Close",
     save: "Save",
     ui_language: "UI Language",
+    recenter_settings: "Center this window each time it opens",
+    theme: "Theme",
+    theme_system: "System",
+    theme_light: "Light",
+    theme_dark: "Dark",
     hotkey_log_title: "Local Logs",
     hotkey_log_enable: "Enable hotkey log",
     hotkey_log_hint: "Write hotkey debug logs to a local file",
+    history_enable: "Keep a local translation history",
+    popup_title: "Popup",
+    auto_expand_long: "Open long results in the review window",
+    preview_long: "Preview long selections before translating in full",
+    popup_size: "Popup size (width x height, logical px)",
+    popup_no_activate: "Don't take focus from the current app when the popup appears",
+    popup_always_on_top: "Keep the popup above other windows",
+    dismiss_on_focus_loss: "Close the popup when it loses focus",
+    auto_close: "Close after idle seconds (empty = never)",
+    auto_copy_result: "Copy the translation to the clipboard automatically",
+    stream_responses: "Show OpenAI-compatible output as it is generated",
+    show_raw_response: "Show the raw provider response in the popup",
+    network_title: "Network",
+    advanced_title: "Advanced",
+    backup_title: "Import / Export",
+    export_api_keys: "Include API keys (written as plain text)",
+    settings_export: "Export Settings...",
+    settings_import: "Import Settings...",
+    settings_export_done: "Settings exported to {path}",
+    settings_import_done: "Imported: {providers} providers, {presets} presets added or updated",
+    reset_defaults: "Reset All",
+    reset_defaults_confirm: "Confirm?",
+    reset_defaults_warning: "Click again to restore defaults. Custom providers, prompt presets and all API keys will be deleted",
+    reset_defaults_done: "All settings restored to defaults",
+    input_delays: "Copy wait, paste wait, focus wait before pasting and key gap in ms, raise them if the selection is often missed",
+    retry_settings: "Retries on timeouts / 429 / 5xx, and first delay in ms (0 retries = off)",
+    cache_capacity: "Cached recent translations (0 = off)",
+    max_requests_per_hour: "Max requests per hour (empty = no limit)",
+    max_chunk_chars: "Split LLM input longer than (chars, 0 = off)",
+    proxy_url: "Proxy URL (empty = system proxy)",
+    retry_empty_result: "Retry once when the result is empty",
+    request_timeout: "Request timeout (seconds)",
+    org_project: "Organization / Project ID (optional)",
+    extra_headers: "Custom headers (optional)",
+    header_name: "Header",
+    header_value: "Value",
+    auto_pick_title: "Auto-pick",
+    auto_pick_enable: "Also translate with a second provider and show the better result",
+    auto_pick_partner: "Second provider",
+    auto_pick_strategy: "Prefer",
+    pick_paragraphs: "Matching paragraph count",
+    pick_length: "Closest length",
+    show_alternative: "Show {provider} result",
+    raw_response: "Raw response",
+    batch_title: "Batch",
+    glossary_title: "Glossary",
+    skip_title: "Skip selections",
+    skip_hint: "One regex per line, matched against the whole selection",
+    notify_skipped: "Show a note when a selection is skipped",
+    selection_skipped: "Not translated: the selection matches a skip pattern",
+    glossary_hint: "One term per line: source = target (whole word, case-insensitive)",
+    batch_translate: "Translate delimiter-separated items one by one",
+    batch_delimiter: "Delimiter (\\n = newline)",
+    multi_target_title: "Multiple Languages",
+    multi_target: "Translate into all of these languages at once (Apply is unavailable)",
+    target_langs: "Language codes",
+    secondary_lang: "Second language (used when the selection is already in the target language)",
+    price_per_1k: "Price per 1K tokens, input / output (optional)",
+    usage_estimate: "~{tokens} tokens (estimate)",
+    usage_estimate_cost: "~{tokens} tokens · ${cost} (estimate)",
+    usage_reported: "{tokens} tokens",
+    usage_reported_cost: "{tokens} tokens · ${cost}",
+    usage_session: "{usage} · session {total}",
+    prompt_import: "Import",
+    prompt_export: "Export",
+    prompt_export_done: "Exported {count} presets to {path}",
+    prompt_import_done: "Imported {added} new, {updated} updated",
+    prompt_import_issues: "Skipped: {issues}",
+    prompt_io_failed: "Failed: {error}",
+    provider_test: "Test",
+    provider_testing: "Testing...",
+    provider_test_ok: "Connected: hello -> {result}",
+    provider_test_failed: "Failed: {error}",
+    fetch_models: "Fetch list",
+    models_fetching: "Loading models...",
+    models_fetch_failed: "Couldn't load models: {error}",
 
     translating: "Translating...",
     copy: "Copy",
     apply: "Apply",
-    hint_apply: "Click result or press Enter to apply",
+    hint_apply: "Edit the result if needed, then Apply",
+    clipboard_error: "Clipboard is busy, please try again",
+    paste_failed: "Translate and paste failed",
+    recognizing_image: "Reading text from the clipboard image...",
+    no_text_in_image: "No text found in the image",
+    bullet_list: "List",
+    expand: "Expand",
+    insert_comment: "Comment",
+    swap_direction: "Translate back",
+    undo_swap: "Undo",
+    speak: "Speak",
+    stop_speaking: "Stop",
+    copy_json: "JSON",
+    preview_label: "Preview of the beginning only",
+    translate_full: "Translate full",
+    review_source: "Source",
+    review_translation: "Translation",
+    retranslate: "Retranslate",
+    close: "Close",
+
+    history_title: "History",
+    history_empty: "No translations recorded yet. Turn on history in Settings.",
+    history_clear: "Clear history",
 
     tray_settings: "Settings",
     tray_exit: "Exit",
@@ -103,13 +463,36 @@ const TEXTS_ZH: Texts = Texts {
     global_hotkey: "全局快捷键",
     hotkey_placeholder: "点击后按下快捷键...",
     hotkey_recording: "请按下快捷键...",
+    hotkey_register_failed: "无法注册 {hotkey}，可能已被其他应用占用，仍使用 {previous}。（{error}）",
+    hotkey_reserved: "{hotkey} 是系统快捷键，仍使用 {previous}。",
+    send_modifier: "复制/粘贴按键",
+    send_modifier_auto: "自动（macOS 用 Cmd，其他用 Ctrl）",
     translation_provider: "翻译服务",
+    fallback_providers: "备用服务",
     provider_settings: "服务设置",
+    provider_type: "服务类型",
+    provider_add: "新增",
+    provider_delete: "删除",
     google_no_config: "Google 翻译 - 无需配置",
+    google_smart_join: "保留分句之间的空格与换行",
     deepl_settings: "DeepL 设置",
     api_key: "API 密钥",
     api_key_placeholder: "输入您的 API 密钥",
+    api_key_clear: "清除",
+    api_key_clear_confirm: "确认清除",
+    api_key_required: "当前服务需要先填写 API 密钥才能翻译",
+    provider_reset: "恢复默认",
+    provider_reset_confirm: "确认恢复",
     deepl_hint: "在 deepl.com/pro-api 获取免费密钥",
+    libretranslate_hint: "自建服务器，仅当服务器要求时才需要填写 API 密钥",
+    deepl_formality: "语气",
+    formality_default: "默认",
+    formality_more: "更正式",
+    formality_less: "更随意",
+    formality_prefer_more: "尽量正式",
+    formality_prefer_less: "尽量随意",
+    deepl_preserve_formatting: "保留原文格式（标点和大小写）",
+    azure_hint: "API 地址填资源终结点，模型填部署名称",
     api_settings: "API 设置",
     api_base_url: "API 地址",
     model: "模型",
@@ -121,30 +504,311 @@ const TEXTS_ZH: Texts = Texts {
     prompt_name: "预设名称",
     prompt_system: "System 模板",
     prompt_user: "User 模板",
-    prompt_vars: "可用变量：{{target_lang_name}} {{target_lang_code}} {{text}}",
+    prompt_temperature: "温度（留空使用默认值）",
+    prompt_max_tokens: "最大输出 token 数（留空使用默认值）",
+    prompt_response_json: "JSON 格式回答，译文字段：",
+    prompt_vars: "可用变量：{{text}} {{target_lang_name}} {{target_lang_code}} {{source_lang_name}} {{source_lang_code}} {{app_name}} {{datetime}}",
+    prompt_missing_text: "用户模板中没有 {{text}}，原文会被接在模板后面",
+    prompt_missing_json: "要求 JSON 回答时，系统或用户模板里必须出现“JSON”，否则 OpenAI 会拒绝请求",
+    prompt_examples: "示例（在原文之前发送）",
+    prompt_example_source: "原文",
+    prompt_example_target: "译文",
     cancel: "关闭",
     save: "保存",
     ui_language: "界面语言",
+    recenter_settings: "每次打开时居中显示此窗口",
+    theme: "主题",
+    theme_system: "跟随系统",
+    theme_light: "浅色",
+    theme_dark: "深色",
     hotkey_log_title: "本地日志",
     hotkey_log_enable: "启用热键日志",
     hotkey_log_hint: "仅写入本地调试日志，不会上报",
+    history_enable: "在本地保存翻译历史",
+    popup_title: "弹窗",
+    auto_expand_long: "译文较长时直接在大窗口中查看",
+    preview_long: "选中内容较长时先翻译开头预览",
+    popup_size: "弹窗尺寸（宽 x 高，逻辑像素）",
+    popup_no_activate: "弹窗出现时不抢占当前应用的焦点",
+    popup_always_on_top: "弹窗始终置顶",
+    dismiss_on_focus_loss: "失去焦点时关闭弹窗",
+    auto_close: "闲置多少秒后关闭（留空不关闭）",
+    auto_copy_result: "翻译完成后自动复制到剪贴板",
+    stream_responses: "OpenAI 兼容接口边生成边显示译文",
+    show_raw_response: "在弹窗中显示服务返回的原始响应",
+    network_title: "网络",
+    advanced_title: "高级",
+    backup_title: "导入 / 导出",
+    export_api_keys: "包含 API 密钥（以明文写入）",
+    settings_export: "导出设置...",
+    settings_import: "导入设置...",
+    settings_export_done: "设置已导出到 {path}",
+    settings_import_done: "已导入：{providers} 个服务商、{presets} 个预设新增或更新",
+    reset_defaults: "恢复默认",
+    reset_defaults_confirm: "确认？",
+    reset_defaults_warning: "再点一次恢复默认设置，自定义服务商、提示词预设和所有 API 密钥都会被删除",
+    reset_defaults_done: "已恢复全部默认设置",
+    input_delays: "复制等待、粘贴等待、粘贴前等待焦点返回和按键间隔（毫秒），经常取不到选中内容时调大",
+    retry_settings: "超时 / 429 / 5xx 时的重试次数和首次等待毫秒数（0 次即关闭）",
+    cache_capacity: "缓存最近的翻译条数（0 即关闭）",
+    max_requests_per_hour: "每小时最多请求次数（留空不限制）",
+    max_chunk_chars: "大模型输入超过该字数时分块翻译（0 即关闭）",
+    proxy_url: "代理地址（留空使用系统代理）",
+    retry_empty_result: "返回空结果时自动重试一次",
+    request_timeout: "请求超时（秒）",
+    org_project: "组织 / 项目 ID（可选）",
+    extra_headers: "自定义请求头（可选）",
+    header_name: "名称",
+    header_value: "值",
+    auto_pick_title: "自动择优",
+    auto_pick_enable: "同时用第二个服务翻译，显示更好的结果",
+    auto_pick_partner: "第二个服务",
+    auto_pick_strategy: "优先",
+    pick_paragraphs: "段落数一致",
+    pick_length: "长度接近",
+    show_alternative: "查看 {provider} 的结果",
+    raw_response: "原始响应",
+    batch_title: "批量翻译",
+    glossary_title: "术语表",
+    skip_title: "跳过翻译",
+    skip_hint: "每行一个正则，需匹配整个选中内容",
+    notify_skipped: "跳过时显示提示",
+    selection_skipped: "未翻译：选中内容符合跳过规则",
+    glossary_hint: "每行一条：原文 = 译文（整词匹配，不区分大小写）",
+    batch_translate: "按分隔符拆分后逐条翻译",
+    batch_delimiter: "分隔符（\\n 表示换行）",
+    multi_target_title: "多语言",
+    multi_target: "同时翻译成下面所有语言（此模式下不能应用）",
+    target_langs: "语言代码",
+    secondary_lang: "第二语言（选中内容已是目标语言时翻译成它）",
+    price_per_1k: "每 1K tokens 价格，输入 / 输出（可选）",
+    usage_estimate: "约 {tokens} tokens（估算）",
+    usage_estimate_cost: "约 {tokens} tokens · ${cost}（估算）",
+    usage_reported: "{tokens} tokens",
+    usage_reported_cost: "{tokens} tokens · ${cost}",
+    usage_session: "{usage} · 启动以来 {total}",
+    prompt_import: "导入",
+    prompt_export: "导出",
+    prompt_export_done: "已导出 {count} 个预设到 {path}",
+    prompt_import_done: "新增 {added} 个，更新 {updated} 个",
+    prompt_import_issues: "已跳过：{issues}",
+    prompt_io_failed: "失败：{error}",
+    provider_test: "测试",
+    provider_testing: "正在测试...",
+    provider_test_ok: "连接成功：hello -> {result}",
+    provider_test_failed: "失败：{error}",
+    fetch_models: "获取列表",
+    models_fetching: "正在获取模型...",
+    models_fetch_failed: "获取模型失败：{error}",
 
     translating: "翻译中...",
     copy: "复制",
     apply: "应用",
-    hint_apply: "点击结果或按回车应用",
+    hint_apply: "可以直接修改译文，再点击应用",
+    clipboard_error: "剪贴板被占用，请重试",
+    paste_failed: "翻译并粘贴失败",
+    recognizing_image: "正在识别剪贴板图片中的文字...",
+    no_text_in_image: "图片中没有识别到文字",
+    bullet_list: "列表",
+    expand: "展开",
+    insert_comment: "注释",
+    swap_direction: "反向翻译",
+    undo_swap: "撤销",
+    speak: "朗读",
+    stop_speaking: "停止",
+    copy_json: "JSON",
+    preview_label: "仅预览开头部分",
+    translate_full: "翻译全文",
+    review_source: "原文",
+    review_translation: "译文",
+    retranslate: "重新翻译",
+    close: "关闭",
+
+    history_title: "翻译历史",
+    history_empty: "暂无记录，可在设置中开启翻译历史",
+    history_clear: "清空历史",
 
     tray_settings: "设置",
     tray_exit: "退出",
 };
 
+const TEXTS_JA: Texts = Texts {
+    settings_title: "設定",
+    global_hotkey: "グローバルホットキー",
+    hotkey_placeholder: "クリックしてキーを押してください...",
+    hotkey_recording: "ホットキーを押してください...",
+    hotkey_register_failed: "{hotkey} を登録できませんでした。他のアプリが使用している可能性があります。{previous} のままにします。（{error}）",
+    hotkey_reserved: "{hotkey} はシステムのショートカットです。{previous} のままにします。",
+    send_modifier: "コピー/貼り付けキー",
+    send_modifier_auto: "自動（macOS は Cmd、その他は Ctrl）",
+    translation_provider: "翻訳サービス",
+    fallback_providers: "予備サービス",
+    provider_settings: "サービス設定",
+    provider_type: "サービスの種類",
+    provider_add: "追加",
+    provider_delete: "削除",
+    google_no_config: "Google 翻訳 - 設定不要",
+    google_smart_join: "分割した文の間の空白と改行を保持する",
+    deepl_settings: "DeepL 設定",
+    api_key: "API キー",
+    api_key_placeholder: "API キーを入力",
+    api_key_clear: "消去",
+    api_key_clear_confirm: "消去しますか？",
+    api_key_required: "このサービスで翻訳するには API キーが必要です",
+    provider_reset: "初期値に戻す",
+    provider_reset_confirm: "戻しますか？",
+    deepl_hint: "無料の API キーは deepl.com/pro-api で取得できます",
+    libretranslate_hint: "セルフホストのサーバー。API キーはサーバーが要求する場合のみ必要です",
+    deepl_formality: "敬語",
+    formality_default: "デフォルト",
+    formality_more: "丁寧",
+    formality_less: "くだけた",
+    formality_prefer_more: "なるべく丁寧",
+    formality_prefer_less: "なるべくくだけた",
+    deepl_preserve_formatting: "書式を保持（句読点と大文字小文字）",
+    azure_hint: "API ベース URL にはリソースのエンドポイント、モデルにはデプロイ名を入力します",
+    api_settings: "API 設定",
+    api_base_url: "API ベース URL",
+    model: "モデル",
+    model_placeholder: "例: gpt-4o-mini",
+    prompt_settings: "プロンプト設定",
+    prompt_preset: "プリセット",
+    prompt_add: "追加",
+    prompt_delete: "削除",
+    prompt_name: "プリセット名",
+    prompt_system: "システムテンプレート",
+    prompt_user: "ユーザーテンプレート",
+    prompt_temperature: "Temperature（空欄 = 既定値）",
+    prompt_max_tokens: "最大出力トークン数（空欄 = 既定値）",
+    prompt_response_json: "JSON 形式で回答、訳文フィールド：",
+    prompt_vars: "変数: {{text}} {{target_lang_name}} {{target_lang_code}} {{source_lang_name}} {{source_lang_code}} {{app_name}} {{datetime}}",
+    prompt_missing_text: "ユーザーテンプレートに {{text}} がありません。原文はテンプレートの後ろに追加されます",
+    prompt_missing_json: "JSON で回答させる場合、システムまたはユーザーテンプレートに「JSON」と書く必要があります。書かないと OpenAI がリクエストを拒否します",
+    prompt_examples: "例（本文の前に送信）",
+    prompt_example_source: "原文",
+    prompt_example_target: "訳文",
+    cancel: "閉じる",
+    save: "保存",
+    ui_language: "表示言語",
+    recenter_settings: "開くたびにこのウィンドウを中央に表示する",
+    theme: "テーマ",
+    theme_system: "システムに合わせる",
+    theme_light: "ライト",
+    theme_dark: "ダーク",
+    hotkey_log_title: "ローカルログ",
+    hotkey_log_enable: "ホットキーログを有効にする",
+    hotkey_log_hint: "デバッグログはローカルファイルにのみ書き込まれ、送信されません",
+    history_enable: "翻訳履歴をローカルに保存する",
+    popup_title: "ポップアップ",
+    auto_expand_long: "長い訳文はレビューウィンドウで開く",
+    preview_long: "長い選択範囲は先頭だけ翻訳してプレビューする",
+    popup_size: "ポップアップのサイズ（幅 x 高さ、論理ピクセル）",
+    popup_no_activate: "ポップアップ表示時に現在のアプリからフォーカスを奪わない",
+    popup_always_on_top: "ポップアップを常に最前面に表示する",
+    dismiss_on_focus_loss: "フォーカスが外れたらポップアップを閉じる",
+    auto_close: "操作がない時に閉じるまでの秒数（空欄で無効）",
+    auto_copy_result: "訳文を自動的にクリップボードへコピーする",
+    stream_responses: "OpenAI 互換サービスの出力を生成中から表示する",
+    show_raw_response: "サービスの生のレスポンスをポップアップに表示する",
+    network_title: "ネットワーク",
+    advanced_title: "詳細",
+    backup_title: "インポート / エクスポート",
+    export_api_keys: "API キーを含める（平文で書き出されます）",
+    settings_export: "設定をエクスポート...",
+    settings_import: "設定をインポート...",
+    settings_export_done: "設定を {path} にエクスポートしました",
+    settings_import_done: "インポート完了：プロバイダー {providers} 件、プリセット {presets} 件を追加または更新",
+    reset_defaults: "初期化",
+    reset_defaults_confirm: "確認？",
+    reset_defaults_warning: "もう一度クリックすると初期設定に戻します。カスタムプロバイダー、プロンプトプリセット、すべての API キーが削除されます",
+    reset_defaults_done: "すべての設定を初期値に戻しました",
+    input_delays: "コピー待ち・貼り付け待ち・貼り付け前のフォーカス待ち・キー間隔（ms）。選択範囲の取得に失敗しやすい場合は増やしてください",
+    retry_settings: "タイムアウト / 429 / 5xx 時の再試行回数と初回待ち時間（ms、0 回 = 無効）",
+    cache_capacity: "キャッシュする最近の翻訳数（0 = 無効）",
+    max_requests_per_hour: "1 時間あたりの最大リクエスト数（空欄で無制限）",
+    max_chunk_chars: "LLM への入力を分割する文字数（0 = 無効）",
+    proxy_url: "プロキシ URL（空欄 = システムのプロキシ）",
+    retry_empty_result: "結果が空のときに 1 回再試行する",
+    request_timeout: "リクエストのタイムアウト（秒）",
+    org_project: "組織 / プロジェクト ID（任意）",
+    extra_headers: "カスタムヘッダー（任意）",
+    header_name: "名前",
+    header_value: "値",
+    auto_pick_title: "自動選択",
+    auto_pick_enable: "2 つ目のサービスでも翻訳し、良い方を表示する",
+    auto_pick_partner: "2 つ目のサービス",
+    auto_pick_strategy: "優先",
+    pick_paragraphs: "段落数が一致する方",
+    pick_length: "長さが近い方",
+    show_alternative: "{provider} の結果を表示",
+    raw_response: "生のレスポンス",
+    batch_title: "一括翻訳",
+    glossary_title: "用語集",
+    skip_title: "翻訳しない選択範囲",
+    skip_hint: "1 行に 1 つの正規表現。選択範囲全体と照合します",
+    notify_skipped: "翻訳をスキップしたときに通知する",
+    selection_skipped: "翻訳しませんでした：選択範囲がスキップ条件に一致します",
+    glossary_hint: "1 行に 1 語：原語 = 訳語（単語単位、大文字小文字を区別しない）",
+    batch_translate: "区切り文字で分けた項目を 1 つずつ翻訳する",
+    batch_delimiter: "区切り文字（\\n = 改行）",
+    multi_target_title: "複数言語",
+    multi_target: "以下のすべての言語に同時に翻訳する（適用は使えません）",
+    target_langs: "言語コード",
+    secondary_lang: "第 2 言語（選択内容がすでに翻訳先の言語のときに使用）",
+    price_per_1k: "1K トークンあたりの価格、入力 / 出力（任意）",
+    usage_estimate: "約 {tokens} トークン（推定）",
+    usage_estimate_cost: "約 {tokens} トークン · ${cost}（推定）",
+    usage_reported: "{tokens} トークン",
+    usage_reported_cost: "{tokens} トークン · ${cost}",
+    usage_session: "{usage} · 起動後の合計 {total}",
+    prompt_import: "インポート",
+    prompt_export: "エクスポート",
+    prompt_export_done: "{count} 件のプリセットを {path} にエクスポートしました",
+    prompt_import_done: "新規 {added} 件、更新 {updated} 件をインポートしました",
+    prompt_import_issues: "スキップ：{issues}",
+    prompt_io_failed: "失敗：{error}",
+    provider_test: "テスト",
+    provider_testing: "テスト中...",
+    provider_test_ok: "接続成功：hello -> {result}",
+    provider_test_failed: "失敗：{error}",
+    fetch_models: "一覧を取得",
+    models_fetching: "モデルを取得中...",
+    models_fetch_failed: "モデルを取得できません：{error}",
+
+    translating: "翻訳中...",
+    copy: "コピー",
+    apply: "適用",
+    hint_apply: "必要なら訳文を編集してから適用してください",
+    clipboard_error: "クリップボードが使用中です。もう一度お試しください",
+    paste_failed: "翻訳して貼り付けできませんでした",
+    recognizing_image: "クリップボードの画像から文字を読み取っています...",
+    no_text_in_image: "画像に文字が見つかりません",
+    bullet_list: "リスト",
+    expand: "拡大",
+    insert_comment: "コメント",
+    swap_direction: "逆翻訳",
+    undo_swap: "元に戻す",
+    speak: "読み上げ",
+    stop_speaking: "停止",
+    copy_json: "JSON",
+    preview_label: "先頭部分のみのプレビュー",
+    translate_full: "全文を翻訳",
+    review_source: "原文",
+    review_translation: "訳文",
+    retranslate: "再翻訳",
+    close: "閉じる",
+
+    history_title: "翻訳履歴",
+    history_empty: "まだ記録がありません。設定で翻訳履歴を有効にしてください",
+    history_clear: "履歴を消去",
+
+    tray_settings: "設定",
+    tray_exit: "終了",
+};
+
 /// Initialize language from config
 pub fn init(ui_lang: &UILanguage) {
-    let lang = match ui_lang {
-        UILanguage::En => Lang::En,
-        UILanguage::Zh => Lang::Zh,
-        UILanguage::Auto => detect_system_language(),
-    };
+    let lang = Lang::from_ui(ui_lang).unwrap_or_else(detect_system_language);
     set_language(lang);
 }
 
@@ -158,6 +822,10 @@ fn detect_system_language() -> Lang {
         if lang_id == 0x0804 || lang_id == 0x0404 || (lang_id & 0xFF) == 0x04 {
             return Lang::Zh;
         }
+        // Japanese: 0x0411
+        if (lang_id & 0xFF) == 0x11 {
+            return Lang::Ja;
+        }
     }
 
     #[cfg(target_os = "macos")]
@@ -174,15 +842,23 @@ fn detect_system_language() -> Lang {
             let languages = CFArray::<CFString>::wrap_under_create_rule(CFLocaleCopyPreferredLanguages());
             if languages.len() > 0 {
                 if let Some(lang) = languages.get(0) {
-                    let lang_str = lang.to_string();
-                    if lang_str.starts_with("zh") {
-                        return Lang::Zh;
+                    if let Some(lang) = Lang::from_locale(&lang.to_string()) {
+                        return lang;
                     }
                 }
             }
         }
     }
 
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        for var in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+            if let Some(lang) = std::env::var(var).ok().as_deref().and_then(Lang::from_locale) {
+                return lang;
+            }
+        }
+    }
+
     Lang::En
 }
 
@@ -191,6 +867,64 @@ pub fn set_language(lang: Lang) {
     if let Ok(mut current) = CURRENT_LANG.write() {
         *current = lang;
     }
+    if let Ok(mut texts) = CURRENT_TEXTS.write() {
+        *texts = lang.texts();
+    }
+}
+
+/// Use `locales/<code>.json` from the config dir on top of the built-in strings, call at startup
+/// and again whenever the whole config is replaced (import, reset).
+/// Keys missing from the file keep the built-in table for `code` (English for unknown codes).
+/// Picking a language in settings goes back to the built-in tables
+pub fn load_locale(code: &str) {
+    let code = code.trim();
+    if code.is_empty() {
+        return;
+    }
+    let path = match Config::config_path() {
+        Ok(path) => path.with_file_name(LOCALES_DIR).join(format!("{}.json", code)),
+        Err(e) => {
+            error!("找不到语言文件目录: {}", e);
+            return;
+        }
+    };
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            error!("读取语言文件失败({}): {}", path.display(), e);
+            return;
+        }
+    };
+    let lang = Lang::from_locale(code).unwrap_or(Lang::En);
+    match merge_locale(lang.texts(), &content) {
+        Ok((texts, unknown)) => {
+            if !unknown.is_empty() {
+                warn!("语言文件中有未知的键: {}", unknown.join(", "));
+            }
+            if let Ok(mut current) = CURRENT_TEXTS.write() {
+                // 只在启动和整体替换配置时加载，泄漏的字符串跟进程同寿命
+                *current = Box::leak(Box::new(texts));
+            }
+        }
+        Err(e) => error!("解析语言文件失败({}): {}", path.display(), e),
+    }
+}
+
+/// `base` with the string values of a locale file applied, plus the keys `Texts` doesn't have
+fn merge_locale(base: &Texts, content: &str) -> Result<(Texts, Vec<String>)> {
+    let entries: HashMap<String, serde_json::Value> = serde_json::from_str(content)?;
+    let mut texts = *base;
+    let mut unknown = Vec::new();
+    for (key, value) in entries {
+        // 非字符串的值直接忽略，保留内置文本
+        let Some(value) = value.as_str() else { continue; };
+        let value: &'static str = Box::leak(value.to_string().into_boxed_str());
+        if !texts.set(&key, value) {
+            unknown.push(key);
+        }
+    }
+    unknown.sort();
+    Ok((texts, unknown))
 }
 
 /// Get current language
@@ -200,26 +934,76 @@ pub fn current_language() -> Lang {
 
 /// Get translated texts for current language
 pub fn t() -> &'static Texts {
-    match current_language() {
-        Lang::En => &TEXTS_EN,
-        Lang::Zh => &TEXTS_ZH,
-    }
+    CURRENT_TEXTS.read().map(|t| *t).unwrap_or(&TEXTS_EN)
+}
+
+/// Entries of the settings language list: "Auto", then `Lang::ALL`
+pub fn language_names() -> Vec<&'static str> {
+    std::iter::once("Auto").chain(Lang::ALL.iter().map(|lang| lang.native_name())).collect()
 }
 
-/// Get language index for UI (0=Auto, 1=English, 2=Chinese)
+/// Get language index for UI (0=Auto, then `Lang::ALL` order)
 pub fn language_to_index(lang: &UILanguage) -> i32 {
-    match lang {
-        UILanguage::Auto => 0,
-        UILanguage::En => 1,
-        UILanguage::Zh => 2,
-    }
+    Lang::from_ui(lang)
+        .and_then(|lang| Lang::ALL.iter().position(|l| *l == lang))
+        .map_or(0, |i| i as i32 + 1)
 }
 
 /// Get UILanguage from index
 pub fn index_to_language(index: i32) -> UILanguage {
-    match index {
-        1 => UILanguage::En,
-        2 => UILanguage::Zh,
-        _ => UILanguage::Auto,
+    usize::try_from(index - 1)
+        .ok()
+        .and_then(|i| Lang::ALL.get(i))
+        .map_or(UILanguage::Auto, |lang| lang.to_ui())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_index_round_trip() {
+        for ui_lang in [UILanguage::Auto, UILanguage::En, UILanguage::Zh, UILanguage::Ja] {
+            assert_eq!(index_to_language(language_to_index(&ui_lang)), ui_lang);
+        }
+        assert_eq!(index_to_language(99), UILanguage::Auto);
+        assert_eq!(index_to_language(-1), UILanguage::Auto);
+        assert_eq!(language_names().len(), Lang::ALL.len() + 1);
+    }
+
+    #[test]
+    fn test_merge_locale() {
+        let (texts, unknown) = merge_locale(
+            &TEXTS_EN,
+            r#"{"copy": "Copier", "close": 3, "not_a_key": "x", "settings_title": "Paramètres"}"#,
+        )
+        .unwrap();
+        assert_eq!(texts.copy, "Copier");
+        assert_eq!(texts.settings_title, "Paramètres");
+        // 缺的和类型不对的都用内置英文
+        assert_eq!(texts.close, TEXTS_EN.close);
+        assert_eq!(texts.apply, TEXTS_EN.apply);
+        assert_eq!(unknown, vec!["not_a_key".to_string()]);
+
+        assert!(merge_locale(&TEXTS_EN, "[1, 2]").is_err());
+    }
+
+    #[test]
+    fn test_every_key_is_settable() {
+        let mut texts = TEXTS_EN;
+        for key in Texts::KEYS {
+            assert!(texts.set(key, "x"), "{}", key);
+        }
+        assert!(!texts.set("missing", "x"));
+    }
+
+    #[test]
+    fn test_from_locale() {
+        assert_eq!(Lang::from_locale("ja-JP"), Some(Lang::Ja));
+        assert_eq!(Lang::from_locale("zh_CN.UTF-8"), Some(Lang::Zh));
+        assert_eq!(Lang::from_locale("zh-Hant-TW"), Some(Lang::Zh));
+        assert_eq!(Lang::from_locale("EN_us"), Some(Lang::En));
+        assert_eq!(Lang::from_locale("fr_FR"), None);
+        assert_eq!(Lang::from_locale(""), None);
     }
 }
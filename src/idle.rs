@@ -0,0 +1,68 @@
+//! Idle detection for memory trimming
+//! Callers pass the current time in, so the trigger can be tested without waiting
+
+use std::time::{Duration, Instant};
+
+/// Last user activity and whether memory was already trimmed since then
+#[derive(Debug)]
+pub struct IdleTracker {
+    last_activity: Instant,
+    trimmed: bool,
+}
+
+impl IdleTracker {
+    pub fn new(now: Instant) -> Self {
+        Self { last_activity: now, trimmed: false }
+    }
+
+    /// Record a hotkey press or translation
+    pub fn touch(&mut self, now: Instant) {
+        self.last_activity = now;
+        self.trimmed = false;
+    }
+
+    /// True once per idle stretch after `idle_after` without activity, zero disables trimming
+    pub fn due(&self, now: Instant, idle_after: Duration) -> bool {
+        !self.trimmed
+            && !idle_after.is_zero()
+            && now.saturating_duration_since(self.last_activity) >= idle_after
+    }
+
+    pub fn mark_trimmed(&mut self) {
+        self.trimmed = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trim_due_after_idle_period_once() {
+        let start = Instant::now();
+        let idle = Duration::from_secs(30 * 60);
+        let mut tracker = IdleTracker::new(start);
+
+        assert!(!tracker.due(start + Duration::from_secs(29 * 60), idle));
+        assert!(tracker.due(start + idle, idle));
+
+        // 清理过一次后，在下次使用之前不再触发
+        tracker.mark_trimmed();
+        assert!(!tracker.due(start + idle * 3, idle));
+
+        // 重新使用后重新计时
+        let later = start + idle * 4;
+        tracker.touch(later);
+        assert!(!tracker.due(later + Duration::from_secs(60), idle));
+        assert!(tracker.due(later + idle, idle));
+    }
+
+    #[test]
+    fn test_zero_idle_period_disables_trim() {
+        let start = Instant::now();
+        let tracker = IdleTracker::new(start);
+        assert!(!tracker.due(start + Duration::from_secs(86_400), Duration::ZERO));
+        // 时钟回拨时不会溢出
+        assert!(!tracker.due(start, Duration::from_secs(1)));
+    }
+}
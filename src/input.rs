@@ -1,8 +1,9 @@
 //! Cross-platform keyboard input simulation and monitoring
 //! Windows: Uses SendInput API and Low-Level Keyboard Hook
 //! macOS: Uses CGEvent APIs
+//! Linux: Uses rdev (XTest for injection, XRecord for monitoring)
 
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Mutex;
 use std::thread;
 use std::time::Duration;
@@ -11,18 +12,25 @@ use std::fs::OpenOptions;
 use std::io::Write;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-const KEY_DELAY_MS: u64 = 10;
+use crate::config::SendModifier;
+#[cfg(target_os = "macos")]
+use crate::hotkey::HotkeyAction;
+
+static KEY_DELAY_MS: AtomicU64 = AtomicU64::new(10);
 
 static CTRL_V_DETECTED: AtomicBool = AtomicBool::new(false);
 static HOTKEY_CAPTURE_ACTIVE: AtomicBool = AtomicBool::new(false);
 static CAPTURED_HOTKEY: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
 static HOTKEY_LOG_ENABLED: AtomicBool = AtomicBool::new(false);
+static SEND_MODIFIER: Mutex<SendModifier> = Mutex::new(SendModifier::Auto);
 #[cfg(target_os = "macos")]
 static ACTIVE_HOTKEY: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
 #[cfg(target_os = "macos")]
+static EXTRA_HOTKEYS: Lazy<Mutex<Vec<(HotkeyAction, String)>>> = Lazy::new(|| Mutex::new(Vec::new()));
+#[cfg(target_os = "macos")]
 static HOTKEY_EVENT_CHANNEL: Lazy<(
-    crossbeam_channel::Sender<()>,
-    crossbeam_channel::Receiver<()>,
+    crossbeam_channel::Sender<HotkeyAction>,
+    crossbeam_channel::Receiver<HotkeyAction>,
 )> = Lazy::new(|| crossbeam_channel::unbounded());
 #[cfg(target_os = "macos")]
 static MONITOR_ERROR_CHANNEL: Lazy<(
@@ -54,8 +62,24 @@ pub fn set_active_hotkey(hotkey: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Bind an optional hotkey next to the translate one, an empty string turns it off
+#[cfg(target_os = "macos")]
+pub fn set_extra_hotkey(action: HotkeyAction, hotkey: &str) -> anyhow::Result<()> {
+    let normalized = if hotkey.trim().is_empty() {
+        None
+    } else {
+        Some(normalize_hotkey_string(hotkey)?)
+    };
+    let mut extras = EXTRA_HOTKEYS.lock().unwrap();
+    extras.retain(|(a, _)| *a != action);
+    if let Some(normalized) = normalized {
+        extras.push((action, normalized));
+    }
+    Ok(())
+}
+
 #[cfg(target_os = "macos")]
-pub fn hotkey_event_receiver() -> crossbeam_channel::Receiver<()> {
+pub fn hotkey_event_receiver() -> crossbeam_channel::Receiver<HotkeyAction> {
     HOTKEY_EVENT_CHANNEL.1.clone()
 }
 
@@ -72,7 +96,60 @@ pub fn set_hotkey_log_enabled(enabled: bool) {
     HOTKEY_LOG_ENABLED.store(enabled, Ordering::SeqCst);
 }
 
+pub fn set_send_modifier(modifier: SendModifier) {
+    *SEND_MODIFIER.lock().unwrap() = modifier;
+}
+
+pub fn set_key_event_delay_ms(delay_ms: u64) {
+    KEY_DELAY_MS.store(delay_ms, Ordering::SeqCst);
+}
+
+fn key_delay() -> Duration {
+    Duration::from_millis(KEY_DELAY_MS.load(Ordering::SeqCst))
+}
+
+/// Modifier actually pressed for copy/paste
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ComboModifier {
+    Ctrl,
+    Cmd,
+}
+
+/// Cmd only exists on macOS; elsewhere it falls back to Ctrl instead of pressing the Windows key
+fn resolve_modifier(setting: SendModifier, is_macos: bool) -> ComboModifier {
+    match setting {
+        SendModifier::Auto | SendModifier::Cmd if is_macos => ComboModifier::Cmd,
+        SendModifier::Auto | SendModifier::Ctrl | SendModifier::Cmd => ComboModifier::Ctrl,
+    }
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+fn current_modifier() -> ComboModifier {
+    resolve_modifier(*SEND_MODIFIER.lock().unwrap(), cfg!(target_os = "macos"))
+}
+
+/// Windows 按键序列：(虚拟键码, 是否抬起)
+#[cfg(any(target_os = "windows", test))]
+fn combo_key_sequence(modifier: ComboModifier, key_vk: u16) -> [(u16, bool); 4] {
+    let modifier_vk = match modifier {
+        ComboModifier::Ctrl => 0x11, // VK_CONTROL
+        ComboModifier::Cmd => 0x5B,  // VK_LWIN
+    };
+    [(modifier_vk, false), (key_vk, false), (key_vk, true), (modifier_vk, true)]
+}
+
+/// macOS CGEventFlags 位
+#[cfg(any(target_os = "macos", test))]
+fn combo_flag_bits(modifier: ComboModifier) -> u64 {
+    match modifier {
+        ComboModifier::Ctrl => 0x0004_0000, // kCGEventFlagMaskControl
+        ComboModifier::Cmd => 0x0010_0000,  // kCGEventFlagMaskCommand
+    }
+}
+
 fn log_hotkey(msg: &str) {
+    // 同时进入常规日志，NANOTRANS_LOG=hotkey=debug 即可看到
+    tracing::debug!(target: "hotkey", "{}", msg);
     if !HOTKEY_LOG_ENABLED.load(Ordering::SeqCst) {
         return;
     }
@@ -133,8 +210,9 @@ fn normalize_hotkey_string(hotkey: &str) -> anyhow::Result<String> {
     Ok(out)
 }
 
+/// Also used for a missing Accessibility permission, the alert covers both
 #[cfg(target_os = "macos")]
-fn report_keyboard_monitor_error(message: &str) {
+pub(crate) fn report_keyboard_monitor_error(message: &str) {
     if MONITOR_ERROR_REPORTED.swap(true, Ordering::SeqCst) {
         return;
     }
@@ -176,6 +254,54 @@ fn normalize_key_name(key: &str) -> Option<&'static str> {
     }
 }
 
+/// Layout-independent name for a Windows virtual-key code.
+/// Names round-trip through `hotkey::parse_key_code`; OEM keys use their
+/// US-layout position names since global-hotkey maps them back to the same VK.
+#[cfg(any(target_os = "windows", test))]
+fn vk_canonical_name(vk: u16) -> Option<&'static str> {
+    match vk {
+        0x41 => Some("A"), 0x42 => Some("B"), 0x43 => Some("C"), 0x44 => Some("D"),
+        0x45 => Some("E"), 0x46 => Some("F"), 0x47 => Some("G"), 0x48 => Some("H"),
+        0x49 => Some("I"), 0x4A => Some("J"), 0x4B => Some("K"), 0x4C => Some("L"),
+        0x4D => Some("M"), 0x4E => Some("N"), 0x4F => Some("O"), 0x50 => Some("P"),
+        0x51 => Some("Q"), 0x52 => Some("R"), 0x53 => Some("S"), 0x54 => Some("T"),
+        0x55 => Some("U"), 0x56 => Some("V"), 0x57 => Some("W"), 0x58 => Some("X"),
+        0x59 => Some("Y"), 0x5A => Some("Z"),
+        0x30 => Some("0"), 0x31 => Some("1"), 0x32 => Some("2"), 0x33 => Some("3"),
+        0x34 => Some("4"), 0x35 => Some("5"), 0x36 => Some("6"), 0x37 => Some("7"),
+        0x38 => Some("8"), 0x39 => Some("9"),
+        0x70 => Some("F1"), 0x71 => Some("F2"), 0x72 => Some("F3"), 0x73 => Some("F4"),
+        0x74 => Some("F5"), 0x75 => Some("F6"), 0x76 => Some("F7"), 0x77 => Some("F8"),
+        0x78 => Some("F9"), 0x79 => Some("F10"), 0x7A => Some("F11"), 0x7B => Some("F12"),
+        0x20 => Some("Space"), 0x0D => Some("Enter"), 0x09 => Some("Tab"),
+        0x08 => Some("Backspace"), 0x2E => Some("Delete"), 0x2D => Some("Insert"),
+        0x24 => Some("Home"), 0x23 => Some("End"), 0x21 => Some("PageUp"),
+        0x22 => Some("PageDown"), 0x25 => Some("Left"), 0x26 => Some("Up"),
+        0x27 => Some("Right"), 0x28 => Some("Down"),
+        0xBA => Some("Semicolon"), 0xBB => Some("Equal"), 0xBC => Some("Comma"),
+        0xBD => Some("Minus"), 0xBE => Some("Period"), 0xBF => Some("Slash"),
+        0xC0 => Some("Backquote"), 0xDB => Some("BracketLeft"), 0xDC => Some("Backslash"),
+        0xDD => Some("BracketRight"), 0xDE => Some("Quote"),
+        0x60 => Some("Numpad0"), 0x61 => Some("Numpad1"), 0x62 => Some("Numpad2"),
+        0x63 => Some("Numpad3"), 0x64 => Some("Numpad4"), 0x65 => Some("Numpad5"),
+        0x66 => Some("Numpad6"), 0x67 => Some("Numpad7"), 0x68 => Some("Numpad8"),
+        0x69 => Some("Numpad9"),
+        _ => None,
+    }
+}
+
+/// Name stored for a captured key. `layout_label` is what the active layout
+/// calls the key; it is never stored. Keys outside the table can't be registered
+/// as a hotkey, so they return None and capture keeps waiting.
+#[cfg(any(target_os = "windows", test))]
+fn captured_key_name(vk: u16, layout_label: Option<&str>) -> Option<String> {
+    if let Some(name) = vk_canonical_name(vk) {
+        return Some(name.to_string());
+    }
+    log_hotkey(&format!("ignored VK{:02X} (layout label {}), not usable in a hotkey", vk, layout_label.unwrap_or("-")));
+    None
+}
+
 // Windows 实现
 #[cfg(target_os = "windows")]
 mod platform_impl {
@@ -220,7 +346,7 @@ mod platform_impl {
                     time: 0,
                     dwExtraInfo: 0,
                 };
-                let name = vk_to_name(&fake_kb).unwrap_or_else(|| format!("VK{:02X}", vk));
+                let Some(name) = vk_to_name(&fake_kb) else { continue };
                 let mut hotkey = String::new();
                 if has_ctrl { hotkey.push_str("Ctrl+"); }
                 if has_alt { hotkey.push_str("Alt+"); }
@@ -247,15 +373,20 @@ mod platform_impl {
         0x41,0x42,0x43,0x44,0x45,0x46,0x47,0x48,0x49,0x4A,0x4B,0x4C,0x4D,0x4E,0x4F,0x50,0x51,0x52,0x53,0x54,0x55,0x56,0x57,0x58,0x59,0x5A,
         0x70,0x71,0x72,0x73,0x74,0x75,0x76,0x77,0x78,0x79,0x7A,0x7B,
         0x20,0x0D,0x09,0x08,0x2E,0x2D,0x24,0x23,0x21,0x22,0x25,0x26,0x27,0x28,
+        0xBA,0xBB,0xBC,0xBD,0xBE,0xBF,0xC0,0xDB,0xDC,0xDD,0xDE,
+        0x60,0x61,0x62,0x63,0x64,0x65,0x66,0x67,0x68,0x69,
     ];
 
     fn vk_to_name(kb: &KBDLLHOOKSTRUCT) -> Option<String> {
         let vk = kb.vkCode as u16;
-        if let Some(name) = common_key_name(vk) {
-            return Some(name.to_string());
-        }
+        // 表内的按键不查系统名称，避免拿到随键盘布局变化的本地化标签
+        let label = if super::vk_canonical_name(vk).is_none() { layout_key_label(kb) } else { None };
+        super::captured_key_name(vk, label.as_deref())
+    }
 
-        let scan_code = unsafe { MapVirtualKeyW(vk.into(), MAPVK_VK_TO_VSC) };
+    /// Localized key label from the active layout, only used for logging
+    fn layout_key_label(kb: &KBDLLHOOKSTRUCT) -> Option<String> {
+        let scan_code = unsafe { MapVirtualKeyW(kb.vkCode, MAPVK_VK_TO_VSC) };
         if scan_code == 0 {
             return None;
         }
@@ -267,40 +398,13 @@ mod platform_impl {
 
         let mut buffer = [0u16; 64];
         let len = unsafe { GetKeyNameTextW(lparam, &mut buffer) };
-        if len > 0 {
-            let mut s = String::from_utf16_lossy(&buffer[..len as usize]);
-            s.retain(|c| c != '\u{0}');
-            let trimmed = s.trim();
-            if !trimmed.is_empty() {
-                return Some(trimmed.to_string());
-            }
-        }
-
-        Some(format!("VK{:02X}", vk))
-    }
-
-    fn common_key_name(vk: u16) -> Option<&'static str> {
-        match vk {
-            0x41 => Some("A"), 0x42 => Some("B"), 0x43 => Some("C"), 0x44 => Some("D"),
-            0x45 => Some("E"), 0x46 => Some("F"), 0x47 => Some("G"), 0x48 => Some("H"),
-            0x49 => Some("I"), 0x4A => Some("J"), 0x4B => Some("K"), 0x4C => Some("L"),
-            0x4D => Some("M"), 0x4E => Some("N"), 0x4F => Some("O"), 0x50 => Some("P"),
-            0x51 => Some("Q"), 0x52 => Some("R"), 0x53 => Some("S"), 0x54 => Some("T"),
-            0x55 => Some("U"), 0x56 => Some("V"), 0x57 => Some("W"), 0x58 => Some("X"),
-            0x59 => Some("Y"), 0x5A => Some("Z"),
-            0x30 => Some("0"), 0x31 => Some("1"), 0x32 => Some("2"), 0x33 => Some("3"),
-            0x34 => Some("4"), 0x35 => Some("5"), 0x36 => Some("6"), 0x37 => Some("7"),
-            0x38 => Some("8"), 0x39 => Some("9"),
-            0x70 => Some("F1"), 0x71 => Some("F2"), 0x72 => Some("F3"), 0x73 => Some("F4"),
-            0x74 => Some("F5"), 0x75 => Some("F6"), 0x76 => Some("F7"), 0x77 => Some("F8"),
-            0x78 => Some("F9"), 0x79 => Some("F10"), 0x7A => Some("F11"), 0x7B => Some("F12"),
-            0x20 => Some("Space"), 0x0D => Some("Enter"), 0x09 => Some("Tab"),
-            0x08 => Some("Backspace"), 0x2E => Some("Delete"), 0x2D => Some("Insert"),
-            0x24 => Some("Home"), 0x23 => Some("End"), 0x21 => Some("PageUp"),
-            0x22 => Some("PageDown"), 0x25 => Some("Left"), 0x26 => Some("Up"),
-            0x27 => Some("Right"), 0x28 => Some("Down"),
-            _ => None,
+        if len <= 0 {
+            return None;
         }
+        let mut s = String::from_utf16_lossy(&buffer[..len as usize]);
+        s.retain(|c| c != '\u{0}');
+        let trimmed = s.trim();
+        (!trimmed.is_empty()).then(|| trimmed.to_string())
     }
 
     fn is_modifier_key(vk: u16) -> bool {
@@ -437,26 +541,21 @@ mod platform_impl {
         }
     }
 
-    pub fn send_ctrl_c() {
-        let inputs = [
-            create_key_input(VK_CONTROL, KEYBD_EVENT_FLAGS(0)),
-            create_key_input(VK_C, KEYBD_EVENT_FLAGS(0)),
-            create_key_input(VK_C, KEYEVENTF_KEYUP),
-            create_key_input(VK_CONTROL, KEYEVENTF_KEYUP),
-        ];
-        send_inputs(&inputs);
-        thread::sleep(Duration::from_millis(KEY_DELAY_MS));
+    pub fn send_copy() {
+        send_key_combo(VK_C);
     }
 
-    pub fn send_ctrl_v() {
-        let inputs = [
-            create_key_input(VK_CONTROL, KEYBD_EVENT_FLAGS(0)),
-            create_key_input(VK_V, KEYBD_EVENT_FLAGS(0)),
-            create_key_input(VK_V, KEYEVENTF_KEYUP),
-            create_key_input(VK_CONTROL, KEYEVENTF_KEYUP),
-        ];
+    pub fn send_paste() {
+        send_key_combo(VK_V);
+    }
+
+    fn send_key_combo(key: VIRTUAL_KEY) {
+        let inputs = combo_key_sequence(current_modifier(), key.0).map(|(vk, up)| {
+            let flags = if up { KEYEVENTF_KEYUP } else { KEYBD_EVENT_FLAGS(0) };
+            create_key_input(VIRTUAL_KEY(vk), flags)
+        });
         send_inputs(&inputs);
-        thread::sleep(Duration::from_millis(KEY_DELAY_MS));
+        thread::sleep(key_delay());
     }
 }
 
@@ -592,25 +691,26 @@ mod platform_impl {
                             }
                         }
                     } else {
-                        let active = super::ACTIVE_HOTKEY.lock().unwrap();
-                        if let Some(active_hotkey) = active.as_deref() {
-                            if !is_modifier_key(keycode) {
-                                let has_cmd = flags.contains(CGEventFlags::CGEventFlagCommand);
-                                let has_ctrl = flags.contains(CGEventFlags::CGEventFlagControl);
-                                let has_alt = flags.contains(CGEventFlags::CGEventFlagAlternate);
-                                let has_shift = flags.contains(CGEventFlags::CGEventFlagShift);
-
-                                if has_cmd || has_ctrl || has_alt || has_shift {
-                                    if let Some(key_name) = keycode_to_name(keycode) {
-                                        let mut hotkey = String::new();
-                                        if has_cmd { hotkey.push_str("Cmd+"); }
-                                        if has_ctrl { hotkey.push_str("Ctrl+"); }
-                                        if has_alt { hotkey.push_str("Alt+"); }
-                                        if has_shift { hotkey.push_str("Shift+"); }
-                                        hotkey.push_str(key_name);
-                                        if hotkey == active_hotkey {
-                                            let _ = super::HOTKEY_EVENT_CHANNEL.0.send(());
-                                        }
+                        let active = super::ACTIVE_HOTKEY.lock().unwrap().clone();
+                        let extras = super::EXTRA_HOTKEYS.lock().unwrap().clone();
+                        if (active.is_some() || !extras.is_empty()) && !is_modifier_key(keycode) {
+                            let has_cmd = flags.contains(CGEventFlags::CGEventFlagCommand);
+                            let has_ctrl = flags.contains(CGEventFlags::CGEventFlagControl);
+                            let has_alt = flags.contains(CGEventFlags::CGEventFlagAlternate);
+                            let has_shift = flags.contains(CGEventFlags::CGEventFlagShift);
+
+                            if has_cmd || has_ctrl || has_alt || has_shift {
+                                if let Some(key_name) = keycode_to_name(keycode) {
+                                    let mut hotkey = String::new();
+                                    if has_cmd { hotkey.push_str("Cmd+"); }
+                                    if has_ctrl { hotkey.push_str("Ctrl+"); }
+                                    if has_alt { hotkey.push_str("Alt+"); }
+                                    if has_shift { hotkey.push_str("Shift+"); }
+                                    hotkey.push_str(key_name);
+                                    if active.as_deref() == Some(hotkey.as_str()) {
+                                        let _ = super::HOTKEY_EVENT_CHANNEL.0.send(HotkeyAction::Translate);
+                                    } else if let Some((action, _)) = extras.iter().find(|(_, h)| *h == hotkey) {
+                                        let _ = super::HOTKEY_EVENT_CHANNEL.0.send(*action);
                                     }
                                 }
                             }
@@ -657,12 +757,16 @@ mod platform_impl {
         });
     }
 
-    pub fn send_ctrl_c() {
-        send_key_combo(8, CGEventFlags::CGEventFlagCommand);
+    pub fn send_copy() {
+        send_key_combo(8, combo_flags());
+    }
+
+    pub fn send_paste() {
+        send_key_combo(9, combo_flags());
     }
 
-    pub fn send_ctrl_v() {
-        send_key_combo(9, CGEventFlags::CGEventFlagCommand);
+    fn combo_flags() -> CGEventFlags {
+        CGEventFlags::from_bits_truncate(combo_flag_bits(current_modifier()))
     }
 
     fn send_key_combo(keycode: u16, flags: CGEventFlags) {
@@ -672,13 +776,157 @@ mod platform_impl {
                 event_down.post(CGEventTapLocation::HID);
             }
 
-            thread::sleep(Duration::from_millis(KEY_DELAY_MS));
+            thread::sleep(key_delay());
 
             if let Ok(event_up) = CGEvent::new_keyboard_event(source, keycode, false) {
                 event_up.post(CGEventTapLocation::HID);
             }
 
-            thread::sleep(Duration::from_millis(KEY_DELAY_MS));
+            thread::sleep(key_delay());
+        }
+    }
+}
+
+// Linux 实现，依赖 X11（Wayland 下通过 XWayland 生效）
+#[cfg(target_os = "linux")]
+mod platform_impl {
+    use super::*;
+    use rdev::{EventType, Key};
+
+    static CTRL_PRESSED: AtomicBool = AtomicBool::new(false);
+    static ALT_PRESSED: AtomicBool = AtomicBool::new(false);
+    static SHIFT_PRESSED: AtomicBool = AtomicBool::new(false);
+    static SUPER_PRESSED: AtomicBool = AtomicBool::new(false);
+
+    /// XRecord 回调里已经能拿到按键，这里不需要轮询
+    pub fn poll_hotkey_capture() -> Option<String> {
+        None
+    }
+
+    pub fn start_keyboard_monitor() {
+        thread::spawn(|| {
+            if let Err(e) = rdev::listen(handle_event) {
+                super::log_hotkey(&format!("keyboard listen failed: {:?}", e));
+            }
+        });
+    }
+
+    fn handle_event(event: rdev::Event) {
+        let (key, is_down) = match event.event_type {
+            EventType::KeyPress(key) => (key, true),
+            EventType::KeyRelease(key) => (key, false),
+            _ => return,
+        };
+
+        match key {
+            Key::ControlLeft | Key::ControlRight => CTRL_PRESSED.store(is_down, Ordering::SeqCst),
+            Key::Alt | Key::AltGr => ALT_PRESSED.store(is_down, Ordering::SeqCst),
+            Key::ShiftLeft | Key::ShiftRight => SHIFT_PRESSED.store(is_down, Ordering::SeqCst),
+            Key::MetaLeft | Key::MetaRight => SUPER_PRESSED.store(is_down, Ordering::SeqCst),
+            _ if is_down => on_key_down(key),
+            _ => {}
+        }
+    }
+
+    fn on_key_down(key: Key) {
+        let has_ctrl = CTRL_PRESSED.load(Ordering::SeqCst);
+        let has_alt = ALT_PRESSED.load(Ordering::SeqCst);
+        let has_shift = SHIFT_PRESSED.load(Ordering::SeqCst);
+        let has_super = SUPER_PRESSED.load(Ordering::SeqCst);
+
+        if key == Key::KeyV && (has_ctrl || has_super) {
+            super::CTRL_V_DETECTED.store(true, Ordering::SeqCst);
+        }
+
+        if !super::HOTKEY_CAPTURE_ACTIVE.load(Ordering::SeqCst) {
+            return;
+        }
+        if key == Key::Escape {
+            super::HOTKEY_CAPTURE_ACTIVE.store(false, Ordering::SeqCst);
+            *super::CAPTURED_HOTKEY.lock().unwrap() = Some(String::new());
+            super::log_hotkey("cancel capture (Esc)");
+            return;
+        }
+        if !(has_ctrl || has_alt || has_shift || has_super) {
+            return;
+        }
+        let Some(name) = key_name(key) else {
+            super::log_hotkey(&format!("no canonical name for {:?}", key));
+            return;
+        };
+
+        let mut hotkey = String::new();
+        if has_ctrl { hotkey.push_str("Ctrl+"); }
+        if has_alt { hotkey.push_str("Alt+"); }
+        if has_shift { hotkey.push_str("Shift+"); }
+        if has_super { hotkey.push_str("Super+"); }
+        hotkey.push_str(name);
+
+        super::HOTKEY_CAPTURE_ACTIVE.store(false, Ordering::SeqCst);
+        *super::CAPTURED_HOTKEY.lock().unwrap() = Some(hotkey.clone());
+        super::log_hotkey(&format!("captured {}", hotkey));
+    }
+
+    /// Names round-trip through `hotkey::parse_key_code`
+    fn key_name(key: Key) -> Option<&'static str> {
+        let name = match key {
+            Key::KeyA => "A", Key::KeyB => "B", Key::KeyC => "C", Key::KeyD => "D",
+            Key::KeyE => "E", Key::KeyF => "F", Key::KeyG => "G", Key::KeyH => "H",
+            Key::KeyI => "I", Key::KeyJ => "J", Key::KeyK => "K", Key::KeyL => "L",
+            Key::KeyM => "M", Key::KeyN => "N", Key::KeyO => "O", Key::KeyP => "P",
+            Key::KeyQ => "Q", Key::KeyR => "R", Key::KeyS => "S", Key::KeyT => "T",
+            Key::KeyU => "U", Key::KeyV => "V", Key::KeyW => "W", Key::KeyX => "X",
+            Key::KeyY => "Y", Key::KeyZ => "Z",
+            Key::Num0 => "0", Key::Num1 => "1", Key::Num2 => "2", Key::Num3 => "3",
+            Key::Num4 => "4", Key::Num5 => "5", Key::Num6 => "6", Key::Num7 => "7",
+            Key::Num8 => "8", Key::Num9 => "9",
+            Key::F1 => "F1", Key::F2 => "F2", Key::F3 => "F3", Key::F4 => "F4",
+            Key::F5 => "F5", Key::F6 => "F6", Key::F7 => "F7", Key::F8 => "F8",
+            Key::F9 => "F9", Key::F10 => "F10", Key::F11 => "F11", Key::F12 => "F12",
+            Key::Space => "Space", Key::Return => "Enter", Key::Tab => "Tab",
+            Key::Backspace => "Backspace", Key::Delete => "Delete", Key::Insert => "Insert",
+            Key::Home => "Home", Key::End => "End", Key::PageUp => "PageUp",
+            Key::PageDown => "PageDown", Key::LeftArrow => "Left", Key::UpArrow => "Up",
+            Key::RightArrow => "Right", Key::DownArrow => "Down",
+            Key::SemiColon => "Semicolon", Key::Equal => "Equal", Key::Comma => "Comma",
+            Key::Minus => "Minus", Key::Dot => "Period", Key::Slash => "Slash",
+            Key::BackQuote => "Backquote", Key::LeftBracket => "BracketLeft",
+            Key::BackSlash => "Backslash", Key::RightBracket => "BracketRight",
+            Key::Quote => "Quote",
+            Key::Kp0 => "Numpad0", Key::Kp1 => "Numpad1", Key::Kp2 => "Numpad2",
+            Key::Kp3 => "Numpad3", Key::Kp4 => "Numpad4", Key::Kp5 => "Numpad5",
+            Key::Kp6 => "Numpad6", Key::Kp7 => "Numpad7", Key::Kp8 => "Numpad8",
+            Key::Kp9 => "Numpad9",
+            _ => return None,
+        };
+        Some(name)
+    }
+
+    pub fn send_copy() {
+        send_key_combo(Key::KeyC);
+    }
+
+    pub fn send_paste() {
+        send_key_combo(Key::KeyV);
+    }
+
+    fn send_key_combo(key: Key) {
+        let modifier = match current_modifier() {
+            ComboModifier::Ctrl => Key::ControlLeft,
+            ComboModifier::Cmd => Key::MetaLeft,
+        };
+        let events = [
+            EventType::KeyPress(modifier),
+            EventType::KeyPress(key),
+            EventType::KeyRelease(key),
+            EventType::KeyRelease(modifier),
+        ];
+        for event in &events {
+            if let Err(e) = rdev::simulate(event) {
+                super::log_hotkey(&format!("simulate {:?} failed: {:?}", event, e));
+            }
+            // X server 需要一点时间处理每个事件，否则可能丢键
+            thread::sleep(key_delay());
         }
     }
 }
@@ -692,12 +940,26 @@ pub fn start_keyboard_monitor() {
     platform_impl::start_keyboard_monitor();
 }
 
+/// Copy shortcut with the configured modifier (Cmd+C / Ctrl+C)
+pub fn send_copy() {
+    platform_impl::send_copy();
+}
+
+/// Paste shortcut with the configured modifier (Cmd+V / Ctrl+V)
+pub fn send_paste() {
+    platform_impl::send_paste();
+}
+
+#[deprecated(note = "use send_copy")]
+#[allow(dead_code)]
 pub fn send_ctrl_c() {
-    platform_impl::send_ctrl_c();
+    send_copy();
 }
 
+#[deprecated(note = "use send_paste")]
+#[allow(dead_code)]
 pub fn send_ctrl_v() {
-    platform_impl::send_ctrl_v();
+    send_paste();
 }
 
 #[cfg(test)]
@@ -711,4 +973,49 @@ mod tests {
         stop_hotkey_capture();
         assert!(!HOTKEY_CAPTURE_ACTIVE.load(Ordering::SeqCst));
     }
+
+    #[test]
+    fn test_captured_key_name_ignores_layout_label() {
+        // 俄文布局下 VK_A 的系统名称是 "Ф"，德文布局下 VK_OEM_1 是 "Ü"
+        assert_eq!(captured_key_name(0x41, Some("Ф")).as_deref(), Some("A"));
+        assert_eq!(captured_key_name(0xBA, Some("Ü")).as_deref(), Some("Semicolon"));
+        assert_eq!(captured_key_name(0x63, Some("Num 3")).as_deref(), Some("Numpad3"));
+
+        // 表外的按键注册不了，不作为快捷键保存
+        assert_eq!(captured_key_name(0xE2, Some("<")), None);
+        assert_eq!(captured_key_name(0xE2, None), None);
+    }
+
+    #[test]
+    fn test_captured_key_names_parse_as_hotkeys() {
+        // 录到的每个名字都要能重新注册
+        for vk in 0..=0xFFu16 {
+            if let Some(name) = captured_key_name(vk, None) {
+                assert!(crate::hotkey::parse_hotkey(&format!("Ctrl+{}", name)).is_ok(), "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_send_modifier_resolution() {
+        assert_eq!(resolve_modifier(SendModifier::Auto, true), ComboModifier::Cmd);
+        assert_eq!(resolve_modifier(SendModifier::Auto, false), ComboModifier::Ctrl);
+        // 远程桌面/虚拟机里的 macOS 需要 Ctrl
+        assert_eq!(resolve_modifier(SendModifier::Ctrl, true), ComboModifier::Ctrl);
+        assert_eq!(resolve_modifier(SendModifier::Cmd, true), ComboModifier::Cmd);
+        // Windows 上 Win+V 会打开剪贴板历史，不能当作粘贴
+        assert_eq!(resolve_modifier(SendModifier::Cmd, false), ComboModifier::Ctrl);
+    }
+
+    #[test]
+    fn test_combo_events_use_configured_modifier() {
+        let ctrl_c = combo_key_sequence(ComboModifier::Ctrl, 0x43);
+        assert_eq!(ctrl_c, [(0x11, false), (0x43, false), (0x43, true), (0x11, true)]);
+        let win_v = combo_key_sequence(ComboModifier::Cmd, 0x56);
+        assert_eq!(win_v[0], (0x5B, false));
+        assert_eq!(win_v[3], (0x5B, true));
+
+        assert_eq!(combo_flag_bits(ComboModifier::Cmd), 0x0010_0000);
+        assert_eq!(combo_flag_bits(ComboModifier::Ctrl), 0x0004_0000);
+    }
 }
@@ -0,0 +1,83 @@
+//! Log setup: `--log-level` or `NANOTRANS_LOG` picks the verbosity (same syntax as `RUST_LOG`),
+//! debug builds print to stderr, release builds write a daily file under `logs/` next to the config
+
+use std::path::PathBuf;
+
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::EnvFilter;
+
+use crate::config::Config;
+
+/// Environment variable read when `--log-level` isn't given
+pub const LOG_ENV: &str = "NANOTRANS_LOG";
+
+const DEFAULT_LEVEL: &str = "info";
+
+/// Daily files kept before the oldest is deleted
+const MAX_LOG_FILES: usize = 7;
+
+/// `logs/` next to the config file
+pub fn log_dir() -> Option<PathBuf> {
+    let path = Config::config_path().ok()?;
+    Some(path.parent()?.join("logs"))
+}
+
+/// Filter for `level`, falling back to the default when it doesn't parse
+fn env_filter(level: Option<&str>) -> (EnvFilter, Option<String>) {
+    let level = level.map(str::trim).filter(|l| !l.is_empty());
+    match level.map(EnvFilter::try_new) {
+        Some(Ok(filter)) => (filter, None),
+        Some(Err(e)) => (EnvFilter::new(DEFAULT_LEVEL), Some(format!("{:?}: {}", level.unwrap_or_default(), e))),
+        None => (EnvFilter::new(DEFAULT_LEVEL), None),
+    }
+}
+
+fn file_appender() -> Option<RollingFileAppender> {
+    let dir = log_dir()?;
+    RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("nanotrans")
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(&dir)
+        .map_err(|e| eprintln!("无法创建日志文件({}): {}", dir.display(), e))
+        .ok()
+}
+
+/// Install the global subscriber, the guard flushes the file writer and must live until exit
+pub fn init(cli_level: Option<&str>) -> Option<WorkerGuard> {
+    let env_level = std::env::var(LOG_ENV).ok();
+    let (filter, invalid) = env_filter(cli_level.or(env_level.as_deref()));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+
+    let appender = if cfg!(debug_assertions) { None } else { file_appender() };
+    let guard = match appender {
+        Some(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            builder.with_writer(writer).with_ansi(false).init();
+            Some(guard)
+        }
+        None => {
+            builder.with_writer(std::io::stderr).init();
+            None
+        }
+    };
+    if let Some(invalid) = invalid {
+        tracing::warn!("日志级别无效，使用 {}: {}", DEFAULT_LEVEL, invalid);
+    }
+    guard
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_filter_falls_back_on_bad_level() {
+        assert!(env_filter(Some("debug")).1.is_none());
+        assert!(env_filter(Some("nanotrans=trace,warn")).1.is_none());
+        assert!(env_filter(None).1.is_none());
+        assert!(env_filter(Some("nanotrans=loud")).1.is_some());
+    }
+}
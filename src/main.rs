@@ -3,81 +3,248 @@
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod appearance;
+mod batch;
 mod caret;
 mod clipboard;
+mod comment;
 mod config;
+mod glossary;
+mod history;
 mod hotkey;
 mod i18n;
+mod idle;
 mod input;
+mod logging;
+mod permissions;
+mod pick;
+mod popup_window;
+mod postprocess;
+mod richtext;
+mod secret;
+mod skip;
+mod speech;
 mod translate;
 mod tray;
+mod usage;
 
 use anyhow::Result;
-use config::{Config, PromptPreset};
-use hotkey::HotkeyManager;
-use slint::{ComponentHandle, LogicalSize, ModelRc, PhysicalPosition, SharedString, VecModel};
+use clipboard::ClipboardContent;
+use config::{Config, DeepLFormality, PromptPreset, ProviderConfig, ProviderType, SendModifier, ThemeMode};
+use hotkey::{HotkeyAction, HotkeyManager};
+use slint::{ComponentHandle, LogicalPosition, LogicalSize, Model, ModelRc, SharedString, VecModel};
 use std::cell::RefCell;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
-use translate::Translator;
+use std::time::{Duration, Instant};
+use pick::PickStrategy;
+use translate::{LangPair, PickedTranslation, RateLimiter, ResultJson, SharedCache, SharedRateLimiter, TranslationCache, Translator};
+use tracing::{error, info, warn};
 
 slint::include_modules!();
 
 /// Application state
 struct SharedState {
     config: Config,
-    original_clipboard: Option<String>,
+    original_clipboard: Option<ClipboardContent>,
+    raw_translation: String,  // 未经后处理的译文，切换列表格式时从这里重新生成
     popup_shown_at: Option<std::time::Instant>,  // 窗口显示时间，用于防止立即关闭
+    popup_had_focus: bool,  // 本次显示后弹窗是否拿到过焦点
+    shown_provider: String,  // 自动择优时当前显示结果的服务名
+    alternative: Option<(String, String)>,  // 自动择优落选的结果：(服务名, 原始译文)
+    translation_cache: SharedCache,  // 最近的翻译结果，跨多次快捷键触发复用
+    rate_limiter: SharedRateLimiter,  // 每小时请求数的滑动窗口，所有翻译共用
+    current_pair: Option<LangPair>,  // 当前显示结果的翻译方向
+    swap_undo: Option<SwapSnapshot>,  // 反向翻译前的内容，用于撤销
+    idle: idle::IdleTracker,  // 长时间不用时把缓存挪到磁盘
+    popup_request: u64,  // 弹窗请求的序号，每次新请求或关闭弹窗时加一，旧请求的结果直接丢弃
+    popup_task: Option<tokio::task::AbortHandle>,  // 正在进行的弹窗请求
+    last_triggered: Option<Instant>,  // 上次处理快捷键的时间，用于去抖
+    popup_activity_at: Option<Instant>,  // 弹窗最后一次被操作的时间，自动关闭从这里计时
+    source_html: Option<(String, String)>,  // 复制到的带格式选区：(纯文本, HTML)
+    rich_source: Option<RichSource>,  // 当前译文对应的带格式选区，应用时才按原格式重建
+    session_usage: Option<usage::UsageEstimate>,  // 启动以来弹窗翻译累计的用量
 }
 
-// 与 popup.slint 的默认尺寸保持一致
-const POPUP_WIDTH: f32 = 380.0;
-const POPUP_HEIGHT: f32 = 220.0;
+/// Formatted selection behind the popup result, rebuilt into HTML only when it is applied
+#[derive(Clone)]
+struct RichSource {
+    translated: String,
+    html: String,
+    pair: LangPair,
+}
+
+/// Popup content before a swap
+struct SwapSnapshot {
+    source: String,
+    translated: String,
+    raw_translation: String,
+    pair: Option<LangPair>,
+    provider: String,
+}
+
+/// Command line options
+struct CliArgs {
+    print_config_path: bool,
+    config_path: Option<PathBuf>,
+    /// Overrides `NANOTRANS_LOG`, e.g. `debug` or `nanotrans=trace`
+    log_level: Option<String>,
+    /// Unknown arguments, reported once logging is up
+    ignored: Vec<String>,
+}
+
+fn parse_cli_args() -> Result<CliArgs> {
+    let mut cli = CliArgs { print_config_path: false, config_path: None, log_level: None, ignored: Vec::new() };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let path = match arg.as_str() {
+            "--print-config-path" => {
+                cli.print_config_path = true;
+                continue;
+            }
+            "--log-level" => {
+                cli.log_level = Some(args.next().ok_or_else(|| anyhow::anyhow!("--log-level requires a level"))?);
+                continue;
+            }
+            "--config" => args
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("--config requires a path"))?,
+            other => match (other.strip_prefix("--config="), other.strip_prefix("--log-level=")) {
+                (Some(path), _) => path.to_string(),
+                (None, Some(level)) => {
+                    cli.log_level = Some(level.to_string());
+                    continue;
+                }
+                (None, None) => {
+                    // macOS 旧版本启动 .app 时会带 -psn_ 参数，忽略即可
+                    cli.ignored.push(other.to_string());
+                    continue;
+                }
+            },
+        };
+        let path = PathBuf::from(path);
+        cli.config_path = Some(if path.is_absolute() { path } else { std::env::current_dir()?.join(path) });
+    }
+    Ok(cli)
+}
+
+/// Release builds on Windows have no console, borrow the parent one for CLI output
+#[cfg(target_os = "windows")]
+fn attach_parent_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_parent_console() {}
 
 fn main() -> Result<()> {
+    let cli = parse_cli_args()?;
+    if cli.config_path.is_some() {
+        config::set_config_path_override(cli.config_path);
+    }
+    if cli.print_config_path {
+        attach_parent_console();
+        println!("{}", Config::config_path()?.display());
+        return Ok(());
+    }
+    // 日志目录跟随 --config，所以要在设置路径之后初始化
+    let _log_guard = logging::init(cli.log_level.as_deref());
+    for arg in &cli.ignored {
+        warn!("忽略未知参数: {}", arg);
+    }
+
     init_macos_font();
     // Load configuration
     let mut config = Config::load().unwrap_or_default();
-    input::set_hotkey_log_enabled(config.hotkey_log_enabled);
+    apply_input_settings(&config);
 
     // Initialize i18n
     i18n::init(&config.ui_language);
+    i18n::load_locale(&config.ui_locale);
+
+    // 启动时主动检查系统权限，避免第一次翻译才失败
+    let show_permission_alert = check_permissions_on_startup(&mut config);
 
     // Prepare hotkey manager (fallback to default on invalid config)
-    let hotkey_manager_inner = match HotkeyManager::new(&config.hotkey) {
+    let mut hotkey_manager_inner = match HotkeyManager::new(&config.hotkey) {
         Ok(manager) => manager,
         Err(e) => {
-            eprintln!(
+            warn!(
                 "注册全局快捷键失败({})，回退到默认 {}",
                 e,
                 hotkey::DEFAULT_HOTKEY
             );
             config.hotkey = hotkey::DEFAULT_HOTKEY.to_string();
             if let Err(save_err) = config.save() {
-                eprintln!("写入默认快捷键失败: {}", save_err);
+                error!("写入默认快捷键失败: {}", save_err);
             }
             HotkeyManager::new(&config.hotkey)?
         }
     };
+    register_extra_hotkeys(&mut hotkey_manager_inner, &config);
+
+    // 上次退出时还停在磁盘上的缓存没人会读了
+    if let Some(path) = cache_spill_path() {
+        TranslationCache::remove_stale_spill(&path);
+    }
 
     // Create shared state
     let shared_state = Arc::new(Mutex::new(SharedState {
         config: config.clone(),
         original_clipboard: None,
+        raw_translation: String::new(),
         popup_shown_at: None,
+        popup_had_focus: false,
+        shown_provider: String::new(),
+        alternative: None,
+        translation_cache: TranslationCache::shared(),
+        rate_limiter: RateLimiter::shared(),
+        current_pair: None,
+        swap_undo: None,
+        idle: idle::IdleTracker::new(Instant::now()),
+        popup_request: 0,
+        popup_task: None,
+        last_triggered: None,
+        popup_activity_at: None,
+        source_html: None,
+        rich_source: None,
+        session_usage: None,
     }));
 
     // Create the translation popup window
+    let scheme = SharedString::from(appearance::scheme(config.theme));
     let popup = TranslatePopup::new()?;
     apply_macos_font_family_popup(&popup);
+    popup.global::<Theme>().set_scheme(scheme.clone());
+    popup.set_on_top(config.popup_always_on_top);
     popup.hide()?;
 
+    // Create the review window for long results (hidden until needed)
+    let review = ReviewWindow::new()?;
+    apply_macos_font_family_review(&review);
+    review.global::<Theme>().set_scheme(scheme.clone());
+    review.hide()?;
+    set_review_i18n_texts(&review);
+    let review_weak = review.as_weak();
+
+    // History window, entries are reloaded each time it opens
+    let history_window = HistoryWindow::new()?;
+    apply_macos_font_family_history(&history_window);
+    history_window.global::<Theme>().set_scheme(scheme);
+    history_window.hide()?;
+    set_history_i18n_texts(&history_window);
+    let history_weak = history_window.as_weak();
+
     // Set i18n texts for popup
     set_popup_i18n_texts(&popup);
+    popup.set_bullet_list(config.bullet_list_output);
 
     // Create system tray
-    let _tray = tray::create_tray()?;
+    let tray = tray::create_tray(&config)?;
 
     // Register global hotkey
     let hotkey_manager = Arc::new(Mutex::new(hotkey_manager_inner));
@@ -97,25 +264,78 @@ fn main() -> Result<()> {
     let shared_state_apply = Arc::clone(&shared_state);
     popup.on_apply_translation({
         let popup_weak = popup_weak.clone();
+        let rt_apply = Arc::clone(&rt);
         move || {
             if let Some(popup) = popup_weak.upgrade() {
                 let translated = popup.get_translated_text().to_string();
                 if !translated.is_empty() {
-                    let original = shared_state_apply.lock().unwrap().original_clipboard.clone();
+                    let (original, delays) = paste_context(&shared_state_apply);
+                    // 译文没被改过时才带上原来的格式
+                    let (rich, config, limiter) = {
+                        let mut state = shared_state_apply.lock().unwrap();
+                        let rich = state.rich_source.clone().filter(|rich| {
+                            rich.translated == translated && !popup.get_result_edited() && !popup.get_bullet_list()
+                        });
+                        (rich, state.config.clone(), Arc::clone(&state.rate_limiter))
+                    };
 
                     // 先隐藏窗口，让焦点回到原来的应用程序
                     popup.hide().ok();
-
-                    // 在后台线程中执行粘贴操作，等待焦点切换完成
-                    std::thread::spawn(move || {
-                        std::thread::sleep(Duration::from_millis(150));
-                        let _ = clipboard::paste_and_restore(&translated, original);
-                    });
+                    popup.set_clipboard_error(SharedString::new());
+
+                    // 粘贴失败时把窗口重新弹出来，用户可以再点一次
+                    let popup_weak = popup_weak.clone();
+                    let on_error = move |e: anyhow::Error| {
+                        if let Some(popup) = popup_weak.upgrade() {
+                            report_clipboard_error(&popup, &e);
+                            popup.show().ok();
+                        }
+                    };
+                    match rich {
+                        // 按原来的标签重建要再请求一次，只在真正粘贴时做
+                        Some(rich) => {
+                            rt_apply.spawn(async move {
+                                let translator = Translator::new(config).with_rate_limit(limiter);
+                                let html = translate_rich_text(&translator, &rich.pair, &rich.html, &translated).await;
+                                paste_in_background(ClipboardContent { text: translated, html }, original, delays, on_error);
+                            });
+                        }
+                        None => paste_in_background(ClipboardContent::plain(translated), original, delays, on_error),
+                    }
                 }
             }
         }
     });
 
+    // 原文和译文注释一起粘贴回去，替换当前选区
+    let shared_state_comment = Arc::clone(&shared_state);
+    popup.on_insert_as_comment({
+        let popup_weak = popup_weak.clone();
+        move || {
+            let Some(popup) = popup_weak.upgrade() else { return; };
+            let source = popup.get_source_text().to_string();
+            let translated = popup.get_translated_text().to_string();
+            let Some(combined) = comment::insert_below(&source, &translated) else { return; };
+            let (original, delays) = paste_context(&shared_state_comment);
+
+            popup.hide().ok();
+            popup.set_clipboard_error(SharedString::new());
+            let popup_weak = popup_weak.clone();
+            paste_in_background(ClipboardContent::plain(combined), original, delays, move |e| {
+                if let Some(popup) = popup_weak.upgrade() {
+                    report_clipboard_error(&popup, &e);
+                    popup.show().ok();
+                }
+            });
+        }
+    });
+
+    // 操作弹窗时重新计算自动关闭时间
+    let shared_state_activity = Arc::clone(&shared_state);
+    popup.on_activity(move || {
+        shared_state_activity.lock().unwrap().popup_activity_at = Some(Instant::now());
+    });
+
     // Handle close popup
     let shared_state_close = Arc::clone(&shared_state);
     popup.on_close_popup({
@@ -123,10 +343,12 @@ fn main() -> Result<()> {
         move || {
             if let Some(popup) = popup_weak.upgrade() {
                 let original = shared_state_close.lock().unwrap().original_clipboard.clone();
-                if let Some(text) = original {
-                    let _ = clipboard::simple::set_text(&text);
+                if let Some(original) = original {
+                    if let Err(e) = clipboard::simple::set_content(&original) {
+                        error!("恢复剪贴板失败: {:#}", e);
+                    }
                 }
-                popup.hide().ok();
+                dismiss_popup(&popup, &shared_state_close);
             }
         }
     });
@@ -138,9 +360,414 @@ fn main() -> Result<()> {
             if let Some(popup) = popup_weak.upgrade() {
                 let translated = popup.get_translated_text().to_string();
                 if !translated.is_empty() {
-                    let _ = clipboard::simple::set_text(&translated);
+                    match clipboard::simple::set_text(&translated) {
+                        Ok(()) => popup.set_clipboard_error(SharedString::new()),
+                        Err(e) => report_clipboard_error(&popup, &e),
+                    }
+                }
+            }
+        }
+    });
+
+    // 朗读译文，正在朗读时再点一次就停止
+    let shared_state_speak = Arc::clone(&shared_state);
+    popup.on_speak_result({
+        let popup_weak = popup_weak.clone();
+        move || {
+            let Some(popup) = popup_weak.upgrade() else { return; };
+            if popup.get_speaking() {
+                speech::stop();
+                popup.set_speaking(false);
+                return;
+            }
+            let translated = popup.get_translated_text().to_string();
+            if translated.trim().is_empty() {
+                return;
+            }
+            let lang = {
+                let state = shared_state_speak.lock().unwrap();
+                state.current_pair.as_ref().map(|p| p.target.clone()).unwrap_or_else(|| state.config.target_lang.clone())
+            };
+            popup.set_speaking(true);
+            let popup_weak = popup_weak.clone();
+            speech::speak(translated, &lang, move || {
+                let _ = slint::invoke_from_event_loop(move || {
+                    if let Some(popup) = popup_weak.upgrade() {
+                        popup.set_speaking(false);
+                    }
+                });
+            });
+        }
+    });
+
+    // 复制原文，划词翻译可能已经覆盖了剪贴板
+    popup.on_copy_source({
+        let popup_weak = popup_weak.clone();
+        move || {
+            let Some(popup) = popup_weak.upgrade() else { return; };
+            let source = popup.get_source_text().to_string();
+            if source.is_empty() {
+                return;
+            }
+            match clipboard::simple::set_text(&source) {
+                Ok(()) => popup.set_clipboard_error(SharedString::new()),
+                Err(e) => report_clipboard_error(&popup, &e),
+            }
+        }
+    });
+
+    // 复制为 JSON，方便粘贴给脚本处理
+    let shared_state_json = Arc::clone(&shared_state);
+    popup.on_copy_json({
+        let popup_weak = popup_weak.clone();
+        move || {
+            let Some(popup) = popup_weak.upgrade() else { return; };
+            let translated = popup.get_translated_text().to_string();
+            if translated.is_empty() {
+                return;
+            }
+            let source = popup.get_source_text().to_string();
+            let json = {
+                let state = shared_state_json.lock().unwrap();
+                let pair = state.current_pair.as_ref();
+                ResultJson {
+                    source: &source,
+                    translation: &translated,
+                    source_lang: pair.and_then(|p| p.source.as_deref()),
+                    target_lang: pair.map(|p| p.target.as_str()).unwrap_or_default(),
+                    provider: &state.shown_provider,
+                }
+                .to_json()
+            };
+            match clipboard::simple::set_text(&json) {
+                Ok(()) => popup.set_clipboard_error(SharedString::new()),
+                Err(e) => report_clipboard_error(&popup, &e),
+            }
+        }
+    });
+
+    // Handle bullet list toggle (re-render from the raw translation)
+    let shared_state_list = Arc::clone(&shared_state);
+    popup.on_bullet_list_toggled({
+        let popup_weak = popup_weak.clone();
+        move || {
+            let Some(popup) = popup_weak.upgrade() else { return; };
+            let enabled = popup.get_bullet_list();
+            let raw = {
+                let mut state = shared_state_list.lock().unwrap();
+                state.config.bullet_list_output = enabled;
+                if let Err(e) = state.config.save() {
+                    error!("保存配置失败: {}", e);
+                }
+                state.raw_translation.clone()
+            };
+            if raw.is_empty() {
+                return;
+            }
+            let shown = render_translation(&popup.get_source_text(), &raw, enabled);
+            popup.set_translated_text(SharedString::from(&shown));
+            // 剪贴板里是自动复制的旧格式，同步成当前显示的内容
+            auto_copy(&popup, &shared_state_list, &shown);
+        }
+    });
+
+    // Switch between the auto-picked result and the alternative
+    let shared_state_alt = Arc::clone(&shared_state);
+    popup.on_show_alternative({
+        let popup_weak = popup_weak.clone();
+        move || {
+            let Some(popup) = popup_weak.upgrade() else { return; };
+            let (raw, other) = {
+                let mut state = shared_state_alt.lock().unwrap();
+                let Some((name, raw)) = state.alternative.take() else { return; };
+                let previous_raw = std::mem::replace(&mut state.raw_translation, raw.clone());
+                let previous_name = std::mem::replace(&mut state.shown_provider, name);
+                state.alternative = Some((previous_name.clone(), previous_raw));
+                (raw, previous_name)
+            };
+            let shown = render_translation(&popup.get_source_text(), &raw, popup.get_bullet_list());
+            popup.set_translated_text(SharedString::from(&shown));
+            // 说明只属于原来那份 JSON 答案
+            popup.set_note_text(SharedString::new());
+            popup.set_alt_label(SharedString::from(format_alt_label(&other)));
+            // 与列表切换一样，剪贴板同步成当前显示的内容
+            auto_copy(&popup, &shared_state_alt, &shown);
+        }
+    });
+
+    // 把译文当作新原文，反方向再翻译一次
+    let shared_state_swap = Arc::clone(&shared_state);
+    let rt_swap = Arc::clone(&rt);
+    popup.on_swap_direction({
+        let popup_weak = popup_weak.clone();
+        move || {
+            let Some(popup) = popup_weak.upgrade() else { return; };
+            let source = popup.get_source_text().to_string();
+            let translated = popup.get_translated_text().to_string();
+            if translated.trim().is_empty() {
+                return;
+            }
+            let (config, limiter, swapped) = {
+                let mut state = shared_state_swap.lock().unwrap();
+                let pair = state
+                    .current_pair
+                    .clone()
+                    .unwrap_or_else(|| Translator::new(state.config.clone()).lang_pair(&source));
+                let swapped = pair.swapped(&source);
+                state.swap_undo = Some(SwapSnapshot {
+                    source,
+                    translated: translated.clone(),
+                    raw_translation: std::mem::take(&mut state.raw_translation),
+                    pair: Some(pair),
+                    provider: state.shown_provider.clone(),
+                });
+                state.current_pair = Some(swapped.clone());
+                state.alternative = None;
+                (state.config.clone(), Arc::clone(&state.rate_limiter), swapped)
+            };
+
+            popup.set_source_text(SharedString::from(&translated));
+            popup.set_translated_text(SharedString::new());
+            popup.set_result_edited(false);
+            popup.set_error_message(SharedString::new());
+            popup.set_clipboard_error(SharedString::new());
+            popup.set_alt_label(SharedString::new());
+            popup.set_usage_text(SharedString::new());
+            popup.set_note_text(SharedString::new());
+            popup.set_lang_label(SharedString::new());
+            popup.set_is_preview(false);
+            popup.set_can_undo_swap(true);
+            popup.set_loading(true);
+            sync_target_language(&popup, &swapped.target);
+
+            let popup_weak = popup_weak.clone();
+            let shared_state = Arc::clone(&shared_state_swap);
+            // 关闭弹窗或再次按快捷键时中止，过期结果不能覆盖剪贴板和新译文
+            let request = next_popup_request(&shared_state_swap);
+            let task = rt_swap.spawn(async move {
+                let result = Translator::new(config).with_rate_limit(limiter).translate_pair(&translated, &swapped).await;
+                let _ = slint::invoke_from_event_loop(move || {
+                    if !is_current_request(&shared_state, request) {
+                        return;
+                    }
+                    let Some(popup) = popup_weak.upgrade() else { return; };
+                    show_pair_result(&popup, &shared_state, &translated, result);
+                });
+            });
+            track_popup_task(&shared_state_swap, request, &task);
+        }
+    });
+
+    // 同一段原文换个目标语言（或原样）重新翻译
+    let shared_state_retranslate = Arc::clone(&shared_state);
+    let rt_retranslate = Arc::clone(&rt);
+    popup.on_retranslate({
+        let popup_weak = popup_weak.clone();
+        move |target| {
+            let Some(popup) = popup_weak.upgrade() else { return; };
+            let source = popup.get_source_text().to_string();
+            if source.trim().is_empty() || popup.get_loading() {
+                return;
+            }
+            let (config, limiter, pair) = {
+                let mut state = shared_state_retranslate.lock().unwrap();
+                let pair = state
+                    .current_pair
+                    .clone()
+                    .unwrap_or_else(|| Translator::new(state.config.clone()).lang_pair(&source))
+                    .with_target(&target);
+                state.current_pair = Some(pair.clone());
+                state.alternative = None;
+                (state.config.clone(), Arc::clone(&state.rate_limiter), pair)
+            };
+
+            popup.set_translated_text(SharedString::new());
+            popup.set_result_edited(false);
+            popup.set_error_message(SharedString::new());
+            popup.set_clipboard_error(SharedString::new());
+            popup.set_alt_label(SharedString::new());
+            popup.set_usage_text(SharedString::new());
+            popup.set_note_text(SharedString::new());
+            popup.set_lang_label(SharedString::new());
+            popup.set_is_preview(false);
+            // 在多语言结果上换语言就回到单一译文
+            popup.set_multi_target(false);
+            popup.set_loading(true);
+
+            let popup_weak = popup_weak.clone();
+            let shared_state = Arc::clone(&shared_state_retranslate);
+            let request = next_popup_request(&shared_state_retranslate);
+            let task = rt_retranslate.spawn(async move {
+                let result = Translator::new(config).with_rate_limit(limiter).translate_pair(&source, &pair).await;
+                let _ = slint::invoke_from_event_loop(move || {
+                    if !is_current_request(&shared_state, request) {
+                        return;
+                    }
+                    let Some(popup) = popup_weak.upgrade() else { return; };
+                    show_pair_result(&popup, &shared_state, &source, result);
+                });
+            });
+            track_popup_task(&shared_state_retranslate, request, &task);
+        }
+    });
+
+    let shared_state_undo = Arc::clone(&shared_state);
+    popup.on_undo_swap({
+        let popup_weak = popup_weak.clone();
+        move || {
+            let Some(popup) = popup_weak.upgrade() else { return; };
+            let Some(snapshot) = ({
+                let mut state = shared_state_undo.lock().unwrap();
+                let snapshot = state.swap_undo.take();
+                if let Some(ref s) = snapshot {
+                    state.raw_translation = s.raw_translation.clone();
+                    state.current_pair = s.pair.clone();
+                    state.shown_provider = s.provider.clone();
+                }
+                snapshot
+            }) else { return; };
+            let label = snapshot.pair.as_ref().map(LangPair::label).unwrap_or_default();
+            if let Some(ref pair) = snapshot.pair {
+                sync_target_language(&popup, &pair.target);
+            }
+            popup.set_source_text(SharedString::from(&snapshot.source));
+            popup.set_translated_text(SharedString::from(&snapshot.translated));
+            popup.set_error_message(SharedString::new());
+            popup.set_usage_text(SharedString::new());
+            popup.set_note_text(SharedString::new());
+            popup.set_lang_label(SharedString::from(label));
+            popup.set_can_undo_swap(false);
+            popup.set_loading(false);
+            auto_copy(&popup, &shared_state_undo, &snapshot.translated);
+        }
+    });
+
+    // Handle translate full (after a preview)
+    let shared_state_full = Arc::clone(&shared_state);
+    let rt_full = Arc::clone(&rt);
+    popup.on_translate_full({
+        let popup_weak = popup_weak.clone();
+        let review_weak = review_weak.clone();
+        move || {
+            let Some(popup) = popup_weak.upgrade() else { return; };
+            let source = popup.get_source_text().to_string();
+            popup.set_is_preview(false);
+            popup.set_translated_text(SharedString::new());
+            popup.set_result_edited(false);
+            popup.set_error_message(SharedString::new());
+            popup.set_clipboard_error(SharedString::new());
+            popup.set_loading(true);
+            // 保持预览时的方向，目标语言快捷键触发的预览也还是译到那个语言
+            let target = shared_state_full.lock().unwrap().current_pair.as_ref().map(|p| p.target.clone());
+            spawn_popup_translation(&popup_weak, &review_weak, &shared_state_full, &rt_full, source, target);
+        }
+    });
+
+    // Handle expand (move the result into the review window)
+    popup.on_expand_result({
+        let popup_weak = popup_weak.clone();
+        let review_weak = review_weak.clone();
+        move || {
+            let (Some(popup), Some(review)) = (popup_weak.upgrade(), review_weak.upgrade()) else { return; };
+            popup.hide().ok();
+            open_review_window(&review, &popup.get_source_text(), &popup.get_translated_text());
+        }
+    });
+
+    // Review window actions
+    review.on_copy_result({
+        let review_weak = review_weak.clone();
+        move || {
+            if let Some(review) = review_weak.upgrade() {
+                let translated = review.get_translated_text().to_string();
+                if !translated.is_empty() {
+                    match clipboard::simple::set_text(&translated) {
+                        Ok(()) => review.set_clipboard_error(SharedString::new()),
+                        Err(e) => {
+                            error!("剪贴板写入失败: {:#}", e);
+                            review.set_clipboard_error(SharedString::from(i18n::t().clipboard_error));
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    let shared_state_review_apply = Arc::clone(&shared_state);
+    review.on_apply_translation({
+        let review_weak = review_weak.clone();
+        move || {
+            let Some(review) = review_weak.upgrade() else { return; };
+            let translated = review.get_translated_text().to_string();
+            if translated.is_empty() {
+                return;
+            }
+            let (original, delays) = paste_context(&shared_state_review_apply);
+            review.hide().ok();
+            review.set_clipboard_error(SharedString::new());
+            let review_weak = review_weak.clone();
+            paste_in_background(ClipboardContent::plain(translated), original, delays, move |e| {
+                if let Some(review) = review_weak.upgrade() {
+                    error!("剪贴板写入失败: {:#}", e);
+                    review.set_clipboard_error(SharedString::from(i18n::t().clipboard_error));
+                    review.show().ok();
                 }
+            });
+        }
+    });
+
+    let shared_state_review_close = Arc::clone(&shared_state);
+    review.on_close_review({
+        let review_weak = review_weak.clone();
+        move || {
+            if let Some(review) = review_weak.upgrade() {
+                let original = shared_state_review_close.lock().unwrap().original_clipboard.clone();
+                if let Some(original) = original {
+                    if let Err(e) = clipboard::simple::set_content(&original) {
+                        error!("恢复剪贴板失败: {:#}", e);
+                    }
+                }
+                review.hide().ok();
+            }
+        }
+    });
+
+    let shared_state_retranslate = Arc::clone(&shared_state);
+    let rt_retranslate = Arc::clone(&rt);
+    review.on_retranslate({
+        let review_weak = review_weak.clone();
+        move || {
+            let Some(review) = review_weak.upgrade() else { return; };
+            let source = review.get_source_text().to_string();
+            if source.trim().is_empty() {
+                return;
             }
+            review.set_loading(true);
+            review.set_error_message(SharedString::new());
+            review.set_clipboard_error(SharedString::new());
+
+            let (config, cache, limiter) = {
+                let state = shared_state_retranslate.lock().unwrap();
+                (state.config.clone(), Arc::clone(&state.translation_cache), Arc::clone(&state.rate_limiter))
+            };
+            let shared_state = Arc::clone(&shared_state_retranslate);
+            let review_weak = review_weak.clone();
+            rt_retranslate.spawn(async move {
+                let bullet_list = config.bullet_list_output;
+                let result = Translator::new(config).with_cache(cache).with_rate_limit(limiter).translate(&source).await;
+                let _ = slint::invoke_from_event_loop(move || {
+                    let Some(review) = review_weak.upgrade() else { return; };
+                    review.set_loading(false);
+                    match result {
+                        Ok(r) => {
+                            let translated = render_translation(&source, &r.translated_text, bullet_list);
+                            shared_state.lock().unwrap().raw_translation = r.translated_text;
+                            review.set_translated_text(SharedString::from(translated));
+                        }
+                        Err(e) => review.set_error_message(SharedString::from(e.to_string())),
+                    }
+                });
+            });
         }
     });
 
@@ -151,9 +778,10 @@ fn main() -> Result<()> {
     let shared_state_settings = Arc::clone(&shared_state);
     let settings_window_popup = Rc::clone(&settings_window);
     let hotkey_manager_popup = Arc::clone(&hotkey_manager);
+    let rt_settings = Arc::clone(&rt);
     popup.on_open_settings({
         move || {
-            open_settings_window(&shared_state_settings, &settings_window_popup, &hotkey_manager_popup);
+            open_settings_window(&shared_state_settings, &settings_window_popup, &hotkey_manager_popup, &rt_settings, false);
         }
     });
 
@@ -162,17 +790,88 @@ fn main() -> Result<()> {
         let popup_weak = popup_weak.clone();
         move |delta_x, delta_y| {
             if let Some(popup) = popup_weak.upgrade() {
-                let current_pos = popup.window().position();
-                popup.window().set_position(PhysicalPosition::new(
-                    current_pos.x + delta_x,
-                    current_pos.y + delta_y,
+                // 拖动偏移是逻辑像素，当前位置也换算成逻辑坐标
+                let current_pos = popup.window().position().to_logical(popup.window().scale_factor());
+                popup.window().set_position(LogicalPosition::new(
+                    current_pos.x + delta_x as f32,
+                    current_pos.y + delta_y as f32,
                 ));
             }
         }
     });
 
+    popup.on_resize_window({
+        let popup_weak = popup_weak.clone();
+        move |delta_x, delta_y| {
+            if let Some(popup) = popup_weak.upgrade() {
+                let size = popup.window().size().to_logical(popup.window().scale_factor());
+                let width = (size.width + delta_x as f32).clamp(config::POPUP_MIN_SIZE.0, config::POPUP_MAX_SIZE.0);
+                let height = (size.height + delta_y as f32).clamp(config::POPUP_MIN_SIZE.1, config::POPUP_MAX_SIZE.1);
+                popup.window().set_size(LogicalSize::new(width, height));
+            }
+        }
+    });
+
+    // 记住拖动后的位置和尺寸，下次弹出时恢复
+    let shared_state_drag = Arc::clone(&shared_state);
+    popup.on_drag_finished({
+        let popup_weak = popup_weak.clone();
+        move || {
+            let Some(popup) = popup_weak.upgrade() else { return; };
+            let scale = popup.window().scale_factor();
+            let position = popup.window().position().to_logical(scale);
+            let size = popup.window().size().to_logical(scale);
+            let mut state = shared_state_drag.lock().unwrap();
+            state.config.popup_position = Some((position.x, position.y));
+            state.config.popup_width = size.width;
+            state.config.popup_height = size.height;
+            state.config.normalize();
+            if let Err(e) = state.config.save() {
+                error!("保存配置失败: {}", e);
+            }
+        }
+    });
+
+    // History window actions
+    history_window.on_copy_entry({
+        let history_weak = history_weak.clone();
+        move |index| {
+            let Some(win) = history_weak.upgrade() else { return; };
+            let Some(item) = win.get_entries().row_data(index as usize) else { return; };
+            match clipboard::simple::set_text(&item.translated) {
+                Ok(()) => win.set_clipboard_error(SharedString::new()),
+                Err(e) => {
+                    error!("剪贴板写入失败: {:#}", e);
+                    win.set_clipboard_error(SharedString::from(i18n::t().clipboard_error));
+                }
+            }
+        }
+    });
+
+    history_window.on_clear_history({
+        let history_weak = history_weak.clone();
+        move || {
+            if let Err(e) = history::clear() {
+                error!("清空翻译历史失败: {}", e);
+            }
+            if let Some(win) = history_weak.upgrade() {
+                show_history_entries(&win);
+            }
+        }
+    });
+
+    history_window.on_close_history({
+        let history_weak = history_weak.clone();
+        move || {
+            if let Some(win) = history_weak.upgrade() {
+                win.hide().ok();
+            }
+        }
+    });
+
     // Set up timer to poll for events
     let popup_weak_timer = popup_weak.clone();
+    let review_weak_timer = review_weak.clone();
     let shared_state_timer = Arc::clone(&shared_state);
     let hotkey_manager_timer = Arc::clone(&hotkey_manager);
     let rt_timer = Arc::clone(&rt);
@@ -181,21 +880,96 @@ fn main() -> Result<()> {
     let shared_state_menu = Arc::clone(&shared_state);
     let hotkey_manager_menu = Arc::clone(&hotkey_manager);
     let popup_weak_ctrlv = popup_weak.clone();
+    let history_weak_menu = history_weak.clone();
+    let (selection_tx, selection_rx) = crossbeam_channel::unbounded::<CapturedSelection>();
     #[cfg(target_os = "macos")]
     let monitor_error_rx = input::keyboard_monitor_error_receiver();
 
     // 启动键盘监控（监控 Ctrl+V）
     input::start_keyboard_monitor();
 
+    // 空闲一段时间后释放缓存和连接池，下次使用时再加载
+    let shared_state_idle = Arc::clone(&shared_state);
+    let idle_timer = slint::Timer::default();
+    idle_timer.start(slint::TimerMode::Repeated, Duration::from_secs(60), move || {
+        trim_if_idle(&shared_state_idle, Instant::now());
+    });
+
     let timer = slint::Timer::default();
     timer.start(slint::TimerMode::Repeated, Duration::from_millis(50), move || {
         // Check for hotkey events
         let hotkey_rx = hotkey::hotkey_event_receiver();
         if let Ok(event) = hotkey_rx.try_recv() {
-            if let Ok(manager) = hotkey_manager_timer.lock() {
-                if manager.is_translate_hotkey(&event) {
-                    handle_translate_hotkey(&popup_weak_timer, &shared_state_timer, &rt_timer);
+            // 托盘里暂停时照常取出事件，只是不处理
+            let action = if tray::is_paused() {
+                None
+            } else {
+                hotkey_manager_timer.lock().ok().and_then(|manager| manager.action_for(&event))
+            };
+            // 按住不放或连按两下时只处理第一次，避免重复复制和互相覆盖的结果
+            let action = action.filter(|_| {
+                let mut state = shared_state_timer.lock().unwrap();
+                let now = Instant::now();
+                let window = Duration::from_millis(state.config.hotkey_debounce_ms);
+                if hotkey::is_bounce(state.last_triggered, now, window) {
+                    return false;
+                }
+                state.last_triggered = Some(now);
+                true
+            });
+            match action {
+                Some(action @ (HotkeyAction::Translate | HotkeyAction::TranslateTo(_))) => {
+                    let (missing_key, target) = {
+                        let state = shared_state_timer.lock().unwrap();
+                        let target = match action {
+                            HotkeyAction::TranslateTo(i) => state.config.target_hotkeys.get(i).map(|b| b.target_lang.clone()),
+                            _ => None,
+                        };
+                        (state.config.active_provider_missing_key(), target)
+                    };
+                    if missing_key {
+                        // 没填密钥时直接引导到设置页，不去复制选中内容也不发请求
+                        open_settings_window(&shared_state_menu, &settings_window_timer, &hotkey_manager_menu, &rt_timer, true);
+                    } else {
+                        handle_translate_hotkey(&shared_state_timer, &selection_tx, target);
+                    }
+                }
+                Some(HotkeyAction::Paste) => {
+                    // 复制选区要等一会儿，放到后台线程，不卡界面
+                    let shared_state = Arc::clone(&shared_state_timer);
+                    let rt = Arc::clone(&rt_timer);
+                    std::thread::spawn(move || handle_paste_hotkey(&shared_state, &rt));
                 }
+                Some(HotkeyAction::Ocr) => {
+                    handle_ocr_hotkey(&popup_weak_timer, &review_weak_timer, &shared_state_timer, &rt_timer, &selection_tx)
+                }
+                None => {}
+            }
+        }
+        // 后台线程复制好的选中内容
+        if let Ok(selection) = selection_rx.try_recv() {
+            show_captured_selection(&popup_weak_timer, &review_weak_timer, &shared_state_timer, &rt_timer, selection);
+        }
+        tray::show_pending_notice(&tray);
+        tray::apply_pending_icon_state(&tray);
+        if tray::take_menu_dirty() {
+            let config = shared_state_timer.lock().unwrap().config.clone();
+            tray::rebuild_menu(&tray, &config);
+        }
+        // 主题改了，所有窗口一起换色，不用重启
+        if appearance::take_theme_dirty() {
+            let scheme = SharedString::from(appearance::scheme(shared_state_timer.lock().unwrap().config.theme));
+            if let Some(popup) = popup_weak_timer.upgrade() {
+                popup.global::<Theme>().set_scheme(scheme.clone());
+            }
+            if let Some(review) = review_weak_timer.upgrade() {
+                review.global::<Theme>().set_scheme(scheme.clone());
+            }
+            if let Some(history) = history_weak_menu.upgrade() {
+                history.global::<Theme>().set_scheme(scheme.clone());
+            }
+            if let Some(win) = settings_window_timer.borrow().as_ref() {
+                win.global::<Theme>().set_scheme(scheme);
             }
         }
 
@@ -203,8 +977,27 @@ fn main() -> Result<()> {
         let menu_rx = tray::menu_event_receiver();
         if let Ok(event) = menu_rx.try_recv() {
             match tray::handle_menu_event(&event) {
+                tray::MenuAction::ToggleEnabled => tray::toggle_paused(&tray),
+                tray::MenuAction::SelectProvider(id) => {
+                    let mut state = shared_state_menu.lock().unwrap();
+                    if state.config.get_provider(&id).is_some() && state.config.active_provider_id != id {
+                        state.config.active_provider_id = id;
+                        if let Err(e) = state.config.save() {
+                            error!("保存配置失败: {}", e);
+                        }
+                    }
+                    // 菜单自己切换了被点项的勾选，重建一次保证只勾当前服务
+                    tray::rebuild_menu(&tray, &state.config);
+                }
+                tray::MenuAction::OpenHistory => {
+                    if let Some(win) = history_weak_menu.upgrade() {
+                        show_history_entries(&win);
+                        win.set_clipboard_error(SharedString::new());
+                        win.show().ok();
+                    }
+                }
                 tray::MenuAction::OpenSettings => {
-                    open_settings_window(&shared_state_menu, &settings_window_timer, &hotkey_manager_menu);
+                    open_settings_window(&shared_state_menu, &settings_window_timer, &hotkey_manager_menu, &rt_timer, false);
                 }
                 tray::MenuAction::Exit => std::process::exit(0),
                 tray::MenuAction::None => {}
@@ -220,6 +1013,14 @@ fn main() -> Result<()> {
             }
         }
 
+        // 点到别的应用后关闭弹窗
+        if let Some(popup) = popup_weak_ctrlv.upgrade() {
+            if popup.window().is_visible() {
+                dismiss_if_unfocused(&popup, &shared_state_timer);
+                close_if_idle(&popup, &shared_state_timer);
+            }
+        }
+
         // Check for captured hotkey in settings window
         if let Some(ref win) = *settings_window_capture.borrow() {
             if win.get_hotkey_recording() {
@@ -240,6 +1041,14 @@ fn main() -> Result<()> {
         }
     });
 
+    // 等事件循环跑起来后再弹权限提示
+    if show_permission_alert {
+        #[cfg(target_os = "macos")]
+        slint::Timer::single_shot(Duration::from_millis(300), || {
+            show_macos_permission_alert_once("");
+        });
+    }
+
     // 使用 run_event_loop_until_quit 让程序在所有窗口关闭后继续运行
     // 只有托盘菜单的 Exit 或调用 quit_event_loop() 才会退出
     slint::run_event_loop_until_quit()?;
@@ -286,6 +1095,20 @@ fn apply_macos_font_family_settings(component: &SettingsWindow) {
     }
 }
 
+#[cfg(target_os = "macos")]
+fn apply_macos_font_family_review(component: &ReviewWindow) {
+    if let Some(font_family) = select_macos_font_family() {
+        component.global::<crate::Theme>().set_font_family(SharedString::from(font_family));
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn apply_macos_font_family_history(component: &HistoryWindow) {
+    if let Some(font_family) = select_macos_font_family() {
+        component.global::<crate::Theme>().set_font_family(SharedString::from(font_family));
+    }
+}
+
 #[cfg(target_os = "macos")]
 fn select_macos_font_family() -> Option<&'static str> {
     if std::path::Path::new("/System/Library/Fonts/Hiragino Sans GB.ttc").exists() {
@@ -305,18 +1128,116 @@ fn apply_macos_font_family_popup(_component: &TranslatePopup) {}
 #[cfg(not(target_os = "macos"))]
 fn apply_macos_font_family_settings(_component: &SettingsWindow) {}
 
-/// Open the settings window
-fn open_settings_window(
+#[cfg(not(target_os = "macos"))]
+fn apply_macos_font_family_review(_component: &ReviewWindow) {}
+
+#[cfg(not(target_os = "macos"))]
+fn apply_macos_font_family_history(_component: &HistoryWindow) {}
+
+/// Bind the paste, OCR and per-language hotkeys from `config`, replacing whatever was bound before
+fn register_extra_hotkeys(manager: &mut HotkeyManager, config: &Config) {
+    // 附加快捷键是可选的，注册失败只记录，不影响主快捷键
+    for (action, hotkey) in [(HotkeyAction::Paste, &config.paste_hotkey), (HotkeyAction::Ocr, &config.ocr_hotkey)] {
+        if let Err(e) = manager.set_extra_hotkey(action, hotkey) {
+            error!("注册快捷键 {:?} 失败({}): {}", action, hotkey, e);
+        }
+    }
+    let target_hotkeys: Vec<&str> = config.target_hotkeys.iter().map(|b| b.hotkey.as_str()).collect();
+    if let Err(e) = manager.set_target_hotkeys(&target_hotkeys) {
+        error!("注册目标语言快捷键失败: {}", e);
+    }
+}
+
+/// Push the key simulation settings into the input module, which keeps its own copy
+fn apply_input_settings(config: &Config) {
+    input::set_hotkey_log_enabled(config.hotkey_log_enabled);
+    input::set_send_modifier(config.send_modifier);
+    input::set_key_event_delay_ms(config.key_event_delay_ms);
+}
+
+/// After the whole config was swapped (import, reset): re-register every hotkey,
+/// re-apply language, theme and key simulation, then rebuild the settings window showing `status`
+#[allow(clippy::too_many_arguments)]
+fn reload_settings_window(
+    w: &SettingsWindow,
+    previous_hotkey: &str,
+    config: &Config,
+    mut status: String,
     shared_state: &Arc<Mutex<SharedState>>,
     settings_window: &Rc<RefCell<Option<SettingsWindow>>>,
     hotkey_manager: &Arc<Mutex<HotkeyManager>>,
+    rt: &Arc<tokio::runtime::Runtime>,
 ) {
-    struct PromptPresetDraft {
-        presets: Vec<PromptPreset>,
-        selected: usize,
+    // 先切换语言，下面的提示才用新的界面语言；init 会丢掉 locales/ 里的覆盖文字，要重新加载
+    i18n::init(&config.ui_language);
+    i18n::load_locale(&config.ui_locale);
+    if config.hotkey != previous_hotkey {
+        let t = i18n::t();
+        let failed = if hotkey::is_reserved_hotkey(&config.hotkey) {
+            Some(t.hotkey_reserved.replace("{hotkey}", &config.hotkey).replace("{previous}", previous_hotkey))
+        } else {
+            hotkey_manager
+                .lock()
+                .map_err(|e| e.to_string())
+                .and_then(|mut mgr| mgr.update_hotkey(&config.hotkey).map_err(|e| e.to_string()))
+                .err()
+                .map(|e| {
+                    t.hotkey_register_failed
+                        .replace("{hotkey}", &config.hotkey)
+                        .replace("{previous}", previous_hotkey)
+                        .replace("{error}", &e)
+                })
+        };
+        // 和 apply_captured_hotkey 一样退回原来的组合，设置里不能显示一个没注册上的快捷键
+        if let Some(message) = failed {
+            error!("注册新快捷键 {} 失败，保留 {}", config.hotkey, previous_hotkey);
+            let mut state = shared_state.lock().unwrap();
+            state.config.hotkey = previous_hotkey.to_string();
+            if let Err(e) = state.config.save() {
+                error!("写入配置失败: {}", e);
+            }
+            status.push_str("; ");
+            status.push_str(&message);
+        }
     }
+    match hotkey_manager.lock() {
+        Ok(mut mgr) => register_extra_hotkeys(&mut mgr, config),
+        Err(e) => error!("快捷键管理器不可用: {}", e),
+    }
+    apply_input_settings(config);
+    tray::mark_menu_dirty();
+    appearance::mark_theme_dirty();
 
-    fn sync_prompt_preset_ui(win: &SettingsWindow, draft: &PromptPresetDraft) {
+    // 几乎每个字段都可能变了，重建设置窗口比逐项同步可靠
+    input::stop_hotkey_capture();
+    w.hide().ok();
+    *settings_window.borrow_mut() = None;
+    let shared_state = Arc::clone(shared_state);
+    let settings_window = Rc::clone(settings_window);
+    let hotkey_manager = Arc::clone(hotkey_manager);
+    let rt = Arc::clone(rt);
+    slint::Timer::single_shot(Duration::ZERO, move || {
+        open_settings_window(&shared_state, &settings_window, &hotkey_manager, &rt, false);
+        if let Some(ref win) = *settings_window.borrow() {
+            win.set_settings_io_status(SharedString::from(status));
+        }
+    });
+}
+
+/// Open the settings window
+fn open_settings_window(
+    shared_state: &Arc<Mutex<SharedState>>,
+    settings_window: &Rc<RefCell<Option<SettingsWindow>>>,
+    hotkey_manager: &Arc<Mutex<HotkeyManager>>,
+    rt: &Arc<tokio::runtime::Runtime>,
+    api_key_required: bool,
+) {
+    struct PromptPresetDraft {
+        presets: Vec<PromptPreset>,
+        selected: usize,
+    }
+
+    fn sync_prompt_preset_ui(win: &SettingsWindow, draft: &PromptPresetDraft) {
         let names: Vec<SharedString> = draft.presets.iter().map(|p| SharedString::from(&p.name)).collect();
         win.set_prompt_preset_names(ModelRc::new(VecModel::from(names)));
         win.set_prompt_preset_index(draft.selected as i32);
@@ -324,12 +1245,105 @@ fn open_settings_window(
             win.set_prompt_preset_name(SharedString::from(&preset.name));
             win.set_prompt_system_template(SharedString::from(&preset.system_template));
             win.set_prompt_user_template(SharedString::from(&preset.user_template));
+            let temperature = preset.temperature.map(|t| t.to_string()).unwrap_or_default();
+            win.set_prompt_temperature(SharedString::from(temperature));
+            let max_tokens = preset.max_tokens.map(|n| n.to_string()).unwrap_or_default();
+            win.set_prompt_max_tokens(SharedString::from(max_tokens));
+            win.set_prompt_response_json(preset.response_is_json);
+            win.set_prompt_json_path(SharedString::from(&preset.json_path));
             win.set_prompt_preset_deletable(!preset.is_preset);
+            set_prompt_template_warning(win, preset);
+            let examples: Vec<PromptExample> = preset
+                .examples
+                .iter()
+                .map(|(source, target)| PromptExample { source: source.as_str().into(), target: target.as_str().into() })
+                .collect();
+            win.set_prompt_examples(ModelRc::new(VecModel::from(examples)));
         } else {
             win.set_prompt_preset_deletable(false);
         }
     }
 
+    fn sync_provider_ui(win: &SettingsWindow, provider: &ProviderConfig) {
+        win.set_api_key(SharedString::from(&provider.api_key));
+        win.set_api_base(SharedString::from(&provider.api_base));
+        win.set_model(SharedString::from(&provider.model));
+        let price_text = |price: Option<f64>| price.map(|p| p.to_string()).unwrap_or_default();
+        win.set_input_price(SharedString::from(price_text(provider.input_price_per_1k)));
+        win.set_output_price(SharedString::from(price_text(provider.output_price_per_1k)));
+        win.set_request_timeout(SharedString::from(provider.timeout_secs.to_string()));
+        win.set_org_id(SharedString::from(provider.org_id.as_deref().unwrap_or_default()));
+        win.set_project_id(SharedString::from(provider.project_id.as_deref().unwrap_or_default()));
+        let formality_idx = provider
+            .deepl_formality
+            .and_then(|f| DeepLFormality::ALL.iter().position(|x| *x == f))
+            .map_or(0, |i| i + 1);
+        win.set_deepl_formality_index(formality_idx as i32);
+        win.set_deepl_preserve_formatting(provider.deepl_preserve_formatting.unwrap_or(false));
+        let mut headers: Vec<(&String, &String)> = provider.extra_headers.iter().collect();
+        headers.sort();
+        let headers: Vec<HeaderRow> = headers
+            .into_iter()
+            .map(|(name, value)| HeaderRow { name: name.as_str().into(), value: value.as_str().into() })
+            .collect();
+        win.set_extra_headers(ModelRc::new(VecModel::from(headers)));
+        win.set_provider_type(SharedString::from(provider.provider_type.as_str()));
+        win.set_provider_is_custom(!provider.is_preset);
+        win.set_provider_deletable(!Config::is_builtin_provider(&provider.id));
+        let type_idx = ProviderType::ALL
+            .iter()
+            .position(|t| *t == provider.provider_type)
+            .unwrap_or(0);
+        win.set_provider_type_index(type_idx as i32);
+        win.set_provider_test_status(SharedString::new());
+        // 模型列表属于上一个服务商
+        win.set_model_options(ModelRc::default());
+        win.set_model_option_index(-1);
+        win.set_models_fetch_error(SharedString::new());
+    }
+
+    /// Provider fields as currently typed in the window
+    fn read_provider_fields(w: &SettingsWindow, p: &mut ProviderConfig) {
+        p.api_key = w.get_api_key().to_string();
+        p.api_base = w.get_api_base().to_string();
+        p.model = w.get_model().to_string();
+        p.input_price_per_1k = usage::parse_price(&w.get_input_price());
+        p.output_price_per_1k = usage::parse_price(&w.get_output_price());
+        if let Some(secs) = parse_timeout(&w.get_request_timeout()) {
+            p.timeout_secs = secs;
+        }
+        let optional = |value: SharedString| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        p.org_id = optional(w.get_org_id());
+        p.project_id = optional(w.get_project_id());
+        p.deepl_formality = (w.get_deepl_formality_index() - 1)
+            .try_into()
+            .ok()
+            .and_then(|i: usize| DeepLFormality::ALL.get(i).copied());
+        p.deepl_preserve_formatting = w.get_deepl_preserve_formatting().then_some(true);
+        // 没填名称的行还在编辑中，不保存
+        p.extra_headers = w
+            .get_extra_headers()
+            .iter()
+            .filter(|row| !row.name.trim().is_empty())
+            .map(|row| (row.name.trim().to_string(), row.value.trim().to_string()))
+            .collect();
+    }
+
+    /// Reload the provider ComboBoxes after the list changed, returns the active index
+    fn refresh_provider_list(win: &SettingsWindow, config: &Config) -> usize {
+        let names: Vec<SharedString> = config.providers.iter().map(|p| SharedString::from(&p.name)).collect();
+        win.set_provider_names(ModelRc::new(VecModel::from(names)));
+        // 换了 model 之后 ComboBox 会重置下标，需要重新设置
+        let idx = config.provider_index(&config.active_provider_id).unwrap_or(0);
+        win.set_provider_index(idx as i32);
+        win.set_auto_pick_provider_index(config.provider_index(&config.auto_pick_provider_id).unwrap_or(0) as i32);
+        win.set_fallback_providers(SharedString::from(format_provider_list(config, &config.fallback_provider_ids)));
+        if let Some(p) = config.providers.get(idx) {
+            sync_provider_ui(win, p);
+        }
+        idx
+    }
+
     fn update_selected_preset_from_ui(win: &SettingsWindow, draft: &mut PromptPresetDraft) {
         let Some(preset) = draft.presets.get_mut(draft.selected) else { return; };
         let name = win.get_prompt_preset_name().to_string();
@@ -343,6 +1357,40 @@ fn open_settings_window(
         } else {
             user_template
         };
+        // 留空或无法解析时回落到全局温度
+        preset.temperature = win
+            .get_prompt_temperature()
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|t| t.is_finite())
+            .map(|t| t.clamp(0.0, 2.0));
+        // 留空则使用服务默认值（Anthropic 为 4096）
+        preset.max_tokens = win
+            .get_prompt_max_tokens()
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|n| *n > 0);
+        preset.response_is_json = win.get_prompt_response_json();
+        preset.json_path = win.get_prompt_json_path().trim().to_string();
+        preset.examples = win
+            .get_prompt_examples()
+            .iter()
+            .map(|row| (row.source.to_string(), row.target.to_string()))
+            .collect();
+        set_prompt_template_warning(win, preset);
+    }
+
+    fn set_prompt_template_warning(win: &SettingsWindow, preset: &PromptPreset) {
+        let warning = if !preset.has_text_placeholder() {
+            i18n::t().prompt_missing_text
+        } else if preset.response_is_json && !preset.mentions_json() {
+            i18n::t().prompt_missing_json
+        } else {
+            ""
+        };
+        win.set_prompt_template_warning(SharedString::from(warning));
     }
 
     fn next_custom_preset(draft: &PromptPresetDraft) -> PromptPreset {
@@ -360,6 +1408,11 @@ fn open_settings_window(
             system_template: String::new(),
             user_template: "{{text}}".to_string(),
             is_preset: false,
+            temperature: None,
+            max_tokens: None,
+            examples: Vec::new(),
+            response_is_json: false,
+            json_path: String::new(),
         }
     }
 
@@ -367,6 +1420,11 @@ fn open_settings_window(
         if let Some(ref win) = *settings_window.borrow() {
             win.set_hotkey_recording(false);
             input::stop_hotkey_capture();
+            if api_key_required {
+                win.set_api_key_required(true);
+            }
+            let always_center = shared_state.lock().unwrap().config.recenter_settings_window;
+            keep_window_on_screen(win.window(), always_center);
             win.show().ok();
             return;
         }
@@ -374,17 +1432,25 @@ fn open_settings_window(
 
     let win = match SettingsWindow::new() {
         Ok(w) => w,
-        Err(e) => { eprintln!("Failed to create settings: {}", e); return; }
+        Err(e) => { error!("Failed to create settings: {}", e); return; }
     };
     apply_macos_font_family_settings(&win);
+    win.global::<Theme>().set_scheme(appearance::scheme(shared_state.lock().unwrap().config.theme).into());
 
     win.set_hotkey_recording(false);
+    win.set_api_key_required(api_key_required);
     input::stop_hotkey_capture();
 
-    // 以磁盘为准，避免内存配置与文件不一致
-    if let Ok(latest) = Config::load() {
-        if let Ok(mut state) = shared_state.lock() {
-            state.config = latest;
+    // 磁盘上有更新的版本（其他实例写入）才重新加载，避免覆盖尚未落盘的修改
+    if let Ok(mut state) = shared_state.lock() {
+        match state.config.reload_if_newer() {
+            Ok(Some(latest)) => {
+                state.config = latest;
+                tray::mark_menu_dirty();
+                appearance::mark_theme_dirty();
+            }
+            Ok(None) => {}
+            Err(e) => error!("重新加载配置失败: {}", e),
         }
     }
 
@@ -392,22 +1458,67 @@ fn open_settings_window(
     set_settings_i18n_texts(&win);
 
     // Load config into UI
-    let (provider_idx, lang_idx, prompt_presets, active_prompt_id, provider_names) = {
+    let (provider_idx, lang_idx, prompt_presets, active_prompt_id, provider_names, auto_pick_idx) = {
         let state = shared_state.lock().unwrap();
         let config = &state.config;
 
         win.set_hotkey(SharedString::from(&config.hotkey));
+        win.set_hotkey_error(SharedString::new());
         win.set_hotkey_log_enabled(config.hotkey_log_enabled);
+        win.set_history_enabled(config.history_enabled);
+        win.set_recenter_settings(config.recenter_settings_window);
+        let modifier_idx = SendModifier::available().iter().position(|m| *m == config.send_modifier).unwrap_or(0);
+        win.set_send_modifier_index(modifier_idx as i32);
+        let theme_idx = ThemeMode::ALL.iter().position(|m| *m == config.theme).unwrap_or(0);
+        win.set_theme_index(theme_idx as i32);
+        win.set_google_smart_join(config.google_smart_join);
+        win.set_auto_expand_long(config.auto_expand_long_results);
+        win.set_preview_long(config.preview_long_selections);
+        win.set_popup_no_activate(config.popup_no_activate);
+        win.set_popup_always_on_top(config.popup_always_on_top);
+        win.set_dismiss_on_focus_loss(config.dismiss_on_focus_loss);
+        win.set_auto_close_secs(SharedString::from(config.auto_close_secs.map(|s| s.to_string()).unwrap_or_default()));
+        win.set_auto_copy_result(config.auto_copy_result);
+        win.set_stream_responses(config.stream_responses);
+        win.set_show_raw_response(config.show_raw_response);
+        win.set_retry_count(SharedString::from(config.retry_count.to_string()));
+        win.set_retry_base_delay(SharedString::from(config.retry_base_delay_ms.to_string()));
+        win.set_copy_delay(SharedString::from(config.copy_delay_ms.to_string()));
+        win.set_paste_delay(SharedString::from(config.paste_delay_ms.to_string()));
+        win.set_focus_delay(SharedString::from(config.focus_delay_ms.to_string()));
+        win.set_key_event_delay(SharedString::from(config.key_event_delay_ms.to_string()));
+        win.set_cache_capacity(SharedString::from(config.cache_capacity.to_string()));
+        win.set_max_requests_per_hour(SharedString::from(config.max_requests_per_hour.map(|n| n.to_string()).unwrap_or_default()));
+        win.set_max_chunk_chars(SharedString::from(config.max_chunk_chars.to_string()));
+        win.set_proxy_url(SharedString::from(config.proxy_url.clone().unwrap_or_default()));
+        win.set_retry_empty_result(config.retry_empty_result);
+        win.set_batch_translate(config.batch_translate);
+        win.set_batch_delimiter(SharedString::from(&config.batch_delimiter));
+        win.set_multi_target(config.multi_target);
+        win.set_target_langs(SharedString::from(config.target_langs.join(", ")));
+        win.set_secondary_lang(SharedString::from(&config.secondary_lang));
+        win.set_fallback_providers(SharedString::from(format_provider_list(config, &config.fallback_provider_ids)));
+        win.set_skip_patterns(SharedString::from(config.skip_patterns.join("\n")));
+        win.set_notify_skipped(config.notify_skipped);
+        win.set_glossary_text(SharedString::from(glossary::format_lines(&config.glossary)));
+        win.set_popup_width(SharedString::from(config.popup_width.to_string()));
+        win.set_popup_height(SharedString::from(config.popup_height.to_string()));
+        win.set_auto_pick(config.auto_pick);
+        let strategy_idx = PickStrategy::ALL.iter().position(|s| *s == config.auto_pick_strategy).unwrap_or(0);
+        win.set_auto_pick_strategy_index(strategy_idx as i32);
 
         let idx = config
             .provider_index(&config.active_provider_id)
             .unwrap_or(0)
             .min(config.providers.len().saturating_sub(1));
 
+        let type_names: Vec<SharedString> = ProviderType::ALL
+            .iter()
+            .map(|t| SharedString::from(t.label()))
+            .collect();
+        win.set_provider_type_names(ModelRc::new(VecModel::from(type_names)));
         if let Some(p) = config.providers.get(idx) {
-            win.set_api_key(SharedString::from(&p.api_key));
-            win.set_api_base(SharedString::from(&p.api_base));
-            win.set_model(SharedString::from(&p.model));
+            sync_provider_ui(&win, p);
         }
 
         let provider_names: Vec<SharedString> = config
@@ -416,12 +1527,14 @@ fn open_settings_window(
             .map(|p| SharedString::from(&p.name))
             .collect();
         let lang_index = i18n::language_to_index(&config.ui_language);
+        let auto_pick_idx = config.provider_index(&config.auto_pick_provider_id).unwrap_or(0);
         (
             idx as i32,
             lang_index,
             config.prompt_presets.clone(),
             config.active_prompt_preset_id.clone(),
             provider_names,
+            auto_pick_idx as i32,
         )
     };
 
@@ -430,11 +1543,10 @@ fn open_settings_window(
     // 必须在设置 provider_names 之后再设置 provider_index，
     // 因为 ComboBox 在设置 model 时可能会重置 current-index
     win.set_provider_index(provider_idx);
+    win.set_auto_pick_provider_index(auto_pick_idx);
 
     // Set language list and index
-    let language_names: Vec<SharedString> = vec![
-        "Auto".into(), "English".into(), "中文".into(),
-    ];
+    let language_names: Vec<SharedString> = i18n::language_names().into_iter().map(SharedString::from).collect();
     win.set_language_names(ModelRc::new(VecModel::from(language_names)));
     win.set_language_index(lang_idx);
 
@@ -463,6 +1575,9 @@ fn open_settings_window(
             if w.get_provider_index() != provider_idx_sync {
                 w.set_provider_index(provider_idx_sync);
             }
+            if w.get_auto_pick_provider_index() != auto_pick_idx {
+                w.set_auto_pick_provider_index(auto_pick_idx);
+            }
             if w.get_language_index() != lang_idx_sync {
                 w.set_language_index(lang_idx_sync);
             }
@@ -480,9 +1595,9 @@ fn open_settings_window(
         autosave_timer_save.stop();
         let shared_state = Arc::clone(&shared_state_autosave);
         autosave_timer_save.start(slint::TimerMode::SingleShot, Duration::from_millis(450), move || {
-            if let Ok(state) = shared_state.lock() {
+            if let Ok(mut state) = shared_state.lock() {
                 if let Err(e) = state.config.save() {
-                    eprintln!("自动保存配置失败: {}", e);
+                    error!("自动保存配置失败: {}", e);
                 }
             }
         });
@@ -501,13 +1616,93 @@ fn open_settings_window(
 
             config.hotkey = w.get_hotkey().to_string();
             config.hotkey_log_enabled = w.get_hotkey_log_enabled();
+            config.history_enabled = w.get_history_enabled();
+            config.recenter_settings_window = w.get_recenter_settings();
+            if let Some(theme) = ThemeMode::ALL.get(w.get_theme_index().max(0) as usize) {
+                config.theme = *theme;
+            }
+            if let Some(modifier) = SendModifier::available().get(w.get_send_modifier_index().max(0) as usize) {
+                config.send_modifier = *modifier;
+            }
+            config.google_smart_join = w.get_google_smart_join();
+            config.auto_expand_long_results = w.get_auto_expand_long();
+            config.preview_long_selections = w.get_preview_long();
+            config.popup_no_activate = w.get_popup_no_activate();
+            config.popup_always_on_top = w.get_popup_always_on_top();
+            config.dismiss_on_focus_loss = w.get_dismiss_on_focus_loss();
+            // 留空或 0 表示不自动关闭
+            let auto_close = w.get_auto_close_secs();
+            if auto_close.trim().is_empty() {
+                config.auto_close_secs = None;
+            } else if let Ok(secs) = auto_close.trim().parse::<u32>() {
+                config.auto_close_secs = Some(secs);
+            }
+            config.auto_copy_result = w.get_auto_copy_result();
+            config.stream_responses = w.get_stream_responses();
+            config.show_raw_response = w.get_show_raw_response();
+            config.auto_pick = w.get_auto_pick();
+            if let Some(p) = config.providers.get(w.get_auto_pick_provider_index().max(0) as usize) {
+                config.auto_pick_provider_id = p.id.clone();
+            }
+            if let Some(strategy) = PickStrategy::ALL.get(w.get_auto_pick_strategy_index().max(0) as usize) {
+                config.auto_pick_strategy = *strategy;
+            }
+            if let Ok(count) = w.get_retry_count().trim().parse::<u32>() {
+                config.retry_count = count.min(config::MAX_RETRY_COUNT);
+            }
+            if let Ok(delay) = w.get_retry_base_delay().trim().parse::<u64>() {
+                config.retry_base_delay_ms = delay.min(config::MAX_RETRY_BASE_DELAY_MS);
+            }
+            if let Ok(delay) = w.get_copy_delay().trim().parse::<u64>() {
+                config.copy_delay_ms = delay.min(config::MAX_INPUT_DELAY_MS);
+            }
+            if let Ok(delay) = w.get_paste_delay().trim().parse::<u64>() {
+                config.paste_delay_ms = delay.min(config::MAX_INPUT_DELAY_MS);
+            }
+            if let Ok(delay) = w.get_focus_delay().trim().parse::<u64>() {
+                config.focus_delay_ms = delay.min(config::MAX_INPUT_DELAY_MS);
+            }
+            if let Ok(delay) = w.get_key_event_delay().trim().parse::<u64>() {
+                config.key_event_delay_ms = delay.min(config::MAX_INPUT_DELAY_MS);
+            }
+            config.retry_empty_result = w.get_retry_empty_result();
+            // 留空或 0 表示不限制
+            let max_requests = w.get_max_requests_per_hour();
+            if max_requests.trim().is_empty() {
+                config.max_requests_per_hour = None;
+            } else if let Ok(limit) = max_requests.trim().parse::<u32>() {
+                config.max_requests_per_hour = (limit > 0).then_some(limit);
+            }
+            if let Ok(capacity) = w.get_cache_capacity().trim().parse::<usize>() {
+                config.cache_capacity = capacity.min(config::MAX_CACHE_CAPACITY);
+            }
+            let proxy_url = w.get_proxy_url().trim().to_string();
+            config.proxy_url = (!proxy_url.is_empty()).then_some(proxy_url);
+            if let Ok(max_chars) = w.get_max_chunk_chars().trim().parse::<usize>() {
+                config.max_chunk_chars = if max_chars == 0 { 0 } else { max_chars.max(config::MIN_CHUNK_CHARS) };
+            }
+            config.batch_translate = w.get_batch_translate();
+            config.batch_delimiter = w.get_batch_delimiter().to_string();
+            config.multi_target = w.get_multi_target();
+            config.target_langs = w.get_target_langs().split([',', '，', ' ']).map(str::to_string).collect();
+            config.secondary_lang = w.get_secondary_lang().to_string();
+            config.fallback_provider_ids = parse_provider_list(&config, &w.get_fallback_providers());
+            config.skip_patterns = skip::parse_lines(&w.get_skip_patterns());
+            config.notify_skipped = w.get_notify_skipped();
+            config.glossary = glossary::parse_lines(&w.get_glossary_text());
+            // 尺寸无法解析时保留原值
+            let parse_size = |text: SharedString| text.trim().parse::<f32>().ok().filter(|v| v.is_finite());
+            if let Some(width) = parse_size(w.get_popup_width()) {
+                config.popup_width = width.clamp(config::POPUP_MIN_SIZE.0, config::POPUP_MAX_SIZE.0);
+            }
+            if let Some(height) = parse_size(w.get_popup_height()) {
+                config.popup_height = height.clamp(config::POPUP_MIN_SIZE.1, config::POPUP_MAX_SIZE.1);
+            }
             config.ui_language = i18n::index_to_language(w.get_language_index());
 
             let idx = (*current_provider_index.borrow()).max(0) as usize;
             if let Some(p) = config.providers.get_mut(idx) {
-                p.api_key = w.get_api_key().to_string();
-                p.api_base = w.get_api_base().to_string();
-                p.model = w.get_model().to_string();
+                read_provider_fields(w, p);
                 config.active_provider_id = p.id.clone();
             }
 
@@ -521,11 +1716,17 @@ fn open_settings_window(
                 config.normalize();
             }
 
-            let hotkey_log_enabled = config.hotkey_log_enabled;
+            apply_input_settings(&config);
             if let Ok(mut state) = shared_state.lock() {
+                clear_stale_cache(&state.translation_cache, &state.config, &config);
+                if tray::menu_key(&state.config) != tray::menu_key(&config) {
+                    tray::mark_menu_dirty();
+                }
+                if state.config.theme != config.theme {
+                    appearance::mark_theme_dirty();
+                }
                 state.config = config;
             }
-            input::set_hotkey_log_enabled(hotkey_log_enabled);
         })
     };
 
@@ -552,18 +1753,15 @@ fn open_settings_window(
 
             if let Ok(mut state) = shared_state_sel.lock() {
                 if let Some(prev) = state.config.providers.get_mut(prev_idx) {
-                    prev.api_key = w.get_api_key().to_string();
-                    prev.api_base = w.get_api_base().to_string();
-                    prev.model = w.get_model().to_string();
+                    read_provider_fields(&w, prev);
                 }
                 if let Some(next) = state.config.providers.get(new_idx) {
-                    w.set_api_key(SharedString::from(&next.api_key));
-                    w.set_api_base(SharedString::from(&next.api_base));
-                    w.set_model(SharedString::from(&next.model));
+                    sync_provider_ui(&w, next);
                 }
             }
 
             *current_provider_index_sel.borrow_mut() = new_idx as i32;
+            w.set_api_key_required(false);
             if w.get_provider_index() != new_idx as i32 {
                 w.set_provider_index(new_idx as i32);
             }
@@ -572,17 +1770,81 @@ fn open_settings_window(
         }
     });
 
+    // Add / delete user-defined providers
+    let shared_state_add_provider = Arc::clone(shared_state);
+    let win_weak_add_provider = win.as_weak();
+    let current_provider_index_add = Rc::clone(&current_provider_index);
+    let schedule_autosave_add_provider = Rc::clone(&schedule_autosave);
+    let apply_ui_to_state_add_provider = Rc::clone(&apply_ui_to_state);
+    win.on_add_provider(move || {
+        let Some(w) = win_weak_add_provider.upgrade() else { return; };
+        // 先把当前服务正在编辑的内容存下来
+        apply_ui_to_state_add_provider(&w);
+        let idx = {
+            let mut state = shared_state_add_provider.lock().unwrap();
+            state.config.add_custom_provider();
+            state.config.normalize();
+            tray::mark_menu_dirty();
+            refresh_provider_list(&w, &state.config)
+        };
+        *current_provider_index_add.borrow_mut() = idx as i32;
+        apply_ui_to_state_add_provider(&w);
+        schedule_autosave_add_provider();
+    });
+
+    let shared_state_del_provider = Arc::clone(shared_state);
+    let win_weak_del_provider = win.as_weak();
+    let current_provider_index_del = Rc::clone(&current_provider_index);
+    let schedule_autosave_del_provider = Rc::clone(&schedule_autosave);
+    let apply_ui_to_state_del_provider = Rc::clone(&apply_ui_to_state);
+    win.on_delete_provider(move || {
+        let Some(w) = win_weak_del_provider.upgrade() else { return; };
+        apply_ui_to_state_del_provider(&w);
+        let idx = {
+            let mut state = shared_state_del_provider.lock().unwrap();
+            let id = state.config.active_provider_id.clone();
+            if !state.config.remove_provider(&id) {
+                return;
+            }
+            tray::mark_menu_dirty();
+            refresh_provider_list(&w, &state.config)
+        };
+        *current_provider_index_del.borrow_mut() = idx as i32;
+        apply_ui_to_state_del_provider(&w);
+        schedule_autosave_del_provider();
+    });
+
+    // Handle provider type change (custom providers only)
+    let shared_state_type = Arc::clone(shared_state);
+    let win_weak_type = win.as_weak();
+    let current_provider_index_type = Rc::clone(&current_provider_index);
+    let schedule_autosave_type = Rc::clone(&schedule_autosave);
+    let apply_ui_to_state_type = Rc::clone(&apply_ui_to_state);
+    win.on_provider_type_selected(move |index| {
+        let Some(w) = win_weak_type.upgrade() else { return; };
+        let Some(new_type) = ProviderType::ALL.get(index.max(0) as usize).cloned() else { return; };
+        apply_ui_to_state_type(&w);
+        let idx = (*current_provider_index_type.borrow()).max(0) as usize;
+        if let Ok(mut state) = shared_state_type.lock() {
+            if let Some(p) = state.config.providers.get_mut(idx) {
+                if !p.is_preset && p.provider_type != new_type {
+                    p.provider_type = new_type;
+                }
+            }
+            state.config.normalize();
+            if let Some(p) = state.config.providers.get(idx) {
+                sync_provider_ui(&w, p);
+            }
+        }
+        schedule_autosave_type();
+    });
+
     // Handle language selection (preview)
     let win_weak_lang = win.as_weak();
     let schedule_autosave_lang = Rc::clone(&schedule_autosave);
     let apply_ui_to_state_lang = Rc::clone(&apply_ui_to_state);
     win.on_language_selected(move |name| {
-        let index = match name.as_str() {
-            "Auto" => 0,
-            "English" => 1,
-            "中文" => 2,
-            _ => 0,
-        };
+        let index = i18n::language_names().iter().position(|n| *n == name.as_str()).unwrap_or(0) as i32;
         let new_lang = i18n::index_to_language(index);
         i18n::init(&new_lang);
         if let Some(w) = win_weak_lang.upgrade() {
@@ -666,6 +1928,228 @@ fn open_settings_window(
         }
     });
 
+    // Few-shot example rows of the selected preset
+    let win_weak_example_add = win.as_weak();
+    let prompt_draft_example_add = Rc::clone(&prompt_draft);
+    win.on_add_prompt_example(move || {
+        if let Some(w) = win_weak_example_add.upgrade() {
+            let mut draft = prompt_draft_example_add.borrow_mut();
+            update_selected_preset_from_ui(&w, &mut draft);
+            let selected = draft.selected;
+            if let Some(preset) = draft.presets.get_mut(selected) {
+                // 空行不参与翻译，填完后由 settings-changed 保存
+                preset.examples.push((String::new(), String::new()));
+            }
+            sync_prompt_preset_ui(&w, &draft);
+        }
+    });
+
+    let win_weak_example_remove = win.as_weak();
+    let prompt_draft_example_remove = Rc::clone(&prompt_draft);
+    let schedule_autosave_example = Rc::clone(&schedule_autosave);
+    let apply_ui_to_state_example = Rc::clone(&apply_ui_to_state);
+    win.on_remove_prompt_example(move |index| {
+        if let Some(w) = win_weak_example_remove.upgrade() {
+            {
+                let mut draft = prompt_draft_example_remove.borrow_mut();
+                update_selected_preset_from_ui(&w, &mut draft);
+                let selected = draft.selected;
+                if let Some(preset) = draft.presets.get_mut(selected) {
+                    if index >= 0 && (index as usize) < preset.examples.len() {
+                        preset.examples.remove(index as usize);
+                    }
+                }
+                sync_prompt_preset_ui(&w, &draft);
+            }
+            apply_ui_to_state_example(&w);
+            schedule_autosave_example();
+        }
+    });
+
+    // Import / export prompt presets via prompt_presets.json
+    let win_weak_prompt_import = win.as_weak();
+    let prompt_draft_import = Rc::clone(&prompt_draft);
+    let shared_state_import = Arc::clone(shared_state);
+    let schedule_autosave_import = Rc::clone(&schedule_autosave);
+    let apply_ui_to_state_import = Rc::clone(&apply_ui_to_state);
+    win.on_import_prompt_presets(move || {
+        let Some(w) = win_weak_prompt_import.upgrade() else { return; };
+        apply_ui_to_state_import(&w);
+        let t = i18n::t();
+        let result = {
+            let mut state = shared_state_import.lock().unwrap();
+            let before = state.config.clone();
+            Config::prompt_presets_path()
+                .and_then(|path| state.config.import_prompt_presets_from(&path))
+                .map(|report| {
+                    // 同 id 的预设被覆盖时，按旧模板缓存的译文不能再用
+                    clear_stale_cache(&state.translation_cache, &before, &state.config);
+                    (report, state.config.clone())
+                })
+        };
+        match result {
+            Ok((report, config)) => {
+                {
+                    let mut draft = prompt_draft_import.borrow_mut();
+                    draft.presets = config.prompt_presets.clone();
+                    draft.selected = config.prompt_preset_index(&config.active_prompt_preset_id).unwrap_or(0);
+                    sync_prompt_preset_ui(&w, &draft);
+                }
+                let mut status = t
+                    .prompt_import_done
+                    .replace("{added}", &report.added.to_string())
+                    .replace("{updated}", &report.updated.to_string());
+                if !report.issues.is_empty() {
+                    for issue in &report.issues {
+                        warn!("导入提示词预设时跳过: {}", issue);
+                    }
+                    status.push_str("; ");
+                    status.push_str(&t.prompt_import_issues.replace("{issues}", &report.issues.join(", ")));
+                }
+                w.set_prompt_io_status(SharedString::from(status));
+                schedule_autosave_import();
+            }
+            Err(e) => {
+                w.set_prompt_io_status(SharedString::from(t.prompt_io_failed.replace("{error}", &e.to_string())));
+            }
+        }
+    });
+
+    let win_weak_prompt_export = win.as_weak();
+    let shared_state_export = Arc::clone(shared_state);
+    let apply_ui_to_state_export = Rc::clone(&apply_ui_to_state);
+    win.on_export_prompt_presets(move || {
+        let Some(w) = win_weak_prompt_export.upgrade() else { return; };
+        apply_ui_to_state_export(&w);
+        let t = i18n::t();
+        let result = Config::prompt_presets_path().and_then(|path| {
+            let state = shared_state_export.lock().unwrap();
+            state.config.export_prompt_presets_to(&path).map(|count| (count, path))
+        });
+        let status = match result {
+            Ok((count, path)) => t
+                .prompt_export_done
+                .replace("{count}", &count.to_string())
+                .replace("{path}", &path.display().to_string()),
+            Err(e) => t.prompt_io_failed.replace("{error}", &e.to_string()),
+        };
+        w.set_prompt_io_status(SharedString::from(status));
+    });
+
+    // Full settings backup to a user-chosen JSON file
+    let win_weak_settings_export = win.as_weak();
+    let shared_state_settings_export = Arc::clone(shared_state);
+    let apply_ui_to_state_settings_export = Rc::clone(&apply_ui_to_state);
+    win.on_export_settings(move || {
+        let Some(w) = win_weak_settings_export.upgrade() else { return; };
+        apply_ui_to_state_settings_export(&w);
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .set_file_name("nanotrans-settings.json")
+            .save_file()
+        else {
+            return;
+        };
+        let t = i18n::t();
+        let result = {
+            let state = shared_state_settings_export.lock().unwrap();
+            state.config.export_to(&path, w.get_export_api_keys())
+        };
+        let status = match result {
+            Ok(()) => t.settings_export_done.replace("{path}", &path.display().to_string()),
+            Err(e) => t.prompt_io_failed.replace("{error}", &e.to_string()),
+        };
+        w.set_settings_io_status(SharedString::from(status));
+    });
+
+    let win_weak_settings_import = win.as_weak();
+    let shared_state_settings_import = Arc::clone(shared_state);
+    let settings_window_import = Rc::clone(settings_window);
+    let hotkey_manager_import = Arc::clone(hotkey_manager);
+    let rt_import = Arc::clone(rt);
+    let apply_ui_to_state_settings_import = Rc::clone(&apply_ui_to_state);
+    win.on_import_settings(move || {
+        let Some(w) = win_weak_settings_import.upgrade() else { return; };
+        apply_ui_to_state_settings_import(&w);
+        let Some(path) = rfd::FileDialog::new().add_filter("JSON", &["json"]).pick_file() else { return; };
+        let t = i18n::t();
+        let result = {
+            let mut state = shared_state_settings_import.lock().unwrap();
+            let previous_hotkey = state.config.hotkey.clone();
+            let before = state.config.clone();
+            state.config.import_from(&path).map(|report| {
+                clear_stale_cache(&state.translation_cache, &before, &state.config);
+                if let Err(e) = state.config.save() {
+                    error!("写入配置失败: {}", e);
+                }
+                (report, previous_hotkey, state.config.clone())
+            })
+        };
+        let (report, previous_hotkey, config) = match result {
+            Ok(imported) => imported,
+            Err(e) => {
+                w.set_settings_io_status(SharedString::from(t.prompt_io_failed.replace("{error}", &e.to_string())));
+                return;
+            }
+        };
+        for issue in &report.issues {
+            warn!("导入设置时跳过: {}", issue);
+        }
+        let mut status = t
+            .settings_import_done
+            .replace("{providers}", &(report.providers_added + report.providers_updated).to_string())
+            .replace("{presets}", &(report.presets_added + report.presets_updated).to_string());
+        if !report.issues.is_empty() {
+            status.push_str("; ");
+            status.push_str(&t.prompt_import_issues.replace("{issues}", &report.issues.join(", ")));
+        }
+        reload_settings_window(
+            &w,
+            &previous_hotkey,
+            &config,
+            status,
+            &shared_state_settings_import,
+            &settings_window_import,
+            &hotkey_manager_import,
+            &rt_import,
+        );
+    });
+
+    // Start over from Config::default(), the button itself asks for confirmation
+    let win_weak_settings_reset = win.as_weak();
+    let shared_state_settings_reset = Arc::clone(shared_state);
+    let settings_window_reset = Rc::clone(settings_window);
+    let hotkey_manager_reset = Arc::clone(hotkey_manager);
+    let rt_reset = Arc::clone(rt);
+    win.on_reset_settings(move || {
+        let Some(w) = win_weak_settings_reset.upgrade() else { return; };
+        let (previous_hotkey, config) = {
+            let mut state = shared_state_settings_reset.lock().unwrap();
+            let previous_hotkey = state.config.hotkey.clone();
+            let before = state.config.clone();
+            state.config.reset_to_defaults();
+            clear_stale_cache(&state.translation_cache, &before, &state.config);
+            if let Err(e) = state.config.save() {
+                error!("写入配置失败: {}", e);
+            }
+            (previous_hotkey, state.config.clone())
+        };
+        info!("设置已恢复默认");
+        // 先切换语言，提示文字才会用新的界面语言
+        i18n::init(&config.ui_language);
+        let status = i18n::t().reset_defaults_done.to_string();
+        reload_settings_window(
+            &w,
+            &previous_hotkey,
+            &config,
+            status,
+            &shared_state_settings_reset,
+            &settings_window_reset,
+            &hotkey_manager_reset,
+            &rt_reset,
+        );
+    });
+
     // Handle settings changed (auto-save)
     let win_weak_changed = win.as_weak();
     let schedule_autosave_changed = Rc::clone(&schedule_autosave);
@@ -686,11 +2170,151 @@ fn open_settings_window(
         if let Some(w) = win_weak_apply.upgrade() {
             autosave_timer_apply.stop();
             apply_ui_to_state_apply(&w);
-            if let Ok(state) = shared_state_apply.lock() {
+            if let Ok(mut state) = shared_state_apply.lock() {
                 if let Err(e) = state.config.save() {
-                    eprintln!("写入配置失败: {}", e);
+                    error!("写入配置失败: {}", e);
+                }
+            }
+        }
+    });
+
+    // Handle test connection, runs with the unsaved provider fields
+    let win_weak_test = win.as_weak();
+    let shared_state_test = Arc::clone(shared_state);
+    let current_provider_index_test = Rc::clone(&current_provider_index);
+    let rt_test = Arc::clone(rt);
+    win.on_test_provider(move || {
+        let Some(w) = win_weak_test.upgrade() else { return; };
+        let config = shared_state_test.lock().unwrap().config.clone();
+        let idx = (*current_provider_index_test.borrow()).max(0) as usize;
+        let Some(mut provider) = config.providers.get(idx).cloned() else { return; };
+        read_provider_fields(&w, &mut provider);
+
+        w.set_provider_test_running(true);
+        w.set_provider_test_ok(false);
+        w.set_provider_test_status(SharedString::from(i18n::t().provider_testing));
+        let win_weak = w.as_weak();
+        rt_test.spawn(async move {
+            let result = translate::test_connection(&config, &provider).await;
+            let _ = slint::invoke_from_event_loop(move || {
+                let Some(w) = win_weak.upgrade() else { return; };
+                let t = i18n::t();
+                w.set_provider_test_running(false);
+                match result {
+                    Ok(r) => {
+                        w.set_provider_test_ok(true);
+                        w.set_provider_test_status(SharedString::from(t.provider_test_ok.replace("{result}", r.translated_text.trim())));
+                    }
+                    Err(e) => {
+                        w.set_provider_test_ok(false);
+                        w.set_provider_test_status(SharedString::from(t.provider_test_failed.replace("{error}", &e.to_string())));
+                    }
+                }
+            });
+        });
+    });
+
+    // Handle fetch models, fills the model dropdown from the provider's /models
+    let win_weak_models = win.as_weak();
+    let shared_state_models = Arc::clone(shared_state);
+    let current_provider_index_models = Rc::clone(&current_provider_index);
+    let rt_models = Arc::clone(rt);
+    win.on_fetch_models(move || {
+        let Some(w) = win_weak_models.upgrade() else { return; };
+        let config = shared_state_models.lock().unwrap().config.clone();
+        let idx = (*current_provider_index_models.borrow()).max(0) as usize;
+        let Some(mut provider) = config.providers.get(idx).cloned() else { return; };
+        read_provider_fields(&w, &mut provider);
+
+        w.set_models_fetch_running(true);
+        w.set_models_fetch_error(SharedString::new());
+        let win_weak = w.as_weak();
+        let provider_index = w.get_provider_index();
+        rt_models.spawn(async move {
+            let result = translate::fetch_models(&config, &provider).await;
+            let _ = slint::invoke_from_event_loop(move || {
+                let Some(w) = win_weak.upgrade() else { return; };
+                w.set_models_fetch_running(false);
+                // 等待期间切换了服务商，结果作废
+                if w.get_provider_index() != provider_index {
+                    return;
+                }
+                match result {
+                    Ok(ids) => {
+                        let model = w.get_model();
+                        let selected = ids.iter().position(|id| *id == model.as_str()).map_or(-1, |i| i as i32);
+                        let ids: Vec<SharedString> = ids.into_iter().map(SharedString::from).collect();
+                        w.set_model_options(ModelRc::new(VecModel::from(ids)));
+                        w.set_model_option_index(selected);
+                    }
+                    Err(e) => {
+                        w.set_models_fetch_error(SharedString::from(i18n::t().models_fetch_failed.replace("{error}", &e.to_string())));
+                    }
                 }
+            });
+        });
+    });
+
+    // Custom header rows of the selected provider
+    let win_weak_header_add = win.as_weak();
+    win.on_add_extra_header(move || {
+        if let Some(w) = win_weak_header_add.upgrade() {
+            let mut rows: Vec<HeaderRow> = w.get_extra_headers().iter().collect();
+            rows.push(HeaderRow::default());
+            w.set_extra_headers(ModelRc::new(VecModel::from(rows)));
+        }
+    });
+
+    let win_weak_header_remove = win.as_weak();
+    let schedule_autosave_header = Rc::clone(&schedule_autosave);
+    let apply_ui_to_state_header = Rc::clone(&apply_ui_to_state);
+    win.on_remove_extra_header(move |index| {
+        if let Some(w) = win_weak_header_remove.upgrade() {
+            let mut rows: Vec<HeaderRow> = w.get_extra_headers().iter().collect();
+            if index >= 0 && (index as usize) < rows.len() {
+                rows.remove(index as usize);
+            }
+            w.set_extra_headers(ModelRc::new(VecModel::from(rows)));
+            apply_ui_to_state_header(&w);
+            schedule_autosave_header();
+        }
+    });
+
+    // Handle clear API key (only the selected provider)
+    let win_weak_clear = win.as_weak();
+    let shared_state_clear = Arc::clone(shared_state);
+    let schedule_autosave_clear = Rc::clone(&schedule_autosave);
+    let apply_ui_to_state_clear = Rc::clone(&apply_ui_to_state);
+    win.on_clear_api_key(move || {
+        if let Some(w) = win_weak_clear.upgrade() {
+            // 先同步其他未保存的输入，再单独清空密钥
+            apply_ui_to_state_clear(&w);
+            if let Ok(mut state) = shared_state_clear.lock() {
+                let active_id = state.config.active_provider_id.clone();
+                state.config.clear_api_key(&active_id);
             }
+            w.set_api_key(SharedString::new());
+            schedule_autosave_clear();
+        }
+    });
+
+    // Handle reset provider (preset base URL/model, key is kept)
+    let win_weak_reset = win.as_weak();
+    let shared_state_reset = Arc::clone(shared_state);
+    let schedule_autosave_reset = Rc::clone(&schedule_autosave);
+    let apply_ui_to_state_reset = Rc::clone(&apply_ui_to_state);
+    win.on_reset_provider(move || {
+        if let Some(w) = win_weak_reset.upgrade() {
+            apply_ui_to_state_reset(&w);
+            if let Ok(mut state) = shared_state_reset.lock() {
+                let active_id = state.config.active_provider_id.clone();
+                if state.config.reset_provider_to_default(&active_id) {
+                    if let Some(p) = state.config.get_provider(&active_id) {
+                        sync_provider_ui(&w, p);
+                    }
+                }
+            }
+            schedule_autosave_reset();
         }
     });
 
@@ -710,81 +2334,918 @@ fn open_settings_window(
     *settings_window.borrow_mut() = Some(win);
 }
 
-fn popup_physical_size(popup: &TranslatePopup) -> (i32, i32) {
-    let mut size = popup.window().size();
-    if size.width == 0 || size.height == 0 {
-        popup.window().set_size(LogicalSize::new(POPUP_WIDTH, POPUP_HEIGHT));
-        size = popup.window().size();
+/// Move a reused window back to the primary monitor when it would open off-screen
+/// (e.g. its monitor was disconnected), or always when `always_center` is set
+fn keep_window_on_screen(window: &slint::Window, always_center: bool) {
+    let scale = window.scale_factor();
+    let os_scale = caret::os_to_logical_scale(scale);
+    let monitors: Vec<caret::ScreenRect> = caret::monitor_rects().iter().map(|m| m.scaled(os_scale)).collect();
+    let position = window.position().to_logical(scale);
+    let size = window.size().to_logical(scale);
+    let rect = caret::ScreenRect { x: position.x, y: position.y, width: size.width, height: size.height };
+    if !always_center && caret::is_on_screen(&rect, &monitors) {
+        return;
+    }
+    if let Some(primary) = monitors.first() {
+        let (x, y) = caret::centered_on(primary, (size.width, size.height));
+        window.set_position(LogicalPosition::new(x, y));
+    }
+}
+
+/// Fill the popup language switcher and select `target`, codes outside the list are appended as-is
+fn sync_target_language(popup: &TranslatePopup, target: &str) {
+    let mut codes: Vec<String> = translate::LANGUAGES.iter().map(|c| c.to_string()).collect();
+    let index = match codes.iter().position(|c| c.eq_ignore_ascii_case(target)) {
+        Some(i) => i,
+        None => {
+            codes.push(target.to_string());
+            codes.len() - 1
+        }
+    };
+    let names: Vec<SharedString> = codes.iter().map(|c| SharedString::from(translate::get_language_name(c))).collect();
+    let codes: Vec<SharedString> = codes.iter().map(SharedString::from).collect();
+    popup.set_language_codes(ModelRc::new(VecModel::from(codes)));
+    popup.set_language_names(ModelRc::new(VecModel::from(names)));
+    // 必须在设置 model 之后设置下标
+    popup.set_target_language_index(index as i32);
+}
+
+/// Put a newly shown result on the clipboard, unless `auto_copy_result` is off (Copy still works)
+fn auto_copy(popup: &TranslatePopup, shared_state: &Arc<Mutex<SharedState>>, text: &str) {
+    if !shared_state.lock().unwrap().config.auto_copy_result {
+        return;
+    }
+    if let Err(e) = clipboard::simple::set_text(text) {
+        report_clipboard_error(popup, &e);
     }
-    (size.width as i32, size.height as i32)
+}
+
+/// Show the result of a one-off translation with an explicit language pair (swap / retranslate)
+fn show_pair_result(
+    popup: &TranslatePopup,
+    shared_state: &Arc<Mutex<SharedState>>,
+    source: &str,
+    result: Result<translate::TranslateResponse>,
+) {
+    popup.set_loading(false);
+    match result {
+        Ok(r) => {
+            let shown = render_translation(source, &r.translated_text, popup.get_bullet_list());
+            popup.set_lang_label(SharedString::from(r.direction_label()));
+            {
+                let mut state = shared_state.lock().unwrap();
+                state.current_pair = Some(r.pair());
+                state.raw_translation = r.translated_text;
+                state.shown_provider = r.provider;
+            }
+            popup.set_translated_text(SharedString::from(&shown));
+            popup.set_note_text(SharedString::from(r.note.unwrap_or_default()));
+            show_usage(popup, shared_state, r.usage);
+            auto_copy(popup, shared_state, &shown);
+        }
+        Err(e) => popup.set_error_message(SharedString::from(e.to_string())),
+    }
+}
+
+/// Apply the configured logical size and return it, Slint handles the display scale
+fn apply_popup_size(popup: &TranslatePopup, config: &Config) -> (f32, f32) {
+    popup.window().set_size(LogicalSize::new(config.popup_width, config.popup_height));
+    (config.popup_width, config.popup_height)
+}
+
+/// Selection copied by the worker thread, the main timer picks it up and shows the popup
+struct CapturedSelection {
+    text: String,
+    original_clipboard: Option<ClipboardContent>,
+    html: Option<String>,  // 选区的 HTML 格式，来源应用没有提供时为 None
+    caret: (i32, i32),  // 复制之后、弹窗出现之前的光标位置
+    target: Option<String>,
 }
 
 /// Handle the translate hotkey press
+/// Copying the selection sleeps for a while, so it runs on a worker thread and the result comes back through `selection_tx`
 fn handle_translate_hotkey(
+    shared_state: &Arc<Mutex<SharedState>>,
+    selection_tx: &crossbeam_channel::Sender<CapturedSelection>,
+    target: Option<String>,
+) {
+    let (copy_delay_ms, auto_copy_result, rich_text_paste) = {
+        let config = &shared_state.lock().unwrap().config;
+        (config.copy_delay_ms, config.auto_copy_result, config.rich_text_paste)
+    };
+    let selection_tx = selection_tx.clone();
+    std::thread::spawn(move || {
+        let original_clipboard = clipboard::simple::get_content().ok();
+        std::thread::sleep(Duration::from_millis(50));
+        input::send_copy();
+        std::thread::sleep(Duration::from_millis(copy_delay_ms));
+
+        let selected_text = match clipboard::simple::get_text() {
+            Ok(text) => text,
+            Err(_) => return,
+        };
+
+        if selected_text.is_empty() { return; }
+        if let Some(ref orig) = original_clipboard {
+            // 只用于比较，翻译时仍然使用原始文本
+            if clipboard::same_selection(&selected_text, &orig.text) { return; }
+        }
+        // 要在恢复剪贴板之前读，之后就是原来的内容了
+        let html = if rich_text_paste { clipboard::simple::get_html().unwrap_or(None) } else { None };
+
+        // 不自动复制译文时，把复制选中内容前的剪贴板还回去
+        if !auto_copy_result {
+            if let Some(ref orig) = original_clipboard {
+                if let Err(e) = clipboard::simple::set_content(orig) {
+                    error!("恢复剪贴板失败: {:#}", e);
+                }
+            }
+        }
+        // 原来的应用还在前台，这时取到的光标位置才准
+        let caret = caret::get_caret_position();
+        let _ = selection_tx.send(CapturedSelection { text: selected_text, original_clipboard, html, caret, target });
+    });
+}
+
+/// Show the popup for a selection captured by `handle_translate_hotkey` and start translating it
+fn show_captured_selection(
     popup_weak: &slint::Weak<TranslatePopup>,
+    review_weak: &slint::Weak<ReviewWindow>,
     shared_state: &Arc<Mutex<SharedState>>,
     rt: &Arc<tokio::runtime::Runtime>,
+    selection: CapturedSelection,
 ) {
-    let original_clipboard = clipboard::simple::get_text().ok();
-    std::thread::sleep(Duration::from_millis(50));
-    input::send_ctrl_c();
-    std::thread::sleep(Duration::from_millis(100));
+    let CapturedSelection { text: selected_text, original_clipboard, html, caret, target } = selection;
+    {
+        let mut state = shared_state.lock().unwrap();
+        state.original_clipboard = original_clipboard;
+        state.source_html = None;
+        state.idle.touch(Instant::now());
+    }
+
+    // 纯数字、邮箱、网址之类不值得发请求
+    let (skipped, notify_skipped) = {
+        let state = shared_state.lock().unwrap();
+        (skip::should_skip(&state.config.skip_patterns, &selected_text), state.config.notify_skipped)
+    };
+    if skipped && !notify_skipped {
+        return;
+    }
+
+    if let Some(popup) = popup_weak.upgrade() {
+        present_popup(&popup, shared_state, &selected_text, caret);
 
-    let selected_text = match clipboard::simple::get_text() {
-        Ok(text) => text,
-        Err(_) => return,
+        if skipped {
+            popup.set_loading(false);
+            popup.set_error_message(SharedString::from(i18n::t().selection_skipped));
+            return;
+        }
+
+        // 选中内容较长时先只翻译开头
+        let preview = {
+            let state = shared_state.lock().unwrap();
+            if state.config.preview_long_selections {
+                translate::preview_slice(&selected_text, state.config.preview_chars).map(str::to_string)
+            } else {
+                None
+            }
+        };
+        popup.set_is_preview(preview.is_some());
+        // 只翻译开头时格式对不上，按纯文本处理
+        if preview.is_none() {
+            shared_state.lock().unwrap().source_html = html.map(|html| (selected_text.clone(), html));
+        }
+        let text = preview.unwrap_or(selected_text);
+        spawn_popup_translation(popup_weak, review_weak, shared_state, rt, text, target);
+    }
+}
+
+/// Reset the popup for `source` and show it next to the caret (or where it was dragged to)
+fn present_popup(popup: &TranslatePopup, shared_state: &Arc<Mutex<SharedState>>, source: &str, caret: (i32, i32)) {
+    // 再次按下快捷键时放弃上一次还没完成的请求
+    cancel_popup_request(shared_state);
+    let (cursor_x, cursor_y) = caret;
+
+    popup.set_source_text(SharedString::from(source));
+    popup.set_translated_text(SharedString::new());
+    popup.set_result_edited(false);
+    popup.set_error_message(SharedString::new());
+    popup.set_clipboard_error(SharedString::new());
+    popup.set_list_like(postprocess::is_list_like(source));
+    popup.set_comment_selection(comment::detect_style(source).is_some());
+    popup.set_loading(true);
+    // 新弹出的窗口按设置置顶，已经打开时保留手动切换的状态
+    if !popup.window().is_visible() {
+        popup.set_on_top(shared_state.lock().unwrap().config.popup_always_on_top);
+    }
+
+    // 计算窗口位置：居中于鼠标上方，并确保不超出屏幕
+    let (popup_width, popup_height, saved_position) = {
+        let config = &shared_state.lock().unwrap().config;
+        let (width, height) = apply_popup_size(popup, config);
+        (width, height, config.popup_position)
+    };
+    let scale = caret::os_to_logical_scale(popup.window().scale_factor());
+    let (x, y) = match saved_position {
+        // 用户拖动过就回到上次的位置，显示器变化后拉回屏幕内
+        Some((x, y)) => {
+            let monitors: Vec<caret::ScreenRect> = caret::monitor_rects().iter().map(|m| m.scaled(scale)).collect();
+            let rect = caret::ScreenRect { x, y, width: popup_width, height: popup_height };
+            caret::clamp_onto_screen(&rect, &monitors)
+        }
+        None => caret::calculate_popup_position(cursor_x, cursor_y, popup_width, popup_height, scale),
     };
+    popup.window().set_position(LogicalPosition::new(x, y));
+    let no_activate = shared_state.lock().unwrap().config.popup_no_activate;
+    let previous_focus = popup_window::remember_focus(no_activate);
+    popup.show().ok();
+    popup_window::after_show(popup.window(), no_activate, previous_focus);
+
+    // 记录窗口显示时间，用于焦点检测保护期
+    let mut state = shared_state.lock().unwrap();
+    state.popup_shown_at = Some(std::time::Instant::now());
+    state.popup_activity_at = state.popup_shown_at;
+    state.popup_had_focus = false;
+}
+
+/// Read the text in the clipboard image and translate it, without an image this is the normal hotkey
+fn handle_ocr_hotkey(
+    popup_weak: &slint::Weak<TranslatePopup>,
+    review_weak: &slint::Weak<ReviewWindow>,
+    shared_state: &Arc<Mutex<SharedState>>,
+    rt: &Arc<tokio::runtime::Runtime>,
+    selection_tx: &crossbeam_channel::Sender<CapturedSelection>,
+) {
+    let png = match clipboard::simple::get_image() {
+        Ok(Some(png)) => png,
+        Ok(None) => return handle_translate_hotkey(shared_state, selection_tx, None),
+        Err(e) => {
+            error!("读取剪贴板图片失败: {:#}", e);
+            return handle_translate_hotkey(shared_state, selection_tx, None);
+        }
+    };
+    let Some(popup) = popup_weak.upgrade() else { return; };
+
+    let (config, limiter) = {
+        let mut state = shared_state.lock().unwrap();
+        // 剪贴板里是图片，应用译文后没有文字可以恢复
+        state.original_clipboard = None;
+        state.source_html = None;
+        state.idle.touch(Instant::now());
+        (state.config.clone(), Arc::clone(&state.rate_limiter))
+    };
+    present_popup(&popup, shared_state, i18n::t().recognizing_image, caret::get_caret_position());
+    popup.set_is_preview(false);
+
+    let translator = Translator::new(config).with_rate_limit(limiter);
+    let popup_weak = popup_weak.clone();
+    let review_weak = review_weak.clone();
+    let request = next_popup_request(shared_state);
+    let shared_state_t = Arc::clone(shared_state);
+    let rt_inner = Arc::clone(rt);
+    let task = rt.spawn(async move {
+        let result = translator.recognize_image(&png).await;
+        let _ = slint::invoke_from_event_loop(move || {
+            if !is_current_request(&shared_state_t, request) {
+                return;
+            }
+            let Some(popup) = popup_weak.upgrade() else { return; };
+            match result {
+                Ok(text) if !text.is_empty() => {
+                    popup.set_source_text(SharedString::from(&text));
+                    popup.set_list_like(postprocess::is_list_like(&text));
+                    spawn_popup_translation(&popup_weak, &review_weak, &shared_state_t, &rt_inner, text, None);
+                }
+                Ok(_) => {
+                    popup.set_loading(false);
+                    popup.set_error_message(SharedString::from(i18n::t().no_text_in_image));
+                }
+                Err(e) => {
+                    popup.set_loading(false);
+                    popup.set_error_message(SharedString::from(e.to_string()));
+                }
+            }
+        });
+    });
+    track_popup_task(shared_state, request, &task);
+}
+
+/// Start a new popup request, dropping whatever the previous one would still deliver
+fn next_popup_request(shared_state: &Arc<Mutex<SharedState>>) -> u64 {
+    cancel_popup_request(shared_state);
+    shared_state.lock().unwrap().popup_request
+}
+
+/// Keep the handle so closing the popup can abort the task, unless it was already superseded
+fn track_popup_task(shared_state: &Arc<Mutex<SharedState>>, request: u64, task: &tokio::task::JoinHandle<()>) {
+    let mut state = shared_state.lock().unwrap();
+    if state.popup_request == request {
+        state.popup_task = Some(task.abort_handle());
+    } else {
+        task.abort();
+    }
+}
+
+fn is_current_request(shared_state: &Arc<Mutex<SharedState>>, request: u64) -> bool {
+    shared_state.lock().unwrap().popup_request == request
+}
+
+/// Abort the in-flight popup request; results already queued for the UI thread are dropped by the id check
+fn cancel_popup_request(shared_state: &Arc<Mutex<SharedState>>) {
+    let mut state = shared_state.lock().unwrap();
+    state.popup_request += 1;
+    if let Some(task) = state.popup_task.take() {
+        if !task.is_finished() {
+            task.abort();
+            tray::request_icon_state(tray::IconState::Idle);
+        }
+    }
+}
 
+/// Translate the selection and paste it over itself, the popup is never shown
+/// Sleeps while the selection is copied, so it runs on its own thread
+fn handle_paste_hotkey(shared_state: &Arc<Mutex<SharedState>>, rt: &tokio::runtime::Runtime) {
+    let copy_delay_ms = shared_state.lock().unwrap().config.copy_delay_ms;
+    let original_clipboard = clipboard::simple::get_content().ok();
+    std::thread::sleep(Duration::from_millis(50));
+    input::send_copy();
+    std::thread::sleep(Duration::from_millis(copy_delay_ms));
+
+    let Ok(selected_text) = clipboard::simple::get_text() else { return; };
     if selected_text.is_empty() { return; }
     if let Some(ref orig) = original_clipboard {
-        if &selected_text == orig { return; }
+        if clipboard::same_selection(&selected_text, &orig.text) { return; }
+    }
+
+    let (config, cache, limiter) = {
+        let mut state = shared_state.lock().unwrap();
+        state.idle.touch(Instant::now());
+        (state.config.clone(), Arc::clone(&state.translation_cache), Arc::clone(&state.rate_limiter))
+    };
+    if config.active_provider_missing_key() {
+        restore_clipboard(original_clipboard);
+        tray::notify(i18n::t().paste_failed);
+        return;
+    }
+    if skip::should_skip(&config.skip_patterns, &selected_text) {
+        restore_clipboard(original_clipboard);
+        return;
     }
 
-    shared_state.lock().unwrap().original_clipboard = original_clipboard;
+    let delays = PasteDelays::from_config(&config);
+    let translator = Translator::new(config).with_cache(cache).with_rate_limit(limiter);
+    tray::request_icon_state(tray::IconState::Busy);
+    rt.spawn(async move {
+        match translator.translate(&selected_text).await {
+            Ok(r) => {
+                tray::request_icon_state(tray::IconState::Idle);
+                paste_in_background(ClipboardContent::plain(r.translated_text), original_clipboard, delays, |e| {
+                    error!("翻译并粘贴失败: {:#}", e);
+                    tray::notify(i18n::t().paste_failed);
+                });
+            }
+            Err(e) => {
+                tray::request_icon_state(tray::IconState::Error);
+                restore_clipboard(original_clipboard);
+                tray::notify(format!("{}: {}", i18n::t().paste_failed, e));
+            }
+        }
+    });
+}
 
-    let (cursor_x, cursor_y) = caret::get_caret_position();
+/// Put back what was on the clipboard before the selection was copied
+fn restore_clipboard(original: Option<ClipboardContent>) {
+    if let Some(original) = original {
+        if let Err(e) = clipboard::simple::set_content(&original) {
+            error!("恢复剪贴板失败: {:#}", e);
+        }
+    }
+}
 
+/// Translate `text` in the background and show the result in the popup
+/// `target` pins the target language, None lets the config pick it
+fn spawn_popup_translation(
+    popup_weak: &slint::Weak<TranslatePopup>,
+    review_weak: &slint::Weak<ReviewWindow>,
+    shared_state: &Arc<Mutex<SharedState>>,
+    rt: &tokio::runtime::Runtime,
+    text: String,
+    target: Option<String>,
+) {
+    let popup_weak_t = popup_weak.clone();
+    let review_weak_t = review_weak.clone();
+    let shared_state_t = Arc::clone(shared_state);
+    let (mut config, cache, limiter, source_html) = {
+        let mut state = shared_state.lock().unwrap();
+        state.raw_translation.clear();
+        state.alternative = None;
+        state.swap_undo = None;
+        state.rich_source = None;
+        // 翻译的正好是复制到的那段文字时，才能按原格式粘贴
+        let source_html = state.source_html.as_ref().filter(|(plain, _)| *plain == text).map(|(_, html)| html.clone());
+        (state.config.clone(), Arc::clone(&state.translation_cache), Arc::clone(&state.rate_limiter), source_html)
+    };
+    // 指定了目标语言就不再按原文语言自动切换，多语言模式也只在没指定时生效
+    let multi_targets = match target {
+        Some(target) => {
+            config.target_lang = target;
+            config.auto_detect = false;
+            None
+        }
+        None => config.multi_target_langs().map(<[String]>::to_vec),
+    };
+    let auto_pick = config.auto_pick_partner().is_some();
+    let translator = Translator::new(config).with_cache(cache).with_rate_limit(limiter);
+    if let Some(targets) = multi_targets {
+        spawn_multi_translation(popup_weak, shared_state, rt, translator, text, targets);
+        return;
+    }
+    let pair = translator.lang_pair(&text);
+    shared_state.lock().unwrap().current_pair = Some(pair.clone());
+    // 新的翻译打断还在进行的朗读
+    speech::stop();
     if let Some(popup) = popup_weak.upgrade() {
-        popup.set_source_text(SharedString::from(&selected_text));
-        popup.set_translated_text(SharedString::new());
-        popup.set_error_message(SharedString::new());
-        popup.set_loading(true);
-
-        // 计算窗口位置：居中于鼠标上方，并确保不超出屏幕
-        let (popup_width, popup_height) = popup_physical_size(&popup);
-        let (x, y) = caret::calculate_popup_position(cursor_x, cursor_y, popup_width, popup_height);
-        popup.window().set_position(PhysicalPosition::new(x, y));
-        popup.show().ok();
+        popup.set_speaking(false);
+        sync_target_language(&popup, &pair.target);
+        popup.set_multi_target(false);
+        popup.set_can_undo_swap(false);
+        popup.set_usage_text(SharedString::new());
+        popup.set_note_text(SharedString::new());
+        popup.set_lang_label(SharedString::new());
+        popup.set_raw_response(SharedString::new());
+        popup.set_raw_expanded(false);
+        popup.set_alt_label(SharedString::new());
+    }
 
-        // 记录窗口显示时间，用于焦点检测保护期
-        shared_state.lock().unwrap().popup_shown_at = Some(std::time::Instant::now());
+    let popup_weak_p = popup_weak.clone();
+    let request = next_popup_request(shared_state);
+    let shared_state_p = Arc::clone(shared_state);
+    tray::request_icon_state(tray::IconState::Busy);
+    let task = rt.spawn(async move {
+        let result = if auto_pick {
+            // 需要两份完整结果才能比较，不走流式
+            translator.translate_auto_pick(&text).await
+        } else {
+            // 流式输出时逐步刷新译文，最终结果仍走下面的统一处理
+            translator
+                .translate_stream(&text, move |partial| {
+                    let partial = SharedString::from(partial);
+                    let popup_weak_p = popup_weak_p.clone();
+                    let shared_state_p = Arc::clone(&shared_state_p);
+                    let _ = slint::invoke_from_event_loop(move || {
+                        if !is_current_request(&shared_state_p, request) {
+                            return;
+                        }
+                        if let Some(popup) = popup_weak_p.upgrade() {
+                            popup.set_loading(false);
+                            popup.set_translated_text(partial);
+                        }
+                    });
+                })
+                .await
+                .map(|best| PickedTranslation { best_provider: best.provider.clone(), best, alternative: None })
+        };
+        tray::request_icon_state(if result.is_ok() { tray::IconState::Idle } else { tray::IconState::Error });
 
-        let popup_weak_t = popup_weak.clone();
-        let config = shared_state.lock().unwrap().config.clone();
-        let text = selected_text.clone();
+        let _ = slint::invoke_from_event_loop(move || {
+            // 弹窗已关闭或已有新的请求
+            if !is_current_request(&shared_state_t, request) {
+                return;
+            }
+            if let Some(popup) = popup_weak_t.upgrade() {
+                popup.set_loading(false);
+                match result {
+                    Ok(picked) => {
+                        let r = picked.best;
+                        let translated = render_translation(&text, &r.translated_text, popup.get_bullet_list());
+                        let alt_name = picked.alternative.as_ref().map(|(name, _)| name.clone());
+                        let provider = picked.best_provider.clone();
+                        // 自动择优两个服务都调用了，用量一起算
+                        let usage = usage::UsageEstimate::sum(r.usage, picked.alternative.as_ref().and_then(|(_, alt)| alt.usage));
+                        popup.set_lang_label(SharedString::from(r.direction_label()));
+                        if !r.target_lang.is_empty() {
+                            sync_target_language(&popup, &r.target_lang);
+                        }
+                        let expand = {
+                            let mut state = shared_state_t.lock().unwrap();
+                            // 慢的翻译不占用自动关闭的时间
+                            state.popup_activity_at = Some(Instant::now());
+                            // 服务识别出的原文语言比按字符猜的更可靠，反向翻译时用它
+                            state.current_pair = Some(r.pair());
+                            // 带格式的选区先只记下来，点应用时再一次性按原来的标签重建
+                            state.rich_source = source_html.map(|html| RichSource { translated: translated.clone(), html, pair: r.pair() });
+                            state.raw_translation = r.translated_text;
+                            state.shown_provider = picked.best_provider;
+                            state.alternative = picked.alternative.map(|(name, alt)| (name, alt.translated_text));
+                            state.config.should_open_expanded(&translated)
+                        };
+                        popup.set_alt_label(SharedString::from(alt_name.map(|n| format_alt_label(&n)).unwrap_or_default()));
+                        popup.set_translated_text(SharedString::from(&translated));
+                        popup.set_note_text(SharedString::from(r.note.unwrap_or_default()));
+                        record_history(&shared_state_t, &text, &translated, &provider);
+                        show_usage(&popup, &shared_state_t, usage);
+                        popup.set_raw_response(SharedString::from(r.raw_response.unwrap_or_default()));
+                        // 翻译完成后自动复制到剪贴板，用户可直接 Ctrl+V
+                        auto_copy(&popup, &shared_state_t, &translated);
+                        // 长译文直接换到大窗口查看
+                        if expand {
+                            if let Some(review) = review_weak_t.upgrade() {
+                                popup.hide().ok();
+                                open_review_window(&review, &text, &translated);
+                            }
+                        }
+                    }
+                    Err(e) => popup.set_error_message(SharedString::from(e.to_string())),
+                }
+            }
+        });
+    });
+    track_popup_task(shared_state, request, &task);
+}
 
-        rt.spawn(async move {
-            let translator = Translator::new(config);
-            let result = translator.translate(&text).await;
+/// Multi-target mode: translate `text` into each of `targets`, one popup section per language
+/// Copy takes all sections together, Apply is hidden since there's no single text to paste
+fn spawn_multi_translation(
+    popup_weak: &slint::Weak<TranslatePopup>,
+    shared_state: &Arc<Mutex<SharedState>>,
+    rt: &tokio::runtime::Runtime,
+    translator: Translator,
+    text: String,
+    targets: Vec<String>,
+) {
+    // 没有单一的翻译方向，反向翻译和按格式粘贴都用不上
+    shared_state.lock().unwrap().current_pair = None;
+    speech::stop();
+    if let Some(popup) = popup_weak.upgrade() {
+        popup.set_speaking(false);
+        popup.set_multi_target(true);
+        popup.set_target_sections(ModelRc::default());
+        sync_target_language(&popup, &targets[0]);
+        popup.set_can_undo_swap(false);
+        popup.set_usage_text(SharedString::new());
+        popup.set_note_text(SharedString::new());
+        popup.set_lang_label(SharedString::new());
+        popup.set_raw_response(SharedString::new());
+        popup.set_raw_expanded(false);
+        popup.set_alt_label(SharedString::new());
+    }
 
-            let _ = slint::invoke_from_event_loop(move || {
-                if let Some(popup) = popup_weak_t.upgrade() {
-                    popup.set_loading(false);
-                    match result {
-                        Ok(r) => {
-                            let translated = r.translated_text.clone();
-                            popup.set_translated_text(SharedString::from(r.translated_text));
-                            // 翻译完成后自动复制到剪贴板，用户可直接 Ctrl+V
-                            let _ = clipboard::simple::set_text(&translated);
+    let popup_weak = popup_weak.clone();
+    let shared_state_t = Arc::clone(shared_state);
+    let request = next_popup_request(shared_state);
+    tray::request_icon_state(tray::IconState::Busy);
+    let task = rt.spawn(async move {
+        let results = translator.translate_multi(&text, &targets).await;
+        let any_ok = results.iter().any(|(_, result)| result.is_ok());
+        tray::request_icon_state(if any_ok { tray::IconState::Idle } else { tray::IconState::Error });
+
+        let _ = slint::invoke_from_event_loop(move || {
+            if !is_current_request(&shared_state_t, request) {
+                return;
+            }
+            let Some(popup) = popup_weak.upgrade() else { return; };
+            popup.set_loading(false);
+            let mut sections = Vec::with_capacity(results.len());
+            let mut translated = Vec::new();
+            let mut usage = None;
+            let mut provider = String::new();
+            let mut first_error = None;
+            for (lang, result) in results {
+                let label = SharedString::from(translate::get_language_name(&lang));
+                match result {
+                    Ok(r) => {
+                        usage = usage::UsageEstimate::sum(usage, r.usage);
+                        if provider.is_empty() {
+                            provider = r.provider;
                         }
-                        Err(e) => popup.set_error_message(SharedString::from(e.to_string())),
+                        sections.push(TargetSection { label, text: SharedString::from(r.translated_text.trim()), error: SharedString::new() });
+                        translated.push((lang, r.translated_text));
+                    }
+                    // 单个语言失败只在它自己的分段里显示
+                    Err(e) => {
+                        let message = e.to_string();
+                        sections.push(TargetSection { label, text: SharedString::new(), error: SharedString::from(&message) });
+                        first_error.get_or_insert(message);
                     }
                 }
-            });
+            }
+            if translated.is_empty() {
+                popup.set_error_message(SharedString::from(first_error.unwrap_or_default()));
+                return;
+            }
+
+            let combined = translate::format_multi_target(&translated);
+            {
+                let mut state = shared_state_t.lock().unwrap();
+                state.popup_activity_at = Some(Instant::now());
+                state.raw_translation = combined.clone();
+                state.shown_provider = provider.clone();
+            }
+            popup.set_target_sections(ModelRc::new(VecModel::from(sections)));
+            // 复制按钮和自动复制都用合并后的全部译文
+            popup.set_translated_text(SharedString::from(&combined));
+            record_history(&shared_state_t, &text, &combined, &provider);
+            show_usage(&popup, &shared_state_t, usage);
+            auto_copy(&popup, &shared_state_t, &combined);
         });
+    });
+    track_popup_task(shared_state, request, &task);
+}
+
+/// Translate the text runs of `html` in one delimited request and put the results back between the original tags
+/// A single run reuses the whole translation, None means paste plain text
+async fn translate_rich_text(translator: &Translator, pair: &LangPair, html: &str, translated: &str) -> Option<String> {
+    let runs = richtext::text_runs(html);
+    let parts = match runs.len() {
+        0 => return None,
+        1 => vec![translated.to_string()],
+        n if n > richtext::MAX_RICH_TEXT_RUNS => return None,
+        _ => {
+            // 标点、数字之类不用翻译，只把有文字的片段拼成一个请求
+            let wordy: Vec<usize> = (0..runs.len()).filter(|&i| runs[i].chars().any(char::is_alphabetic)).collect();
+            let joined: Vec<String> = wordy.iter().map(|&i| runs[i].clone()).collect();
+            let response = match translator.translate_pair(&richtext::join_runs(&joined), pair).await {
+                Ok(r) => r,
+                Err(e) => {
+                    warn!("按格式翻译失败，改为粘贴纯文本: {}", e);
+                    return None;
+                }
+            };
+            let Some(translated_runs) = richtext::split_runs(&response.translated_text, wordy.len()) else {
+                warn!("按格式翻译返回的片段数量不一致，改为粘贴纯文本");
+                return None;
+            };
+            let mut parts = runs;
+            for (i, text) in wordy.into_iter().zip(translated_runs) {
+                parts[i] = text;
+            }
+            parts
+        }
+    };
+    richtext::rebuild(html, &parts)
+}
+
+/// Append a finished translation to the local history when it is enabled
+fn record_history(shared_state: &Arc<Mutex<SharedState>>, source: &str, translated: &str, provider: &str) {
+    if !shared_state.lock().unwrap().config.history_enabled {
+        return;
+    }
+    if let Err(e) = history::append(&history::HistoryEntry::new(source, translated, provider)) {
+        error!("写入翻译历史失败: {}", e);
+    }
+}
+
+/// Reload history.jsonl into the history window
+fn show_history_entries(win: &HistoryWindow) {
+    let entries = history::load().unwrap_or_else(|e| {
+        error!("读取翻译历史失败: {}", e);
+        Vec::new()
+    });
+    let now = history::unix_now();
+    let items: Vec<HistoryItem> = entries
+        .into_iter()
+        .map(|entry| HistoryItem {
+            meta: SharedString::from(format!("{} · {}", entry.provider, history::format_age(entry.timestamp, now))),
+            source: SharedString::from(entry.source),
+            translated: SharedString::from(entry.translated),
+        })
+        .collect();
+    win.set_entries(ModelRc::new(VecModel::from(items)));
+}
+
+/// Hide the popup after it lost focus, when the option is on
+fn dismiss_if_unfocused(popup: &TranslatePopup, shared_state: &Arc<Mutex<SharedState>>) {
+    let mut state = shared_state.lock().unwrap();
+    let focused = popup_window::ours_focused();
+    if focused {
+        state.popup_had_focus = true;
+    }
+    let check = popup_window::FocusCheck {
+        enabled: state.config.dismiss_on_focus_loss,
+        pinned: popup.get_pinned(),
+        had_focus: state.popup_had_focus,
+        focused,
+        shown_at: state.popup_shown_at,
+        now: Instant::now(),
+    };
+    if popup_window::should_dismiss_on_focus_loss(&check) {
+        state.popup_had_focus = false;
+        drop(state);
+        popup.hide().ok();
+    }
+}
+
+/// Hide the popup after the configured time without interaction
+fn close_if_idle(popup: &TranslatePopup, shared_state: &Arc<Mutex<SharedState>>) {
+    let state = shared_state.lock().unwrap();
+    let idle = popup_window::should_auto_close(
+        state.config.auto_close_secs,
+        popup.get_pinned(),
+        popup.get_loading() || popup.get_speaking(),
+        state.popup_activity_at,
+        Instant::now(),
+    );
+    drop(state);
+    // 自动复制的译文要留在剪贴板里，所以不恢复原来的内容
+    if idle {
+        dismiss_popup(popup, shared_state);
+    }
+}
+
+/// Stop reading aloud, drop the pending request and hide the popup
+fn dismiss_popup(popup: &TranslatePopup, shared_state: &Arc<Mutex<SharedState>>) {
+    speech::stop();
+    popup.set_speaking(false);
+    // 关掉弹窗后不再需要结果，也不能再自动复制
+    cancel_popup_request(shared_state);
+    popup.hide().ok();
+}
+
+/// Drop cached translations when going from `before` to `after` changes what they would be
+fn clear_stale_cache(cache: &SharedCache, before: &Config, after: &Config) {
+    if before.invalidates_cache(after) {
+        if let Ok(mut cache) = cache.lock() {
+            cache.clear();
+        }
+    }
+}
+
+/// Where the idle translation cache is parked, next to config.json
+fn cache_spill_path() -> Option<PathBuf> {
+    match Config::config_path() {
+        Ok(path) => Some(path.with_file_name("cache.json")),
+        Err(e) => {
+            error!("找不到缓存目录: {}", e);
+            None
+        }
     }
 }
 
+/// Park the translation cache on disk and drop pooled connections after the configured idle time
+fn trim_if_idle(shared_state: &Arc<Mutex<SharedState>>, now: Instant) {
+    let cache = {
+        let mut state = shared_state.lock().unwrap();
+        let idle_after = Duration::from_secs(state.config.idle_trim_minutes * 60);
+        if !state.idle.due(now, idle_after) {
+            return;
+        }
+        state.idle.mark_trimmed();
+        // 没开历史记录时不把原文和译文写到磁盘上
+        state.config.history_enabled.then(|| Arc::clone(&state.translation_cache))
+    };
+    translate::release_shared_client();
+    let (Some(cache), Some(path)) = (cache, cache_spill_path()) else { return; };
+    // 写文件放到后台线程，不卡界面
+    std::thread::spawn(move || {
+        if let Err(e) = cache.lock().unwrap().spill_to(&path) {
+            error!("缓存写入磁盘失败: {}", e);
+        }
+    });
+}
+
+/// Comma-separated provider names for settings, unknown ids are shown as-is
+fn format_provider_list(config: &Config, ids: &[String]) -> String {
+    ids.iter()
+        .map(|id| config.get_provider(id).map(|p| p.name.clone()).unwrap_or_else(|| id.clone()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Names or ids from settings to provider ids, unknown entries are kept for later
+fn parse_provider_list(config: &Config, text: &str) -> Vec<String> {
+    text.split([',', '，'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| config.find_provider(s).map(|p| p.id.clone()).unwrap_or_else(|| s.to_string()))
+        .collect()
+}
+
+/// Popup link for switching to the auto-pick alternative
+fn format_alt_label(provider: &str) -> String {
+    i18n::t().show_alternative.replace("{provider}", provider)
+}
+
+/// Timeout field from settings, blank or invalid keeps the current value
+fn parse_timeout(text: &str) -> Option<u64> {
+    text.trim()
+        .parse::<u64>()
+        .ok()
+        .map(|secs| secs.clamp(config::MIN_TIMEOUT_SECS, config::MAX_TIMEOUT_SECS))
+}
+
+/// Popup label for the usage estimate
+fn format_usage(estimate: usage::UsageEstimate) -> String {
+    let t = i18n::t();
+    let tokens = estimate.total_tokens().to_string();
+    let (plain, with_cost) = if estimate.exact {
+        (t.usage_reported, t.usage_reported_cost)
+    } else {
+        (t.usage_estimate, t.usage_estimate_cost)
+    };
+    match estimate.cost {
+        Some(cost) => with_cost
+            .replace("{tokens}", &tokens)
+            .replace("{cost}", &usage::format_cost(cost)),
+        None => plain.replace("{tokens}", &tokens),
+    }
+}
+
+/// Show a result's usage in the popup footer and add it to the total since launch
+/// Providers without usage (Google, DeepL) leave the footer empty
+fn show_usage(popup: &TranslatePopup, shared_state: &Arc<Mutex<SharedState>>, usage: Option<usage::UsageEstimate>) {
+    let Some(usage) = usage else {
+        popup.set_usage_text(SharedString::new());
+        return;
+    };
+    let total = {
+        let mut state = shared_state.lock().unwrap();
+        let total = usage::UsageEstimate::sum(state.session_usage, Some(usage)).unwrap_or(usage);
+        state.session_usage = Some(total);
+        total
+    };
+    let text = i18n::t()
+        .usage_session
+        .replace("{usage}", &format_usage(usage))
+        .replace("{total}", &format_usage(total));
+    popup.set_usage_text(SharedString::from(text));
+}
+
+/// Query permissions and update the persisted prompt flag, returns whether to alert
+fn check_permissions_on_startup(config: &mut Config) -> bool {
+    let status = permissions::current_status();
+    let decision = permissions::decide_prompt(status, config.permission_prompted);
+    if !status.all_granted() {
+        warn!(
+            "缺少系统权限: 辅助功能={}, 输入监控={}",
+            status.accessibility, status.input_monitoring
+        );
+    }
+    if decision.prompted != config.permission_prompted {
+        config.permission_prompted = decision.prompted;
+        if let Err(e) = config.save() {
+            error!("保存配置失败: {}", e);
+        }
+    }
+    decision.show_alert
+}
+
+/// Clipboard to put back after pasting, and how long the target app gets to read the paste
+fn paste_context(shared_state: &Arc<Mutex<SharedState>>) -> (Option<ClipboardContent>, PasteDelays) {
+    let state = shared_state.lock().unwrap();
+    (state.original_clipboard.clone(), PasteDelays::from_config(&state.config))
+}
+
+/// Configured waits around a synthesized paste
+#[derive(Clone, Copy)]
+struct PasteDelays {
+    focus: Duration,
+    restore: Duration,
+}
+
+impl PasteDelays {
+    fn from_config(config: &Config) -> Self {
+        Self { focus: Duration::from_millis(config.focus_delay_ms), restore: Duration::from_millis(config.paste_delay_ms) }
+    }
+}
+
+/// Paste once focus is back in the previous app, `on_error` runs on the UI thread
+fn paste_in_background<F>(content: ClipboardContent, original: Option<ClipboardContent>, delays: PasteDelays, on_error: F)
+where
+    F: FnOnce(anyhow::Error) + Send + 'static,
+{
+    std::thread::spawn(move || {
+        std::thread::sleep(delays.focus);
+        if let Err(e) = clipboard::paste_and_restore(&content, original, delays.restore) {
+            let _ = slint::invoke_from_event_loop(move || on_error(e));
+        }
+    });
+}
+
+/// Show a result in the review window
+fn open_review_window(review: &ReviewWindow, source: &str, translated: &str) {
+    review.set_source_text(SharedString::from(source));
+    review.set_translated_text(SharedString::from(translated));
+    review.set_loading(false);
+    review.set_error_message(SharedString::new());
+    review.set_clipboard_error(SharedString::new());
+    review.show().ok();
+}
+
+/// Apply the optional display transforms to a raw translation
+fn render_translation(source: &str, raw: &str, bullet_list: bool) -> String {
+    if bullet_list {
+        postprocess::format_as_bullets(source, raw)
+    } else {
+        raw.to_string()
+    }
+}
+
+/// Show a clipboard failure in the popup instead of silently dropping it
+fn report_clipboard_error(popup: &TranslatePopup, err: &anyhow::Error) {
+    error!("剪贴板操作失败: {:#}", err);
+    popup.set_clipboard_error(SharedString::from(i18n::t().clipboard_error));
+}
+
 fn apply_captured_hotkey(
     win: &SettingsWindow,
     hotkey_manager: &Arc<Mutex<HotkeyManager>>,
@@ -795,23 +3256,38 @@ fn apply_captured_hotkey(
         return;
     }
     let previous = win.get_hotkey().to_string();
+    let t = i18n::t();
+    if hotkey::is_reserved_hotkey(hotkey) {
+        win.set_hotkey(SharedString::from(&previous));
+        win.set_hotkey_error(SharedString::from(
+            t.hotkey_reserved.replace("{hotkey}", hotkey).replace("{previous}", &previous),
+        ));
+        return;
+    }
     let hotkey_result = hotkey_manager
         .lock()
         .map_err(|e| format!("hotkey manager unavailable: {}", e))
         .and_then(|mut mgr| mgr.update_hotkey(hotkey).map_err(|e| e.to_string()));
 
     if let Err(err) = hotkey_result {
-        eprintln!("预览更新全局快捷键失败: {}", err);
+        error!("预览更新全局快捷键失败: {}", err);
         win.set_hotkey(SharedString::from(&previous));
+        win.set_hotkey_error(SharedString::from(
+            t.hotkey_register_failed
+                .replace("{hotkey}", hotkey)
+                .replace("{previous}", &previous)
+                .replace("{error}", &err),
+        ));
         return;
     }
 
     win.set_hotkey(SharedString::from(hotkey));
+    win.set_hotkey_error(SharedString::new());
 
     if let Ok(mut state) = shared_state.lock() {
         state.config.hotkey = hotkey.to_string();
         if let Err(e) = state.config.save() {
-            eprintln!("写入配置失败: {}", e);
+            error!("写入配置失败: {}", e);
         }
     }
 }
@@ -869,7 +3345,7 @@ fn show_macos_permission_alert(reason: &str) {
     }
 
     if !reason.is_empty() {
-        eprintln!("keyboard monitor error: {}", reason);
+        error!("keyboard monitor error: {}", reason);
     }
 }
 
@@ -898,6 +3374,40 @@ fn set_popup_i18n_texts(popup: &TranslatePopup) {
     popup.set_i18n_copy(SharedString::from(t.copy));
     popup.set_i18n_apply(SharedString::from(t.apply));
     popup.set_i18n_hint(SharedString::from(t.hint_apply));
+    popup.set_i18n_bullet_list(SharedString::from(t.bullet_list));
+    popup.set_i18n_expand(SharedString::from(t.expand));
+    popup.set_i18n_insert_comment(SharedString::from(t.insert_comment));
+    popup.set_i18n_swap_direction(SharedString::from(t.swap_direction));
+    popup.set_i18n_undo_swap(SharedString::from(t.undo_swap));
+    popup.set_i18n_speak(SharedString::from(t.speak));
+    popup.set_i18n_stop_speaking(SharedString::from(t.stop_speaking));
+    popup.set_i18n_copy_json(SharedString::from(t.copy_json));
+    popup.set_i18n_preview_label(SharedString::from(t.preview_label));
+    popup.set_i18n_translate_full(SharedString::from(t.translate_full));
+    popup.set_i18n_raw_response(SharedString::from(t.raw_response));
+    popup.set_i18n_source(SharedString::from(t.review_source));
+}
+
+/// Set i18n texts for review window
+fn set_review_i18n_texts(review: &ReviewWindow) {
+    let t = i18n::t();
+    review.set_i18n_source(SharedString::from(t.review_source));
+    review.set_i18n_translation(SharedString::from(t.review_translation));
+    review.set_i18n_translating(SharedString::from(t.translating));
+    review.set_i18n_copy(SharedString::from(t.copy));
+    review.set_i18n_apply(SharedString::from(t.apply));
+    review.set_i18n_retranslate(SharedString::from(t.retranslate));
+    review.set_i18n_close(SharedString::from(t.close));
+}
+
+/// Set i18n texts for history window
+fn set_history_i18n_texts(win: &HistoryWindow) {
+    let t = i18n::t();
+    win.set_i18n_title(SharedString::from(t.history_title));
+    win.set_i18n_empty(SharedString::from(t.history_empty));
+    win.set_i18n_copy(SharedString::from(t.copy));
+    win.set_i18n_clear(SharedString::from(t.history_clear));
+    win.set_i18n_close(SharedString::from(t.close));
 }
 
 /// Set i18n texts for settings window
@@ -906,14 +3416,46 @@ fn set_settings_i18n_texts(win: &SettingsWindow) {
     win.set_i18n_title(SharedString::from(t.settings_title));
     win.set_i18n_hotkey(SharedString::from(t.global_hotkey));
     win.set_i18n_hotkey_placeholder(SharedString::from(t.hotkey_placeholder));
+    win.set_i18n_send_modifier(SharedString::from(t.send_modifier));
+    let modifier_names: Vec<SharedString> = SendModifier::available()
+        .iter()
+        .map(|m| match m {
+            SendModifier::Auto => t.send_modifier_auto.into(),
+            SendModifier::Ctrl => "Ctrl".into(),
+            SendModifier::Cmd => "Cmd".into(),
+        })
+        .collect();
+    win.set_send_modifier_names(ModelRc::new(VecModel::from(modifier_names)));
     win.set_i18n_hotkey_recording(SharedString::from(t.hotkey_recording));
     win.set_i18n_provider(SharedString::from(t.translation_provider));
+    win.set_i18n_fallback_providers(SharedString::from(t.fallback_providers));
     win.set_i18n_provider_settings(SharedString::from(t.provider_settings));
+    win.set_i18n_provider_type(SharedString::from(t.provider_type));
+    win.set_i18n_provider_add(SharedString::from(t.provider_add));
+    win.set_i18n_provider_delete(SharedString::from(t.provider_delete));
     win.set_i18n_google_hint(SharedString::from(t.google_no_config));
+    win.set_i18n_google_smart_join(SharedString::from(t.google_smart_join));
     win.set_i18n_deepl_settings(SharedString::from(t.deepl_settings));
     win.set_i18n_api_key(SharedString::from(t.api_key));
     win.set_i18n_api_key_placeholder(SharedString::from(t.api_key_placeholder));
+    win.set_i18n_api_key_clear(SharedString::from(t.api_key_clear));
+    win.set_i18n_api_key_clear_confirm(SharedString::from(t.api_key_clear_confirm));
+    win.set_i18n_api_key_required(SharedString::from(t.api_key_required));
+    win.set_i18n_provider_reset(SharedString::from(t.provider_reset));
+    win.set_i18n_provider_reset_confirm(SharedString::from(t.provider_reset_confirm));
     win.set_i18n_deepl_hint(SharedString::from(t.deepl_hint));
+    win.set_i18n_libretranslate_hint(SharedString::from(t.libretranslate_hint));
+    win.set_i18n_deepl_formality(SharedString::from(t.deepl_formality));
+    win.set_i18n_deepl_preserve_formatting(SharedString::from(t.deepl_preserve_formatting));
+    let formality_names: Vec<SharedString> = vec![
+        t.formality_default.into(),
+        t.formality_more.into(),
+        t.formality_less.into(),
+        t.formality_prefer_more.into(),
+        t.formality_prefer_less.into(),
+    ];
+    win.set_deepl_formality_names(ModelRc::new(VecModel::from(formality_names)));
+    win.set_i18n_azure_hint(SharedString::from(t.azure_hint));
     win.set_i18n_api_settings(SharedString::from(t.api_settings));
     win.set_i18n_api_base(SharedString::from(t.api_base_url));
     win.set_i18n_model(SharedString::from(t.model));
@@ -926,10 +3468,77 @@ fn set_settings_i18n_texts(win: &SettingsWindow) {
     win.set_i18n_prompt_name(SharedString::from(t.prompt_name));
     win.set_i18n_prompt_system(SharedString::from(t.prompt_system));
     win.set_i18n_prompt_user(SharedString::from(t.prompt_user));
+    win.set_i18n_prompt_temperature(SharedString::from(t.prompt_temperature));
+    win.set_i18n_prompt_max_tokens(SharedString::from(t.prompt_max_tokens));
+    win.set_i18n_prompt_response_json(SharedString::from(t.prompt_response_json));
     win.set_i18n_prompt_vars(SharedString::from(t.prompt_vars));
+    win.set_i18n_prompt_examples(SharedString::from(t.prompt_examples));
+    win.set_i18n_prompt_example_source(SharedString::from(t.prompt_example_source));
+    win.set_i18n_prompt_example_target(SharedString::from(t.prompt_example_target));
     win.set_i18n_cancel(SharedString::from(t.cancel));
     win.set_i18n_language(SharedString::from(t.ui_language));
+    win.set_i18n_recenter_settings(SharedString::from(t.recenter_settings));
+    win.set_i18n_theme(SharedString::from(t.theme));
+    let theme_names: Vec<SharedString> = vec![t.theme_system.into(), t.theme_light.into(), t.theme_dark.into()];
+    win.set_theme_names(ModelRc::new(VecModel::from(theme_names)));
     win.set_i18n_hotkey_log_title(SharedString::from(t.hotkey_log_title));
     win.set_i18n_hotkey_log_enable(SharedString::from(t.hotkey_log_enable));
     win.set_i18n_hotkey_log_hint(SharedString::from(t.hotkey_log_hint));
+    win.set_i18n_history_enable(SharedString::from(t.history_enable));
+    win.set_i18n_popup_title(SharedString::from(t.popup_title));
+    win.set_i18n_auto_expand_long(SharedString::from(t.auto_expand_long));
+    win.set_i18n_preview_long(SharedString::from(t.preview_long));
+    win.set_i18n_popup_size(SharedString::from(t.popup_size));
+    win.set_i18n_popup_no_activate(SharedString::from(t.popup_no_activate));
+    win.set_i18n_popup_always_on_top(SharedString::from(t.popup_always_on_top));
+    win.set_i18n_dismiss_on_focus_loss(SharedString::from(t.dismiss_on_focus_loss));
+    win.set_i18n_auto_close(SharedString::from(t.auto_close));
+    win.set_i18n_auto_copy_result(SharedString::from(t.auto_copy_result));
+    win.set_i18n_stream_responses(SharedString::from(t.stream_responses));
+    win.set_i18n_show_raw_response(SharedString::from(t.show_raw_response));
+    win.set_i18n_network_title(SharedString::from(t.network_title));
+    win.set_i18n_advanced_title(SharedString::from(t.advanced_title));
+    win.set_i18n_backup_title(SharedString::from(t.backup_title));
+    win.set_i18n_export_api_keys(SharedString::from(t.export_api_keys));
+    win.set_i18n_settings_export(SharedString::from(t.settings_export));
+    win.set_i18n_settings_import(SharedString::from(t.settings_import));
+    win.set_i18n_reset_defaults(SharedString::from(t.reset_defaults));
+    win.set_i18n_reset_defaults_confirm(SharedString::from(t.reset_defaults_confirm));
+    win.set_i18n_reset_defaults_warning(SharedString::from(t.reset_defaults_warning));
+    win.set_i18n_input_delays(SharedString::from(t.input_delays));
+    win.set_i18n_retry_settings(SharedString::from(t.retry_settings));
+    win.set_i18n_cache_capacity(SharedString::from(t.cache_capacity));
+    win.set_i18n_max_requests_per_hour(SharedString::from(t.max_requests_per_hour));
+    win.set_i18n_max_chunk_chars(SharedString::from(t.max_chunk_chars));
+    win.set_i18n_proxy_url(SharedString::from(t.proxy_url));
+    win.set_i18n_retry_empty_result(SharedString::from(t.retry_empty_result));
+    win.set_i18n_request_timeout(SharedString::from(t.request_timeout));
+    win.set_i18n_org_project(SharedString::from(t.org_project));
+    win.set_i18n_extra_headers(SharedString::from(t.extra_headers));
+    win.set_i18n_header_name(SharedString::from(t.header_name));
+    win.set_i18n_header_value(SharedString::from(t.header_value));
+    win.set_i18n_auto_pick_title(SharedString::from(t.auto_pick_title));
+    win.set_i18n_auto_pick_enable(SharedString::from(t.auto_pick_enable));
+    win.set_i18n_auto_pick_partner(SharedString::from(t.auto_pick_partner));
+    win.set_i18n_auto_pick_strategy(SharedString::from(t.auto_pick_strategy));
+    let strategy_names: Vec<SharedString> = vec![t.pick_paragraphs.into(), t.pick_length.into()];
+    win.set_auto_pick_strategy_names(ModelRc::new(VecModel::from(strategy_names)));
+    win.set_i18n_batch_title(SharedString::from(t.batch_title));
+    win.set_i18n_glossary_title(SharedString::from(t.glossary_title));
+    win.set_i18n_skip_title(SharedString::from(t.skip_title));
+    win.set_i18n_skip_hint(SharedString::from(t.skip_hint));
+    win.set_i18n_notify_skipped(SharedString::from(t.notify_skipped));
+    win.set_i18n_glossary_hint(SharedString::from(t.glossary_hint));
+    win.set_i18n_batch_translate(SharedString::from(t.batch_translate));
+    win.set_i18n_batch_delimiter(SharedString::from(t.batch_delimiter));
+    win.set_i18n_multi_target_title(SharedString::from(t.multi_target_title));
+    win.set_i18n_multi_target(SharedString::from(t.multi_target));
+    win.set_i18n_target_langs(SharedString::from(t.target_langs));
+    win.set_i18n_secondary_lang(SharedString::from(t.secondary_lang));
+    win.set_i18n_price_per_1k(SharedString::from(t.price_per_1k));
+    win.set_i18n_prompt_import(SharedString::from(t.prompt_import));
+    win.set_i18n_provider_test(SharedString::from(t.provider_test));
+    win.set_i18n_fetch_models(SharedString::from(t.fetch_models));
+    win.set_i18n_models_fetching(SharedString::from(t.models_fetching));
+    win.set_i18n_prompt_export(SharedString::from(t.prompt_export));
 }
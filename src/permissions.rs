@@ -0,0 +1,90 @@
+//! System permission pre-check
+//! macOS: Accessibility (AXIsProcessTrusted) and Input Monitoring (CGPreflightListenEventAccess)
+
+/// Snapshot of the permissions the hotkey/paste flow depends on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PermissionStatus {
+    pub accessibility: bool,
+    pub input_monitoring: bool,
+}
+
+impl PermissionStatus {
+    pub fn all_granted(&self) -> bool {
+        self.accessibility && self.input_monitoring
+    }
+}
+
+/// Startup decision, `prompted` is the persisted "already asked" flag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PromptDecision {
+    pub show_alert: bool,
+    pub prompted: bool,
+}
+
+/// Only ask once while permissions are missing; once everything is granted
+/// the flag resets so a later revoke gets a fresh prompt.
+pub fn decide_prompt(status: PermissionStatus, prompted: bool) -> PromptDecision {
+    if status.all_granted() {
+        return PromptDecision { show_alert: false, prompted: false };
+    }
+    PromptDecision { show_alert: !prompted, prompted: true }
+}
+
+#[cfg(target_os = "macos")]
+pub fn current_status() -> PermissionStatus {
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> bool;
+    }
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGPreflightListenEventAccess() -> bool;
+    }
+
+    // 两个接口都只查询状态，不会触发系统弹窗
+    unsafe {
+        PermissionStatus {
+            accessibility: AXIsProcessTrusted(),
+            input_monitoring: CGPreflightListenEventAccess(),
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn current_status() -> PermissionStatus {
+    PermissionStatus { accessibility: true, input_monitoring: true }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRANTED: PermissionStatus = PermissionStatus { accessibility: true, input_monitoring: true };
+    const NO_AX: PermissionStatus = PermissionStatus { accessibility: false, input_monitoring: true };
+    const NO_INPUT: PermissionStatus = PermissionStatus { accessibility: true, input_monitoring: false };
+
+    #[test]
+    fn test_prompt_once_while_missing() {
+        let first = decide_prompt(NO_AX, false);
+        assert_eq!(first, PromptDecision { show_alert: true, prompted: true });
+
+        // 已经提示过，下次启动不再打扰
+        let second = decide_prompt(NO_AX, first.prompted);
+        assert_eq!(second, PromptDecision { show_alert: false, prompted: true });
+
+        let other_missing = decide_prompt(NO_INPUT, second.prompted);
+        assert!(!other_missing.show_alert);
+    }
+
+    #[test]
+    fn test_prompt_resets_after_grant() {
+        assert_eq!(decide_prompt(GRANTED, false), PromptDecision { show_alert: false, prompted: false });
+
+        let granted = decide_prompt(GRANTED, true);
+        assert!(!granted.show_alert);
+        assert!(!granted.prompted);
+
+        // 授权后又被撤销，重新提示
+        assert!(decide_prompt(NO_INPUT, granted.prompted).show_alert);
+    }
+}
@@ -0,0 +1,131 @@
+//! Auto-pick between two providers' translations
+//! Empty results and refusals are ruled out first, the rest are ranked by the chosen strategy
+
+use serde::{Deserialize, Serialize};
+
+use crate::usage::estimate_tokens;
+
+/// How the remaining candidates are ranked
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PickStrategy {
+    /// Paragraph (non-empty line) count closest to the source, length breaks ties
+    #[default]
+    Paragraphs,
+    /// Estimated token count closest to the source
+    Length,
+}
+
+impl PickStrategy {
+    pub const ALL: [PickStrategy; 2] = [PickStrategy::Paragraphs, PickStrategy::Length];
+}
+
+/// Typical openings of a model refusing or answering instead of translating
+const REFUSAL_PREFIXES: &[&str] = &[
+    "i'm sorry",
+    "i am sorry",
+    "sorry, i",
+    "i cannot",
+    "i can't",
+    "i can not",
+    "i'm unable",
+    "i am unable",
+    "as an ai",
+    "抱歉",
+    "对不起",
+    "我无法",
+    "我不能",
+    "作为一个ai",
+    "作为一个 ai",
+    "作为ai",
+];
+
+/// Index of the best candidate, None when every candidate is empty or a refusal
+pub fn pick_best(source: &str, candidates: &[&str], strategy: PickStrategy) -> Option<usize> {
+    candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| is_usable(source, c))
+        .min_by(|(_, a), (_, b)| {
+            let (a, b) = (rank(source, a, strategy), rank(source, b, strategy));
+            a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .map(|(i, _)| i)
+}
+
+fn is_usable(source: &str, candidate: &str) -> bool {
+    !candidate.trim().is_empty() && !is_refusal(source, candidate)
+}
+
+/// A refusal opening only counts when the source itself doesn't start that way
+/// (translating "I'm sorry..." into English is fine)
+pub fn is_refusal(source: &str, candidate: &str) -> bool {
+    let starts_with_refusal = |text: &str| {
+        let head: String = text.trim_start().chars().take(24).collect::<String>().to_lowercase();
+        REFUSAL_PREFIXES.iter().any(|p| head.starts_with(p))
+    };
+    starts_with_refusal(candidate) && !starts_with_refusal(source)
+}
+
+/// Lower is better; ties keep the earlier candidate (the active provider)
+fn rank(source: &str, candidate: &str, strategy: PickStrategy) -> (f32, f32) {
+    let length = length_distance(source, candidate);
+    match strategy {
+        PickStrategy::Paragraphs => {
+            let diff = paragraph_count(source).abs_diff(paragraph_count(candidate));
+            (diff as f32, length)
+        }
+        PickStrategy::Length => (length, 0.0),
+    }
+}
+
+fn paragraph_count(text: &str) -> usize {
+    text.lines().filter(|l| !l.trim().is_empty()).count()
+}
+
+/// Distance of the token-count ratio from 1, token estimates keep CJK and Latin text comparable
+fn length_distance(source: &str, candidate: &str) -> f32 {
+    let source = estimate_tokens(source).max(1) as f32;
+    let candidate = estimate_tokens(candidate).max(1) as f32;
+    (candidate / source).ln().abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_skips_empty_and_refusal() {
+        let source = "Restart the service after changing the config.";
+        let candidates = [
+            "",
+            "I'm sorry, but I can't help with that request.",
+            "修改配置后重启服务。",
+        ];
+        assert_eq!(pick_best(source, &candidates, PickStrategy::Paragraphs), Some(2));
+        assert_eq!(pick_best(source, &candidates, PickStrategy::Length), Some(2));
+
+        assert!(is_refusal(source, "抱歉，我无法翻译这段内容。"));
+        // 原文本身就是道歉时不算拒绝
+        assert!(!is_refusal("I'm sorry for the delay.", "I'm sorry for the delay."));
+        assert_eq!(pick_best(source, &["", "  "], PickStrategy::Paragraphs), None);
+    }
+
+    #[test]
+    fn test_pick_prefers_matching_paragraphs() {
+        let source = "First point.\n\nSecond point.\n\nThird point.";
+        let merged = "第一点。第二点。第三点。";
+        let kept = "第一点。\n\n第二点。\n\n第三点。";
+        assert_eq!(pick_best(source, &[merged, kept], PickStrategy::Paragraphs), Some(1));
+    }
+
+    #[test]
+    fn test_pick_prefers_closer_length() {
+        let source = "The quick brown fox jumps over the lazy dog near the river bank.";
+        let truncated = "敏捷的狐狸。";
+        let full = "敏捷的棕色狐狸跳过了河岸边的懒狗。";
+        assert_eq!(pick_best(source, &[truncated, full], PickStrategy::Length), Some(1));
+        // 完全相同时保留第一个
+        assert_eq!(pick_best(source, &[full, full], PickStrategy::Length), Some(0));
+    }
+}
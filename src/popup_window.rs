@@ -0,0 +1,272 @@
+//! Non-activating popup mode
+//! Windows: WS_EX_NOACTIVATE on the popup window, macOS: hand activation back to the previous app
+//! Also decides when the popup goes away after losing focus or sitting idle
+
+use std::time::{Duration, Instant};
+
+/// Right after `show()` the foreground can still flip back to the source app, ignore focus changes until then
+pub const FOCUS_PROTECT: Duration = Duration::from_millis(500);
+
+/// `WS_EX_NOACTIVATE`, kept here so the style math is testable on every platform
+#[cfg(any(target_os = "windows", test))]
+const WS_EX_NOACTIVATE: isize = 0x0800_0000;
+
+/// Whatever had focus before the popup was shown (HWND on Windows, pid on macOS)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreviousFocus(isize);
+
+/// Extended window style with the no-activate bit set or cleared
+#[cfg(any(target_os = "windows", test))]
+pub fn ex_style_with_no_activate(current: isize, no_activate: bool) -> isize {
+    if no_activate {
+        current | WS_EX_NOACTIVATE
+    } else {
+        current & !WS_EX_NOACTIVATE
+    }
+}
+
+/// Only hand focus back when the mode is on, we know who had it, and showing
+/// the popup actually took it away from them
+pub fn should_restore_focus(no_activate: bool, previous: Option<PreviousFocus>, ours_active: bool) -> bool {
+    no_activate && previous.is_some() && ours_active
+}
+
+/// State sampled by the popup timer for the dismiss-on-focus-loss decision
+#[derive(Debug, Clone, Copy)]
+pub struct FocusCheck {
+    pub enabled: bool,
+    pub pinned: bool,
+    /// The popup was in the foreground at some point since it was shown
+    pub had_focus: bool,
+    pub focused: bool,
+    pub shown_at: Option<Instant>,
+    pub now: Instant,
+}
+
+/// Hide only after the popup had focus and lost it again, outside the protection window and not pinned
+/// (a no-activate popup the user never clicked stays until Ctrl+V or close)
+pub fn should_dismiss_on_focus_loss(check: &FocusCheck) -> bool {
+    if !check.enabled || check.pinned || !check.had_focus || check.focused {
+        return false;
+    }
+    match check.shown_at {
+        Some(shown_at) => check.now.saturating_duration_since(shown_at) >= FOCUS_PROTECT,
+        None => false,
+    }
+}
+
+/// Idle auto-close: off when unset, pinned or busy (translating, reading aloud), otherwise after `secs` since the last interaction
+pub fn should_auto_close(secs: Option<u32>, pinned: bool, busy: bool, last_activity: Option<Instant>, now: Instant) -> bool {
+    let (Some(secs), Some(last)) = (secs.filter(|&s| s > 0), last_activity) else { return false; };
+    !pinned && !busy && now.saturating_duration_since(last) >= Duration::from_secs(u64::from(secs))
+}
+
+/// Whether one of our windows is in the foreground, polled while the popup is open
+pub fn ours_focused() -> bool {
+    platform::ours_active()
+}
+
+/// Capture the focused app/window, call before `show()`
+pub fn remember_focus(no_activate: bool) -> Option<PreviousFocus> {
+    if !no_activate {
+        return None;
+    }
+    platform::foreground().map(PreviousFocus)
+}
+
+/// Apply the style and give focus back, call right after `show()`
+/// (the native window only exists once the popup has been shown once)
+pub fn after_show(window: &slint::Window, no_activate: bool, previous: Option<PreviousFocus>) {
+    platform::apply_style(window, no_activate);
+    if should_restore_focus(no_activate, previous, platform::ours_active()) {
+        if let Some(PreviousFocus(handle)) = previous {
+            platform::restore(handle);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetForegroundWindow, GetWindowLongPtrW, GetWindowThreadProcessId, SetForegroundWindow,
+        SetWindowLongPtrW, GWL_EXSTYLE,
+    };
+
+    fn is_ours(hwnd: HWND) -> bool {
+        let mut process_id: u32 = 0;
+        unsafe { GetWindowThreadProcessId(hwnd, Some(&mut process_id)) };
+        process_id == std::process::id()
+    }
+
+    pub fn foreground() -> Option<isize> {
+        let hwnd = unsafe { GetForegroundWindow() };
+        // 自己的窗口不需要记录
+        if hwnd.0.is_null() || is_ours(hwnd) {
+            return None;
+        }
+        Some(hwnd.0 as isize)
+    }
+
+    pub fn ours_active() -> bool {
+        let hwnd = unsafe { GetForegroundWindow() };
+        !hwnd.0.is_null() && is_ours(hwnd)
+    }
+
+    pub fn apply_style(window: &slint::Window, no_activate: bool) {
+        let handle = window.window_handle();
+        let Ok(raw) = handle.window_handle() else { return; };
+        let RawWindowHandle::Win32(win32) = raw.as_raw() else { return; };
+        let hwnd = HWND(win32.hwnd.get() as *mut core::ffi::c_void);
+        unsafe {
+            let current = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+            let updated = super::ex_style_with_no_activate(current, no_activate);
+            if updated != current {
+                SetWindowLongPtrW(hwnd, GWL_EXSTYLE, updated);
+            }
+        }
+    }
+
+    pub fn restore(handle: isize) {
+        let _ = unsafe { SetForegroundWindow(HWND(handle as *mut core::ffi::c_void)) };
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use cocoa::base::{id, nil, BOOL, NO};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    /// NSApplicationActivateIgnoringOtherApps
+    const ACTIVATE_IGNORING_OTHER_APPS: usize = 1 << 1;
+
+    pub fn foreground() -> Option<isize> {
+        unsafe {
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let app: id = msg_send![workspace, frontmostApplication];
+            if app == nil {
+                return None;
+            }
+            let pid: i32 = msg_send![app, processIdentifier];
+            (pid as u32 != std::process::id()).then_some(pid as isize)
+        }
+    }
+
+    pub fn ours_active() -> bool {
+        unsafe {
+            let app: id = msg_send![class!(NSApplication), sharedApplication];
+            let active: BOOL = msg_send![app, isActive];
+            active != NO
+        }
+    }
+
+    // 普通 NSWindow 不支持 non-activating panel 样式，靠把激活还给原应用实现
+    pub fn apply_style(_window: &slint::Window, _no_activate: bool) {}
+
+    pub fn restore(pid: isize) {
+        unsafe {
+            let app: id = msg_send![
+                class!(NSRunningApplication),
+                runningApplicationWithProcessIdentifier: pid as i32
+            ];
+            if app != nil {
+                let _: BOOL = msg_send![app, activateWithOptions: ACTIVATE_IGNORING_OTHER_APPS];
+            }
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    pub fn foreground() -> Option<isize> {
+        None
+    }
+
+    pub fn ours_active() -> bool {
+        false
+    }
+
+    pub fn apply_style(_window: &slint::Window, _no_activate: bool) {}
+
+    pub fn restore(_handle: isize) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ex_style_toggle() {
+        let base = 0x0000_0008; // WS_EX_TOPMOST
+        let on = ex_style_with_no_activate(base, true);
+        assert_eq!(on, base | WS_EX_NOACTIVATE);
+        assert_eq!(ex_style_with_no_activate(on, true), on);
+        assert_eq!(ex_style_with_no_activate(on, false), base);
+    }
+
+    #[test]
+    fn test_should_restore_focus() {
+        let prev = Some(PreviousFocus(42));
+        assert!(should_restore_focus(true, prev, true));
+        assert!(!should_restore_focus(false, prev, true));
+        assert!(!should_restore_focus(true, None, true));
+        // 弹窗没有抢到焦点就不用还
+        assert!(!should_restore_focus(true, prev, false));
+    }
+
+    #[test]
+    fn test_dismiss_on_focus_loss() {
+        let shown = Instant::now();
+        let lost = FocusCheck {
+            enabled: true,
+            pinned: false,
+            had_focus: true,
+            focused: false,
+            shown_at: Some(shown),
+            now: shown + Duration::from_secs(2),
+        };
+        assert!(should_dismiss_on_focus_loss(&lost));
+        assert!(!should_dismiss_on_focus_loss(&FocusCheck { enabled: false, ..lost }));
+        assert!(!should_dismiss_on_focus_loss(&FocusCheck { pinned: true, ..lost }));
+        assert!(!should_dismiss_on_focus_loss(&FocusCheck { focused: true, ..lost }));
+        // 从没拿到过焦点（不抢焦点模式）时不算失去焦点
+        assert!(!should_dismiss_on_focus_loss(&FocusCheck { had_focus: false, ..lost }));
+    }
+
+    #[test]
+    fn test_dismiss_waits_for_protection_window() {
+        let shown = Instant::now();
+        let check = FocusCheck {
+            enabled: true,
+            pinned: false,
+            had_focus: true,
+            focused: false,
+            shown_at: Some(shown),
+            now: shown + Duration::from_millis(100),
+        };
+        assert!(!should_dismiss_on_focus_loss(&check));
+        assert!(should_dismiss_on_focus_loss(&FocusCheck { now: shown + FOCUS_PROTECT, ..check }));
+        assert!(!should_dismiss_on_focus_loss(&FocusCheck { shown_at: None, ..check }));
+    }
+
+    #[test]
+    fn test_auto_close() {
+        let last = Instant::now();
+        let later = last + Duration::from_secs(10);
+        assert!(should_auto_close(Some(10), false, false, Some(last), later));
+        assert!(!should_auto_close(Some(10), false, false, Some(last), last + Duration::from_secs(9)));
+        assert!(!should_auto_close(None, false, false, Some(last), later));
+        assert!(!should_auto_close(Some(0), false, false, Some(last), later));
+        assert!(!should_auto_close(Some(10), true, false, Some(last), later));
+        assert!(!should_auto_close(Some(10), false, true, Some(last), later));
+        assert!(!should_auto_close(Some(10), false, false, None, later));
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_no_activate_constant_matches_win32() {
+        use windows::Win32::UI::WindowsAndMessaging::WS_EX_NOACTIVATE as WIN32_NOACTIVATE;
+        assert_eq!(WS_EX_NOACTIVATE, WIN32_NOACTIVATE.0 as isize);
+    }
+}
@@ -0,0 +1,186 @@
+//! Optional transforms applied to a translation before it is shown
+//! Kept separate from providers so toggling never needs a new request
+
+/// Minimum number of items before text counts as a list
+const MIN_LIST_ITEMS: usize = 3;
+/// Items longer than this look like prose rather than list entries
+const MAX_ITEM_CHARS: usize = 48;
+/// Newline-separated items may be longer (one entry per line)
+const MAX_LINE_ITEM_CHARS: usize = 120;
+
+/// 常见列表连接词，拆分后从条目开头去掉（单字的“和/或”容易误伤词语，不处理）
+const LEADING_CONJUNCTIONS: [&str; 3] = ["and ", "or ", "以及"];
+
+/// Heuristic check for comma/semicolon/newline separated items
+pub fn is_list_like(text: &str) -> bool {
+    let text = text.trim();
+    if text.is_empty() {
+        return false;
+    }
+
+    let lines: Vec<&str> = text.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+    if lines.len() >= MIN_LIST_ITEMS {
+        return lines.iter().all(|l| l.chars().count() <= MAX_LINE_ITEM_CHARS && !has_inner_sentence_end(l));
+    }
+
+    for separators in [&[';', '；'][..], &[',', '，', '、'][..]] {
+        let items = split_items(text, separators);
+        if items.len() >= MIN_LIST_ITEMS {
+            return items
+                .iter()
+                .all(|item| item.chars().count() <= MAX_ITEM_CHARS && !has_inner_sentence_end(item));
+        }
+    }
+    false
+}
+
+/// Format the translation as a bullet list when the source looks like one.
+/// Returns the translation unchanged if it cannot be split cleanly.
+pub fn format_as_bullets(source: &str, translated: &str) -> String {
+    if !is_list_like(source) {
+        return translated.to_string();
+    }
+
+    let translated = translated.trim();
+    let lines: Vec<&str> = translated.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+    let items = if lines.len() >= 2 {
+        lines.into_iter().map(clean_item).collect::<Vec<_>>()
+    } else {
+        [&[';', '；'][..], &[',', '，', '、'][..]]
+            .iter()
+            .map(|seps| split_items(translated, seps))
+            .find(|items| items.len() >= 2)
+            .unwrap_or_default()
+    };
+
+    if items.len() < 2 {
+        return translated.to_string();
+    }
+
+    items
+        .iter()
+        .map(|item| format!("- {}", item))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn split_items(text: &str, separators: &[char]) -> Vec<String> {
+    text.split(|c| separators.contains(&c))
+        .map(clean_item)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn clean_item(item: &str) -> String {
+    let mut item = item.trim();
+    // 已经是列表格式的条目先去掉原有的符号
+    for marker in ["- ", "* ", "• "] {
+        if let Some(rest) = item.strip_prefix(marker) {
+            item = rest.trim_start();
+        }
+    }
+    for conj in LEADING_CONJUNCTIONS {
+        if let Some(rest) = item.strip_prefix(conj) {
+            item = rest.trim_start();
+            break;
+        }
+    }
+    item.trim_end_matches(['.', '。', '!', '！', '?', '？']).trim().to_string()
+}
+
+/// Strip a ``` fence that an LLM wrapped around a plain translation.
+/// Conservative: the whole output must be one fence without a language tag,
+/// and the source must not itself be a fenced block (real code stays intact).
+pub fn unwrap_code_fence(source: &str, translated: &str) -> String {
+    let trimmed = translated.trim();
+    if source.trim_start().starts_with("```") {
+        return trimmed.to_string();
+    }
+
+    let inner = trimmed
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .and_then(|rest| rest.split_once('\n'));
+    match inner {
+        // 开头那一行必须为空，带语言标记的视为真正的代码块
+        Some((tag, body)) if tag.trim().is_empty() && !body.contains("```") && !body.trim().is_empty() => {
+            body.trim().to_string()
+        }
+        _ => trimmed.to_string(),
+    }
+}
+
+/// Sentence punctuation before the last char means the item is prose
+fn has_inner_sentence_end(item: &str) -> bool {
+    let trimmed = item.trim_end_matches(['.', '。', '!', '！', '?', '？']);
+    trimmed.contains(['。', '！', '？']) || trimmed.contains(". ") || trimmed.contains("! ") || trimmed.contains("? ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_list_like() {
+        assert!(is_list_like("apples, bananas, oranges"));
+        assert!(is_list_like("red; green; blue"));
+        assert!(is_list_like("苹果、香蕉、橙子"));
+        assert!(is_list_like("first item\nsecond item\nthird item"));
+
+        assert!(!is_list_like(""));
+        assert!(!is_list_like("Hello, world"));
+        assert!(!is_list_like("This is a normal sentence without any list at all."));
+        assert!(!is_list_like(
+            "When I arrived at the station late in the evening, the last train had already left, so I walked home."
+        ));
+        assert!(!is_list_like("First sentence. Second one, with a comma, and more."));
+    }
+
+    #[test]
+    fn test_format_as_bullets() {
+        assert_eq!(
+            format_as_bullets("apples, bananas, oranges", "苹果、香蕉、橙子"),
+            "- 苹果\n- 香蕉\n- 橙子"
+        );
+        assert_eq!(
+            format_as_bullets("苹果，香蕉，橙子", "apples, bananas, and oranges."),
+            "- apples\n- bananas\n- oranges"
+        );
+        assert_eq!(
+            format_as_bullets("one\ntwo\nthree", "一\n二\n三"),
+            "- 一\n- 二\n- 三"
+        );
+    }
+
+    #[test]
+    fn test_format_as_bullets_leaves_prose_untouched() {
+        let source = "I went to the store, bought milk and came back.";
+        let translated = "我去了商店，买了牛奶就回来了。";
+        assert_eq!(format_as_bullets(source, translated), translated);
+
+        // 译文无法拆分时保持原样
+        assert_eq!(format_as_bullets("a, b, c", "abc"), "abc");
+    }
+
+    #[test]
+    fn test_unwrap_code_fence_plain_result() {
+        assert_eq!(unwrap_code_fence("Hello world", "```\n你好，世界\n```"), "你好，世界");
+        assert_eq!(unwrap_code_fence("Hello", "  ```\nline one\nline two\n```\n"), "line one\nline two");
+    }
+
+    #[test]
+    fn test_unwrap_code_fence_keeps_code() {
+        // 带语言标记
+        let tagged = "```rust\nfn main() {}\n```";
+        assert_eq!(unwrap_code_fence("fn main() {}", tagged), tagged);
+
+        // 原文本身就是代码块
+        let fenced = "```\n// 打印问候\nprintln!(\"hi\");\n```";
+        assert_eq!(unwrap_code_fence("```\n// print greeting\nprintln!(\"hi\");\n```", fenced), fenced);
+
+        // 多个代码块或只有部分被包住
+        let mixed = "```\na\n```\ntext\n```\nb\n```";
+        assert_eq!(unwrap_code_fence("a text b", mixed), mixed);
+        assert_eq!(unwrap_code_fence("x", "see ```\ncode\n```"), "see ```\ncode\n```");
+    }
+}
@@ -0,0 +1,209 @@
+//! Rich text selections: the HTML flavor of a copy is split into tags and text runs,
+//! the runs are translated and put back between the original tags
+
+/// Selections with more runs than this are pasted as plain text
+pub const MAX_RICH_TEXT_RUNS: usize = 30;
+
+/// Line between runs when they are sent as one request, models keep it as-is
+const RUN_SEPARATOR: &str = "|||";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    /// Tag, comment or doctype, copied through as-is
+    Markup(&'a str),
+    /// Raw text between tags, entities still encoded
+    Text(&'a str),
+}
+
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+    while !rest.is_empty() {
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            tokens.push(Token::Text(&rest[..end]));
+            rest = &rest[end..];
+            continue;
+        }
+        let end = if rest.starts_with("<!--") {
+            rest.find("-->").map(|i| i + 3)
+        } else {
+            rest.find('>').map(|i| i + 1)
+        };
+        // 没闭合的标签按文本处理，剩下的内容原样保留
+        let Some(end) = end else {
+            tokens.push(Token::Text(rest));
+            break;
+        };
+        tokens.push(Token::Markup(&rest[..end]));
+        rest = &rest[end..];
+    }
+    tokens
+}
+
+/// Lowercased tag name and whether it closes, None for comments and doctypes
+fn tag_name(markup: &str) -> Option<(String, bool)> {
+    let inner = markup.strip_prefix('<')?;
+    let (closing, inner) = match inner.strip_prefix('/') {
+        Some(inner) => (true, inner),
+        None => (false, inner),
+    };
+    let name: String = inner.chars().take_while(|c| c.is_ascii_alphanumeric()).collect();
+    (!name.is_empty()).then(|| (name.to_ascii_lowercase(), closing))
+}
+
+/// Indices of the text tokens that carry translatable text, script/style bodies are skipped
+fn translatable(tokens: &[Token<'_>]) -> Vec<usize> {
+    let mut skipping = false;
+    let mut indices = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Markup(markup) => {
+                if let Some((name, closing)) = tag_name(markup) {
+                    if name == "script" || name == "style" {
+                        skipping = !closing;
+                    }
+                }
+            }
+            Token::Text(text) if !skipping && !decode_entities(text).trim().is_empty() => indices.push(i),
+            Token::Text(_) => {}
+        }
+    }
+    indices
+}
+
+/// Decoded text of each run, in document order
+pub fn text_runs(html: &str) -> Vec<String> {
+    let tokens = tokenize(html);
+    translatable(&tokens)
+        .into_iter()
+        .filter_map(|i| match tokens[i] {
+            Token::Text(text) => Some(decode_entities(text).trim().to_string()),
+            Token::Markup(_) => None,
+        })
+        .collect()
+}
+
+/// The same HTML with every run replaced by its translation, None when the counts don't line up
+pub fn rebuild(html: &str, translated: &[String]) -> Option<String> {
+    let tokens = tokenize(html);
+    let runs = translatable(&tokens);
+    if runs.len() != translated.len() {
+        return None;
+    }
+    let mut out = String::with_capacity(html.len());
+    let mut next = translated.iter();
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Text(text) if runs.contains(&i) => {
+                // 保留原来两侧的空白，行内标签之间的空格不会丢
+                let core = text.trim();
+                let start = text.len() - text.trim_start().len();
+                out.push_str(&text[..start]);
+                out.push_str(&escape_html(next.next()?.trim()));
+                out.push_str(&text[start + core.len()..]);
+            }
+            Token::Text(text) | Token::Markup(text) => out.push_str(text),
+        }
+    }
+    Some(out)
+}
+
+/// All runs as one text for a single translation request
+pub fn join_runs(runs: &[String]) -> String {
+    runs.join(&format!("\n{}\n", RUN_SEPARATOR))
+}
+
+/// Split a translation of `join_runs` back into runs, None when the count changed
+pub fn split_runs(translated: &str, count: usize) -> Option<Vec<String>> {
+    let runs: Vec<String> = translated.split(RUN_SEPARATOR).map(|run| run.trim().to_string()).collect();
+    (runs.len() == count).then_some(runs)
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\n' => out.push_str("<br>"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Common named entities and numeric references, anything unknown stays as written
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                    None => entity.strip_prefix('#').and_then(|n| n.parse().ok()).and_then(char::from_u32),
+                },
+            };
+            c.map(|c| (c, end + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_runs_skip_tags_and_scripts() {
+        let html = "<!--StartFragment--><p>Hello <b>bold</b> &amp; more</p>\n<style>p { color: red }</style><p> </p><!--EndFragment-->";
+        assert_eq!(text_runs(html), vec!["Hello", "bold", "& more"]);
+    }
+
+    #[test]
+    fn test_rebuild_keeps_tags_and_spacing() {
+        let html = "<p>Hello <b>bold</b> world</p>";
+        let translated = vec!["你好".to_string(), "粗体".to_string(), "<世界>".to_string()];
+        assert_eq!(rebuild(html, &translated).unwrap(), "<p>你好 <b>粗体</b> &lt;世界&gt;</p>");
+        // 数量对不上时退回纯文本
+        assert_eq!(rebuild(html, &translated[..2]), None);
+    }
+
+    #[test]
+    fn test_runs_round_trip_through_one_request() {
+        let runs = vec!["Hello".to_string(), "bold".to_string(), "& more".to_string()];
+        let joined = join_runs(&runs);
+        assert_eq!(joined, "Hello\n|||\nbold\n|||\n& more");
+        assert_eq!(split_runs("你好\n|||\n粗体 ||| 还有", 3).unwrap(), vec!["你好", "粗体", "还有"]);
+        // 模型合并了片段时退回纯文本
+        assert_eq!(split_runs("你好 粗体\n|||\n还有", 3), None);
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(decode_entities("a&lt;b&gt; &#39;c&#x27; &copy; & d"), "a<b> 'c' &copy; & d");
+    }
+}
@@ -0,0 +1,148 @@
+//! At-rest encryption for API keys in config.json
+//! The master key lives in the OS keychain, the config file only holds ciphertext
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+#[cfg(not(test))]
+use once_cell::sync::Lazy;
+use tracing::warn;
+
+/// Marks an encrypted value, anything else is a plaintext key from an older config
+const PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+#[cfg(not(test))]
+const KEYRING_SERVICE: &str = "NanoTrans";
+#[cfg(not(test))]
+const KEYRING_USER: &str = "config-master-key";
+
+/// Read once per process, None when the keychain can't be used
+#[cfg(not(test))]
+static MASTER_KEY: Lazy<Option<Key>> = Lazy::new(load_or_create_master_key);
+
+#[cfg(not(test))]
+fn master_key() -> Option<&'static Key> {
+    MASTER_KEY.as_ref()
+}
+
+// 测试不碰系统钥匙串
+#[cfg(test)]
+fn master_key() -> Option<&'static Key> {
+    static TEST_KEY: [u8; 32] = [7; 32];
+    Some(Key::from_slice(&TEST_KEY))
+}
+
+#[cfg(not(test))]
+fn load_or_create_master_key() -> Option<Key> {
+    let entry = match keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER) {
+        Ok(entry) => entry,
+        Err(e) => {
+            warn!("无法访问系统钥匙串，API 密钥将以明文保存: {}", e);
+            return None;
+        }
+    };
+    match entry.get_password() {
+        Ok(stored) => {
+            let key = STANDARD.decode(stored.trim()).ok().filter(|bytes| bytes.len() == 32);
+            if key.is_none() {
+                warn!("钥匙串中的主密钥格式不对，API 密钥将以明文保存");
+            }
+            key.map(|bytes| *Key::from_slice(&bytes))
+        }
+        // 只有确实不存在时才新建，钥匙串暂时锁定等错误不能覆盖原来的主密钥
+        Err(keyring::Error::NoEntry) => {
+            let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+            match entry.set_password(&STANDARD.encode(key)) {
+                Ok(()) => Some(key),
+                Err(e) => {
+                    warn!("写入主密钥失败，API 密钥将以明文保存: {}", e);
+                    None
+                }
+            }
+        }
+        Err(e) => {
+            warn!("读取主密钥失败，API 密钥将以明文保存: {}", e);
+            None
+        }
+    }
+}
+
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(PREFIX)
+}
+
+/// Value to write into config.json; empty and already encrypted values pass through,
+/// without a usable keychain the plaintext is kept so the key isn't lost
+pub fn encrypt(plain: &str) -> String {
+    if plain.is_empty() || is_encrypted(plain) {
+        return plain.to_string();
+    }
+    let Some(key) = master_key() else { return plain.to_string(); };
+    let cipher = ChaCha20Poly1305::new(key);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    match cipher.encrypt(&nonce, plain.as_bytes()) {
+        Ok(ciphertext) => {
+            let mut payload = nonce.to_vec();
+            payload.extend_from_slice(&ciphertext);
+            format!("{}{}", PREFIX, STANDARD.encode(payload))
+        }
+        Err(_) => plain.to_string(),
+    }
+}
+
+/// Value read from config.json; plaintext passes through, anything that can't be decrypted becomes empty
+pub fn decrypt(stored: &str) -> String {
+    let Some(encoded) = stored.strip_prefix(PREFIX) else { return stored.to_string(); };
+    let Some(key) = master_key() else {
+        warn!("钥匙串中没有主密钥，已加密的 API 密钥按空处理");
+        return String::new();
+    };
+    let plain = STANDARD
+        .decode(encoded)
+        .ok()
+        .filter(|payload| payload.len() > NONCE_LEN)
+        .and_then(|payload| {
+            let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+            ChaCha20Poly1305::new(key).decrypt(Nonce::from_slice(nonce), ciphertext).ok()
+        })
+        .and_then(|bytes| String::from_utf8(bytes).ok());
+    plain.unwrap_or_else(|| {
+        warn!("API 密钥解密失败，按空处理");
+        String::new()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let stored = encrypt("sk-secret");
+        assert!(is_encrypted(&stored));
+        assert!(!stored.contains("sk-secret"));
+        assert_eq!(decrypt(&stored), "sk-secret");
+        // 每次加密用新的 nonce
+        assert_ne!(encrypt("sk-secret"), stored);
+    }
+
+    #[test]
+    fn test_plaintext_and_empty_pass_through() {
+        assert_eq!(encrypt(""), "");
+        assert_eq!(decrypt(""), "");
+        assert_eq!(decrypt("legacy-key"), "legacy-key");
+        let stored = encrypt("k");
+        assert_eq!(encrypt(&stored), stored);
+    }
+
+    #[test]
+    fn test_broken_ciphertext_is_empty() {
+        assert_eq!(decrypt("enc:v1:not base64!"), "");
+        assert_eq!(decrypt("enc:v1:AAAA"), "");
+        // 密文被改动时校验失败
+        let stored = encrypt("sk-secret");
+        let mut payload = STANDARD.decode(&stored[PREFIX.len()..]).unwrap();
+        *payload.last_mut().unwrap() ^= 1;
+        assert_eq!(decrypt(&format!("{}{}", PREFIX, STANDARD.encode(payload))), "");
+    }
+}
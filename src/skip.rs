@@ -0,0 +1,107 @@
+//! Selections that are not worth a request (numbers, emails, URLs...)
+//! Each pattern must match the whole trimmed selection, so users don't need `^...$`
+
+use regex::Regex;
+use tracing::warn;
+
+/// Defaults shipped in the config, users can edit or remove them
+pub fn default_skip_patterns() -> Vec<String> {
+    [
+        // 数字、金额、百分比
+        r"[+\-]?[\d.,\s]*\d[\d.,\s]*%?",
+        // 邮箱
+        r"[\w.+\-]+@[\w\-]+(\.[\w\-]+)+",
+        // 网址
+        r"(https?://|www\.)\S+",
+        // UUID
+        r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}",
+    ]
+    .iter()
+    .map(|p| p.to_string())
+    .collect()
+}
+
+/// Compiled patterns, invalid ones are reported and left out
+pub struct SkipMatcher {
+    patterns: Vec<Regex>,
+}
+
+impl SkipMatcher {
+    pub fn new(patterns: &[String]) -> (Self, Vec<String>) {
+        let mut compiled = Vec::new();
+        let mut errors = Vec::new();
+        for pattern in patterns.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            match Regex::new(&format!("^(?:{})$", pattern)) {
+                Ok(re) => compiled.push(re),
+                Err(e) => errors.push(format!("{}: {}", pattern, e)),
+            }
+        }
+        (Self { patterns: compiled }, errors)
+    }
+
+    /// Whether the whole selection matches one of the patterns
+    pub fn matches(&self, text: &str) -> bool {
+        let text = text.trim();
+        !text.is_empty() && self.patterns.iter().any(|re| re.is_match(text))
+    }
+}
+
+/// One-off check used at capture time
+pub fn should_skip(patterns: &[String], text: &str) -> bool {
+    let (matcher, errors) = SkipMatcher::new(patterns);
+    for error in errors {
+        warn!("跳过规则无效: {}", error);
+    }
+    matcher.matches(text)
+}
+
+/// One pattern per line for the settings text box
+pub fn parse_lines(text: &str) -> Vec<String> {
+    text.lines().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_patterns_match_whole_selection() {
+        let (matcher, errors) = SkipMatcher::new(&default_skip_patterns());
+        assert!(errors.is_empty());
+        for text in [
+            "42",
+            " 1,234.50 \n",
+            "-3.5%",
+            "someone@example.com",
+            "https://example.com/a?b=c",
+            "www.example.org",
+            "123e4567-e89b-12d3-a456-426614174000",
+        ] {
+            assert!(matcher.matches(text), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_mixed_text_is_translated() {
+        let (matcher, _) = SkipMatcher::new(&default_skip_patterns());
+        for text in [
+            "Version 42 is out",
+            "Contact someone@example.com for help",
+            "See https://example.com for details",
+            "hello",
+            "   ",
+        ] {
+            assert!(!matcher.matches(text), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_invalid_and_custom_patterns() {
+        let patterns = vec!["(unclosed".to_string(), r"#\d+".to_string(), " ".to_string()];
+        let (matcher, errors) = SkipMatcher::new(&patterns);
+        assert_eq!(errors.len(), 1);
+        assert!(matcher.matches("#1024"));
+        assert!(!matcher.matches("issue #1024"));
+        assert_eq!(parse_lines("a\n\n  b  \n"), vec!["a", "b"]);
+    }
+}
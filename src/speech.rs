@@ -0,0 +1,191 @@
+//! Read the translation aloud with the OS speech synthesizer
+//! macOS `say`, Windows System.Speech via PowerShell, Linux `espeak-ng`; text goes in through stdin
+
+use once_cell::sync::Lazy;
+use std::io::Write;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tracing::error;
+
+/// The running synthesizer process, `generation` bumps on every speak/stop so old threads back off
+#[derive(Default)]
+struct Playback {
+    generation: u64,
+    child: Option<Child>,
+}
+
+static PLAYBACK: Lazy<Mutex<Playback>> = Lazy::new(|| Mutex::new(Playback::default()));
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Speak `text` in a voice for `lang` (e.g. "ja", "zh-TW"), interrupting anything already playing.
+/// `on_done` runs on the worker thread once playback ends by itself, not after `stop`
+pub fn speak(text: String, lang: &str, on_done: impl FnOnce() + Send + 'static) {
+    let generation = {
+        let mut playback = PLAYBACK.lock().unwrap();
+        kill(&mut playback);
+        playback.generation
+    };
+    let mut command = synth_command(lang);
+    thread::spawn(move || {
+        let mut child = match command.stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null()).spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                error!("启动语音合成失败: {}", e);
+                if PLAYBACK.lock().unwrap().generation == generation {
+                    on_done();
+                }
+                return;
+            }
+        };
+        // 先关掉 stdin 再等，否则合成程序会一直等输入
+        if let Some(mut stdin) = child.stdin.take() {
+            if let Err(e) = stdin.write_all(text.as_bytes()) {
+                error!("写入朗读文本失败: {}", e);
+            }
+        }
+        {
+            let mut playback = PLAYBACK.lock().unwrap();
+            if playback.generation != generation {
+                // 写入期间已经被打断
+                let _ = child.kill();
+                let _ = child.wait();
+                return;
+            }
+            playback.child = Some(child);
+        }
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let mut playback = PLAYBACK.lock().unwrap();
+            if playback.generation != generation {
+                return;
+            }
+            let finished = match playback.child.as_mut().map(|c| c.try_wait()) {
+                Some(Ok(None)) => false,
+                Some(Ok(Some(status))) => {
+                    if !status.success() {
+                        error!("语音合成异常退出: {}", status);
+                    }
+                    true
+                }
+                Some(Err(e)) => {
+                    error!("等待语音合成失败: {}", e);
+                    true
+                }
+                None => true,
+            };
+            if finished {
+                playback.child = None;
+                drop(playback);
+                on_done();
+                return;
+            }
+        }
+    });
+}
+
+/// Stop playback, a no-op when nothing is playing
+pub fn stop() {
+    kill(&mut PLAYBACK.lock().unwrap());
+}
+
+fn kill(playback: &mut Playback) {
+    playback.generation += 1;
+    if let Some(mut child) = playback.child.take() {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+/// Primary language subtag, lowercased ("zh-TW" -> "zh")
+fn base_lang(lang: &str) -> String {
+    lang.split(['-', '_']).next().unwrap_or_default().trim().to_ascii_lowercase()
+}
+
+/// A voice that ships with macOS for the language, None keeps the system voice
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn macos_voice(lang: &str) -> Option<&'static str> {
+    let tag = lang.to_ascii_lowercase();
+    if tag == "zh-tw" || tag == "zh-hant" {
+        return Some("Meijia");
+    }
+    let voice = match base_lang(lang).as_str() {
+        "en" => "Samantha",
+        "zh" => "Tingting",
+        "ja" => "Kyoko",
+        "ko" => "Yuna",
+        "fr" => "Thomas",
+        "de" => "Anna",
+        "es" => "Monica",
+        "it" => "Alice",
+        "pt" => "Luciana",
+        "ru" => "Milena",
+        _ => return None,
+    };
+    Some(voice)
+}
+
+/// espeak-ng voice name, it calls Mandarin "cmn"
+#[cfg_attr(any(target_os = "macos", target_os = "windows"), allow(dead_code))]
+fn espeak_voice(lang: &str) -> String {
+    match base_lang(lang).as_str() {
+        "" => "en".to_string(),
+        "zh" => "cmn".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn synth_command(lang: &str) -> Command {
+    let mut command = Command::new("say");
+    if let Some(voice) = macos_voice(lang) {
+        command.args(["-v", voice]);
+    }
+    command
+}
+
+#[cfg(target_os = "windows")]
+fn synth_command(lang: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    // 只有装了对应语言的语音才切换，否则用系统默认语音
+    let script = format!(
+        "[Console]::InputEncoding = [Text.Encoding]::UTF8; \
+         Add-Type -AssemblyName System.Speech; \
+         $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+         $v = $s.GetInstalledVoices() | Where-Object {{ $_.Enabled -and $_.VoiceInfo.Culture.TwoLetterISOLanguageName -eq '{}' }} | Select-Object -First 1; \
+         if ($v) {{ $s.SelectVoice($v.VoiceInfo.Name) }}; \
+         $s.Speak([Console]::In.ReadToEnd())",
+        base_lang(lang)
+    );
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-NonInteractive", "-Command", &script]).creation_flags(CREATE_NO_WINDOW);
+    command
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn synth_command(lang: &str) -> Command {
+    let mut command = Command::new("espeak-ng");
+    command.args(["-v", &espeak_voice(lang), "--stdin"]);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_voice_for_lang() {
+        assert_eq!(base_lang("zh-TW"), "zh");
+        assert_eq!(base_lang("pt_BR"), "pt");
+        assert_eq!(macos_voice("ja"), Some("Kyoko"));
+        assert_eq!(macos_voice("zh-CN"), Some("Tingting"));
+        assert_eq!(macos_voice("zh-TW"), Some("Meijia"));
+        assert_eq!(macos_voice("xx"), None);
+        assert_eq!(espeak_voice("zh-CN"), "cmn");
+        assert_eq!(espeak_voice("DE"), "de");
+        assert_eq!(espeak_voice(""), "en");
+    }
+}
@@ -0,0 +1,157 @@
+//! Anthropic messages API
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::provider::{ProviderFuture, TranslationProvider};
+use super::{build_translation_prompts, prepend_examples, read_answer, resolve_max_tokens, resolve_temperature, retry};
+use super::{TranslateRequest, TranslateResponse, Translator};
+use crate::config::ProviderConfig;
+use crate::usage::{self, UsageEstimate};
+
+/// `max_tokens` is mandatory for Anthropic, used when the preset doesn't set one
+pub const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+pub struct Anthropic;
+
+impl TranslationProvider for Anthropic {
+    fn translate<'a>(
+        &'a self,
+        ctx: &'a Translator,
+        provider: &'a ProviderConfig,
+        request: &'a TranslateRequest,
+    ) -> ProviderFuture<'a> {
+        Box::pin(translate(ctx, provider, request))
+    }
+}
+
+/// `{base}/v1/messages`; a base that already ends in `/v1` or the full endpoint is not doubled up
+fn messages_url(provider: &ProviderConfig) -> String {
+    let base = provider.api_base.trim().trim_end_matches('/');
+    let base = base.strip_suffix("/messages").unwrap_or(base);
+    let base = base.strip_suffix("/v1").unwrap_or(base);
+    format!("{}/v1/messages", base)
+}
+
+/// Messages endpoint with auth and version headers
+fn messages_request(ctx: &Translator, provider: &ProviderConfig) -> reqwest::RequestBuilder {
+    ctx.client
+        .post(messages_url(provider))
+        .header("x-api-key", &provider.api_key)
+        .header("anthropic-version", provider.anthropic_version())
+        .header("Content-Type", "application/json")
+}
+
+/// Anthropic API translation
+async fn translate(ctx: &Translator, provider: &ProviderConfig, request: &TranslateRequest) -> Result<TranslateResponse> {
+    if provider.api_key.is_empty() {
+        anyhow::bail!("Anthropic API key not configured");
+    }
+
+    #[derive(Serialize)]
+    struct AnthropicRequest {
+        model: String,
+        max_tokens: u32,
+        system: String,
+        messages: Vec<AnthropicMessage>,
+        temperature: f32,
+    }
+
+    #[derive(Serialize)]
+    struct AnthropicMessage {
+        role: String,
+        content: String,
+    }
+
+    #[derive(Deserialize)]
+    struct AnthropicResponse {
+        content: Vec<AnthropicContent>,
+        #[serde(default)]
+        usage: Option<AnthropicUsage>,
+    }
+
+    #[derive(Deserialize)]
+    struct AnthropicUsage {
+        input_tokens: u32,
+        output_tokens: u32,
+    }
+
+    #[derive(Deserialize)]
+    struct AnthropicContent {
+        text: String,
+    }
+
+    let (system_prompt, user_prompt) = build_translation_prompts(&ctx.config, request);
+    let user_prompt = prepend_examples(&ctx.config, user_prompt);
+    let input_tokens = usage::estimate_tokens(&system_prompt) + usage::estimate_tokens(&user_prompt);
+
+    let anthropic_req = AnthropicRequest {
+        model: provider.model.clone(),
+        max_tokens: resolve_max_tokens(&ctx.config).unwrap_or(DEFAULT_MAX_TOKENS),
+        system: system_prompt,
+        messages: vec![AnthropicMessage { role: "user".to_string(), content: user_prompt }],
+        temperature: resolve_temperature(&ctx.config).min(1.0),
+    };
+
+    let http_request = messages_request(ctx, provider)
+        .json(&anthropic_req);
+    let response = ctx.send(provider, http_request).await?;
+    let (response, raw_response) = ctx.read_json::<AnthropicResponse>(provider, response).await?;
+
+    let reported = response.usage;
+    let translation = response.content.into_iter().next()
+        .ok_or_else(|| retry::empty_result(&provider.name))?
+        .text;
+
+    let usage = match reported {
+        Some(u) => UsageEstimate::reported(provider, u.input_tokens, u.output_tokens),
+        None => UsageEstimate::for_provider(provider, input_tokens, usage::estimate_tokens(&translation)),
+    };
+    let (translated_text, note) = read_answer(&ctx.config, provider, &request.text, &translation);
+    Ok(TranslateResponse { translated_text, usage: Some(usage), raw_response, note, ..Default::default() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_anthropic_version_header() {
+        let config = Config::default();
+        let translator = Translator::new(config.clone());
+        let mut provider = config.get_provider("anthropic").unwrap().clone();
+
+        let header = |p: &ProviderConfig| {
+            let req = messages_request(&translator, p).build().unwrap();
+            req.headers()["anthropic-version"].to_str().unwrap().to_string()
+        };
+
+        assert_eq!(header(&provider), crate::config::DEFAULT_ANTHROPIC_VERSION);
+
+        provider.anthropic_version = Some("2024-10-22".to_string());
+        assert_eq!(header(&provider), "2024-10-22");
+
+        // 格式不对时回退默认值
+        provider.anthropic_version = Some("latest".to_string());
+        assert_eq!(header(&provider), crate::config::DEFAULT_ANTHROPIC_VERSION);
+    }
+
+    #[test]
+    fn test_messages_url_base_variants() {
+        let mut provider = Config::default().get_provider("anthropic").unwrap().clone();
+        for base in [
+            "https://api.anthropic.com",
+            "https://api.anthropic.com/",
+            "https://api.anthropic.com/v1",
+            " https://api.anthropic.com/v1/ ",
+            "https://api.anthropic.com/v1/messages",
+        ] {
+            provider.api_base = base.to_string();
+            assert_eq!(messages_url(&provider), "https://api.anthropic.com/v1/messages", "base {:?}", base);
+        }
+        // 代理路径里的其他部分保持不变
+        provider.api_base = "https://proxy.example.com/anthropic/v1".to_string();
+        assert_eq!(messages_url(&provider), "https://proxy.example.com/anthropic/v1/messages");
+    }
+}
@@ -0,0 +1,233 @@
+//! In-memory LRU cache of recent translations
+//! Lives in the app state so it survives across hotkey presses; capacity comes from the config
+//! After a long idle period the entries are moved to disk and read back on the next lookup
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::TranslateResponse;
+
+/// Cache handle shared by every `Translator` built from the app state
+pub type SharedCache = Arc<Mutex<TranslationCache>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheKey {
+    pub provider_id: String,
+    pub target_lang: String,
+    pub prompt_preset_id: String,
+    pub text: String,
+}
+
+/// What survives a trip to disk; usage and raw bodies are dropped
+#[derive(Serialize, Deserialize)]
+struct StoredEntry {
+    key: CacheKey,
+    translated_text: String,
+    provider: String,
+    source_lang: Option<String>,
+    target_lang: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+}
+
+/// Most recently used entry is at the back; capacity is small (~100) so a linear scan is fine
+#[derive(Debug, Default)]
+pub struct TranslationCache {
+    entries: VecDeque<(CacheKey, TranslateResponse)>,
+    /// Set while the entries are parked on disk
+    spilled: Option<PathBuf>,
+}
+
+impl TranslationCache {
+    pub fn shared() -> SharedCache {
+        Arc::new(Mutex::new(Self::default()))
+    }
+
+    pub fn get(&mut self, key: &CacheKey) -> Option<TranslateResponse> {
+        self.reload();
+        let pos = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(pos)?;
+        let response = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(response)
+    }
+
+    /// Drop everything, e.g. after the glossary changed
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        if let Some(path) = self.spilled.take() {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    /// A spill file left by a previous run (app quit while idle) is never read back, delete it
+    pub fn remove_stale_spill(path: &Path) {
+        for file in [path.to_path_buf(), path.with_extension("json.tmp")] {
+            match fs::remove_file(&file) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => warn!("删除残留的缓存文件失败({}): {}", file.display(), e),
+            }
+        }
+    }
+
+    /// Write the entries to `path` and release their memory, `get`/`put` load them back
+    pub fn spill_to(&mut self, path: &Path) -> Result<()> {
+        if self.entries.is_empty() {
+            self.entries.shrink_to_fit();
+            return Ok(());
+        }
+        let stored: Vec<StoredEntry> = self
+            .entries
+            .iter()
+            .map(|(key, response)| StoredEntry {
+                key: key.clone(),
+                translated_text: response.translated_text.clone(),
+                provider: response.provider.clone(),
+                source_lang: response.source_lang.clone(),
+                target_lang: response.target_lang.clone(),
+                note: response.note.clone(),
+            })
+            .collect();
+        // 先写临时文件再改名，中途退出不会留下半个文件
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(&stored)?)?;
+        fs::rename(&tmp, path)?;
+        self.entries = VecDeque::new();
+        self.spilled = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Bring spilled entries back into memory, a missing or broken file just means an empty cache
+    fn reload(&mut self) {
+        let Some(path) = self.spilled.take() else { return; };
+        let stored: Vec<StoredEntry> = fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        let _ = fs::remove_file(&path);
+        self.entries = stored
+            .into_iter()
+            .map(|e| {
+                let response = TranslateResponse {
+                    translated_text: e.translated_text,
+                    provider: e.provider,
+                    source_lang: e.source_lang,
+                    target_lang: e.target_lang,
+                    note: e.note,
+                    ..Default::default()
+                };
+                (e.key, response)
+            })
+            .collect();
+    }
+
+    /// Store a result, evicting the least recently used beyond `capacity` (0 stores nothing)
+    /// Usage is dropped, a cache hit costs nothing and must not count again
+    pub fn put(&mut self, key: CacheKey, response: TranslateResponse, capacity: usize) {
+        self.reload();
+        if let Some(pos) = self.entries.iter().position(|(k, _)| *k == key) {
+            self.entries.remove(pos);
+        }
+        if capacity == 0 || response.translated_text.trim().is_empty() {
+            return;
+        }
+        self.entries.push_back((key, TranslateResponse { usage: None, ..response }));
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage::UsageEstimate;
+
+    fn key(text: &str) -> CacheKey {
+        CacheKey {
+            provider_id: "openai".to_string(),
+            target_lang: "zh".to_string(),
+            prompt_preset_id: "default".to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    fn response(text: &str) -> TranslateResponse {
+        TranslateResponse { translated_text: text.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let mut cache = TranslationCache::default();
+        cache.put(key("a"), response("甲"), 2);
+        cache.put(key("b"), response("乙"), 2);
+        // 读取 a 之后，b 变成最久未使用
+        assert_eq!(cache.get(&key("a")).unwrap().translated_text, "甲");
+        cache.put(key("c"), response("丙"), 2);
+
+        assert_eq!(cache.entries.len(), 2);
+        assert!(cache.get(&key("b")).is_none());
+        assert!(cache.get(&key("a")).is_some());
+        assert!(cache.get(&key("c")).is_some());
+    }
+
+    #[test]
+    fn test_cache_key_and_empty_results() {
+        let mut cache = TranslationCache::default();
+        cache.put(key("a"), response("   "), 10);
+        assert!(cache.entries.is_empty());
+
+        cache.put(key("a"), response("甲"), 10);
+        let other_lang = CacheKey { target_lang: "ja".to_string(), ..key("a") };
+        assert!(cache.get(&other_lang).is_none());
+
+        // 容量为 0 即关闭
+        cache.put(key("b"), response("乙"), 0);
+        assert!(cache.get(&key("b")).is_none());
+
+        // 命中缓存不再计入用量
+        let usage = UsageEstimate { input_tokens: 10, output_tokens: 5, cost: None, exact: true };
+        cache.put(key("c"), TranslateResponse { usage: Some(usage), ..response("丙") }, 10);
+        assert!(cache.get(&key("c")).unwrap().usage.is_none());
+    }
+
+    #[test]
+    fn test_spill_and_reload() {
+        let path = std::env::temp_dir().join(format!("nanotrans-cache-test-{}.json", std::process::id()));
+        let mut cache = TranslationCache::default();
+        cache.put(key("a"), TranslateResponse { provider: "Google".to_string(), ..response("甲") }, 10);
+        cache.put(key("b"), response("乙"), 10);
+
+        cache.spill_to(&path).unwrap();
+        assert!(cache.entries.is_empty());
+        assert!(path.exists());
+
+        let restored = cache.get(&key("a")).unwrap();
+        assert_eq!(restored.translated_text, "甲");
+        assert_eq!(restored.provider, "Google");
+        assert_eq!(cache.entries.len(), 2);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_stale_spill_is_removed() {
+        let path = std::env::temp_dir().join(format!("nanotrans-stale-cache-test-{}.json", std::process::id()));
+        let mut cache = TranslationCache::default();
+        cache.put(key("a"), response("甲"), 10);
+        cache.spill_to(&path).unwrap();
+        assert!(!path.with_extension("json.tmp").exists());
+
+        // 新进程不知道这个文件，启动时直接删掉
+        TranslationCache::remove_stale_spill(&path);
+        assert!(!path.exists());
+        // 文件不存在时什么都不做
+        TranslationCache::remove_stale_spill(&path);
+    }
+}
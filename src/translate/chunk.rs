@@ -0,0 +1,223 @@
+//! Splitting long input for providers with a request size limit
+//! Paragraph breaks are tried first, then sentence ends, then plain character boundaries
+//! Every chunk keeps the whitespace that followed it so the translations can be re-joined as-is
+
+/// One piece of the input and the separator that followed it in the original text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    pub text: String,
+    pub separator: String,
+}
+
+/// Sentence terminators, the full-width ones end a sentence even without a following space
+const SENTENCE_ENDS: [char; 9] = ['.', '!', '?', ';', '。', '！', '？', '；', '…'];
+/// Closing quotes/brackets that stay with the sentence they close
+const CLOSERS: [char; 8] = ['"', '\'', ')', ']', '”', '’', '）', '」'];
+
+/// Split `text` so every chunk's URL-encoded length is at most `max_encoded`
+pub fn split(text: &str, max_encoded: usize) -> Vec<Chunk> {
+    split_by(text, max_encoded, encoded_len)
+}
+
+/// Split `text` so `measure` of every chunk is at most `max`
+/// Concatenating `text + separator` of all chunks gives back the original input
+pub fn split_by(text: &str, max: usize, measure: fn(&str) -> usize) -> Vec<Chunk> {
+    let fits = |s: &str| measure(s) <= max;
+    if fits(text) {
+        return vec![Chunk { text: text.to_string(), separator: String::new() }];
+    }
+
+    let mut units: Vec<(&str, &str)> = Vec::new();
+    for (paragraph, paragraph_sep) in split_paragraphs(text) {
+        if fits(paragraph) {
+            units.push((paragraph, paragraph_sep));
+            continue;
+        }
+        let sentences = split_sentences(paragraph);
+        let last = sentences.len() - 1;
+        for (i, (sentence, sentence_sep)) in sentences.into_iter().enumerate() {
+            // 段落自己的分隔符挂在最后一句后面
+            let sep = if i == last { paragraph_sep } else { sentence_sep };
+            if fits(sentence) {
+                units.push((sentence, sep));
+            } else {
+                let pieces = hard_split(sentence, max, measure);
+                let last_piece = pieces.len() - 1;
+                for (j, (piece, piece_sep)) in pieces.into_iter().enumerate() {
+                    units.push((piece, if j == last_piece { sep } else { piece_sep }));
+                }
+            }
+        }
+    }
+
+    // 小段落合并回去，减少请求次数
+    let mut chunks: Vec<Chunk> = Vec::new();
+    for (unit, sep) in units {
+        if let Some(current) = chunks.last_mut() {
+            let merged = format!("{}{}{}", current.text, current.separator, unit);
+            if fits(&merged) {
+                current.text = merged;
+                current.separator = sep.to_string();
+                continue;
+            }
+        }
+        chunks.push(Chunk { text: unit.to_string(), separator: sep.to_string() });
+    }
+    chunks
+}
+
+/// Put translated chunks back together with the original separators
+pub fn join(chunks: &[Chunk], translated: &[String]) -> String {
+    chunks
+        .iter()
+        .zip(translated)
+        .map(|(chunk, text)| format!("{}{}", text.trim_end(), chunk.separator))
+        .collect()
+}
+
+fn encoded_len(text: &str) -> usize {
+    urlencoding::encode(text).len()
+}
+
+pub fn char_len(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Paragraphs separated by whitespace that contains a blank line
+fn split_paragraphs(text: &str) -> Vec<(&str, &str)> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if !c.is_whitespace() {
+            continue;
+        }
+        let mut end = i + c.len_utf8();
+        while let Some(&(j, d)) = chars.peek() {
+            if !d.is_whitespace() {
+                break;
+            }
+            end = j + d.len_utf8();
+            chars.next();
+        }
+        if text[i..end].matches('\n').count() >= 2 {
+            pieces.push((&text[start..i], &text[i..end]));
+            start = end;
+        }
+    }
+    pieces.push((&text[start..], ""));
+    pieces
+}
+
+/// Sentences with the whitespace after them as separator
+fn split_sentences(text: &str) -> Vec<(&str, &str)> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if !SENTENCE_ENDS.contains(&c) {
+            continue;
+        }
+        let mut sentence_end = i + c.len_utf8();
+        while let Some(&(j, d)) = chars.peek() {
+            if !CLOSERS.contains(&d) && !SENTENCE_ENDS.contains(&d) {
+                break;
+            }
+            sentence_end = j + d.len_utf8();
+            chars.next();
+        }
+        let mut end = sentence_end;
+        while let Some(&(j, d)) = chars.peek() {
+            if !d.is_whitespace() {
+                break;
+            }
+            end = j + d.len_utf8();
+            chars.next();
+        }
+        // 半角标点后面要有空白才算句末，避免拆开 3.14 或 example.com
+        let full_width = !c.is_ascii();
+        if (end > sentence_end || full_width) && end < text.len() {
+            pieces.push((&text[start..sentence_end], &text[sentence_end..end]));
+            start = end;
+        }
+    }
+    pieces.push((&text[start..], ""));
+    pieces
+}
+
+/// Last resort for a single huge sentence: cut at char boundaries, preferably at a space
+fn hard_split(text: &str, max: usize, measure: fn(&str) -> usize) -> Vec<(&str, &str)> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut len = 0;
+    let mut last_space: Option<(usize, usize)> = None;
+    for (i, c) in text.char_indices() {
+        let width = measure(c.encode_utf8(&mut [0; 4]));
+        if len + width > max && i > start {
+            match last_space.take() {
+                Some((space_start, space_end)) if space_start > start => {
+                    pieces.push((&text[start..space_start], &text[space_start..space_end]));
+                    start = space_end;
+                    len = measure(&text[start..i]);
+                }
+                _ => {
+                    pieces.push((&text[start..i], ""));
+                    start = i;
+                    len = 0;
+                }
+            }
+        }
+        len += width;
+        if c == ' ' {
+            last_space = Some((i, i + 1));
+        }
+    }
+    pieces.push((&text[start..], ""));
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rebuild(chunks: &[Chunk]) -> String {
+        chunks.iter().map(|c| format!("{}{}", c.text, c.separator)).collect()
+    }
+
+    #[test]
+    fn test_short_text_is_one_chunk() {
+        let chunks = split("Hello world.\n\nSecond paragraph.", 5000);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].separator, "");
+    }
+
+    #[test]
+    fn test_split_on_paragraphs_and_sentences_keeps_separators() {
+        let text = "First sentence here. Second sentence here.\n\n\nAnother paragraph.\n\n第一句。第二句！";
+        let chunks = split(text, 30);
+        assert_eq!(rebuild(&chunks), text);
+        assert!(chunks.iter().all(|c| encoded_len(&c.text) <= 30));
+        assert_eq!(chunks[0].text, "First sentence here.");
+        assert_eq!(chunks[0].separator, " ");
+        assert_eq!(chunks[1].separator, "\n\n\n");
+
+        let translated: Vec<String> = chunks.iter().map(|c| format!("[{}]", c.text)).collect();
+        let joined = join(&chunks, &translated);
+        assert!(joined.starts_with("[First sentence here.] [Second sentence here.]\n\n\n"));
+        assert!(joined.contains("]\n\n["));
+    }
+
+    #[test]
+    fn test_hard_split_respects_utf8_and_order() {
+        let text = "翻译".repeat(40);
+        let chunks = split(&text, 50);
+        assert!(chunks.len() > 1);
+        assert_eq!(rebuild(&chunks), text);
+        assert!(chunks.iter().all(|c| encoded_len(&c.text) <= 50));
+
+        let words = "lorem ipsum ".repeat(20);
+        let chunks = split(words.trim_end(), 40);
+        assert_eq!(rebuild(&chunks), words.trim_end());
+        assert!(chunks.iter().all(|c| !c.text.starts_with(' ') && !c.text.ends_with(' ')));
+    }
+}
@@ -0,0 +1,120 @@
+//! DeepL API, free or pro host picked from the key
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::provider::{ProviderFuture, TranslationProvider};
+use super::{retry, TranslateRequest, TranslateResponse, Translator};
+use crate::config::{DeepLFormality, ProviderConfig};
+
+/// Target languages with a formal register; others reject `more`/`less`
+const FORMALITY_LANGS: [&str; 9] = ["de", "fr", "it", "es", "nl", "pl", "pt", "ja", "ru"];
+
+pub struct DeepL;
+
+impl TranslationProvider for DeepL {
+    fn translate<'a>(
+        &'a self,
+        ctx: &'a Translator,
+        provider: &'a ProviderConfig,
+        request: &'a TranslateRequest,
+    ) -> ProviderFuture<'a> {
+        Box::pin(translate(ctx, provider, request))
+    }
+}
+
+#[derive(Serialize)]
+struct DeepLRequest {
+    text: Vec<String>,
+    target_lang: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_lang: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    formality: Option<DeepLFormality>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    preserve_formatting: Option<bool>,
+}
+
+/// Whether DeepL accepts `formality` for this target, e.g. `de` or `pt-BR`
+fn supports_formality(target_lang: &str) -> bool {
+    let base = target_lang.split(['-', '_']).next().unwrap_or_default().to_lowercase();
+    FORMALITY_LANGS.contains(&base.as_str())
+}
+
+fn deepl_request(provider: &ProviderConfig, request: &TranslateRequest) -> DeepLRequest {
+    DeepLRequest {
+        text: vec![request.text.clone()],
+        target_lang: request.target_lang.to_uppercase(),
+        source_lang: request.source_lang.clone().map(|s| s.to_uppercase()),
+        // 不支持的语言直接不发，免得整个请求报错
+        formality: provider.deepl_formality.filter(|_| supports_formality(&request.target_lang)),
+        preserve_formatting: provider.deepl_preserve_formatting,
+    }
+}
+
+/// DeepL translation
+async fn translate(ctx: &Translator, provider: &ProviderConfig, request: &TranslateRequest) -> Result<TranslateResponse> {
+    if provider.api_key.is_empty() {
+        anyhow::bail!("DeepL API key not configured");
+    }
+
+    #[derive(Deserialize)]
+    struct DeepLResponse {
+        translations: Vec<DeepLTranslation>,
+    }
+
+    #[derive(Deserialize)]
+    struct DeepLTranslation {
+        text: String,
+        #[serde(default)]
+        detected_source_language: Option<String>,
+    }
+
+    let deepl_req = deepl_request(provider, request);
+
+    let url = format!("{}/translate", provider.deepl_api_base());
+
+    let http_request = ctx.client
+        .post(&url)
+        .header("Authorization", format!("DeepL-Auth-Key {}", provider.api_key))
+        .json(&deepl_req);
+    let response = ctx.send(provider, http_request).await?;
+    let (response, raw_response) = ctx.read_json::<DeepLResponse>(provider, response).await?;
+
+    let translation = response.translations.into_iter().next()
+        .ok_or_else(|| retry::empty_result(&provider.name))?;
+
+    Ok(TranslateResponse {
+        translated_text: translation.text,
+        usage: None,
+        raw_response,
+        detected_source_lang: translation.detected_source_language.map(|lang| lang.to_lowercase()),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_formality_only_for_supported_targets() {
+        let mut provider = Config::default().get_provider("deepl").unwrap().clone();
+        let request = |target: &str| TranslateRequest { text: "hi".to_string(), source_lang: None, target_lang: target.to_string() };
+        let body = |p: &ProviderConfig, target: &str| serde_json::to_value(deepl_request(p, &request(target))).unwrap();
+
+        // 没设置时请求体不变
+        let plain = body(&provider, "de");
+        assert!(plain.get("formality").is_none());
+        assert!(plain.get("preserve_formatting").is_none());
+
+        provider.deepl_formality = Some(DeepLFormality::PreferMore);
+        provider.deepl_preserve_formatting = Some(true);
+        let german = body(&provider, "de");
+        assert_eq!(german["formality"], "prefer_more");
+        assert_eq!(german["preserve_formatting"], true);
+        assert_eq!(body(&provider, "pt-BR")["formality"], "prefer_more");
+        assert!(body(&provider, "zh").get("formality").is_none());
+    }
+}
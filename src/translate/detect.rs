@@ -0,0 +1,104 @@
+//! Language detection for picking the target language in auto mode
+//! Uses whatlang's trigram/script model, short or mixed selections fall back to a character heuristic
+
+use whatlang::Lang;
+
+use super::has_cjk;
+
+/// Short language code for a detected language, None for languages without a mapping here
+fn short_code(lang: Lang) -> Option<&'static str> {
+    Some(match lang {
+        Lang::Eng => "en",
+        Lang::Cmn => "zh",
+        Lang::Jpn => "ja",
+        Lang::Kor => "ko",
+        Lang::Fra => "fr",
+        Lang::Deu => "de",
+        Lang::Spa => "es",
+        Lang::Rus => "ru",
+        Lang::Por => "pt",
+        Lang::Ita => "it",
+        Lang::Ara => "ar",
+        Lang::Tha => "th",
+        Lang::Vie => "vi",
+        Lang::Nld => "nl",
+        Lang::Pol => "pl",
+        Lang::Tur => "tr",
+        Lang::Ukr => "uk",
+        Lang::Ind => "id",
+        Lang::Hin => "hi",
+        Lang::Swe => "sv",
+        _ => return None,
+    })
+}
+
+/// Detected language when whatlang is confident about it
+fn detect_reliable(text: &str) -> Option<&'static str> {
+    let info = whatlang::detect(text)?;
+    if !info.is_reliable() {
+        return None;
+    }
+    short_code(info.lang())
+}
+
+/// Previous heuristic: kana means Japanese, other CJK ideographs Chinese, everything else English
+fn guess_by_chars(text: &str) -> &'static str {
+    if text.chars().any(|c| matches!(c, '\u{3040}'..='\u{309F}' | '\u{30A0}'..='\u{30FF}')) {
+        "ja"
+    } else if text.chars().any(|c| matches!(c, '\u{AC00}'..='\u{D7AF}' | '\u{1100}'..='\u{11FF}')) {
+        "ko"
+    } else if has_cjk(text) {
+        "zh"
+    } else {
+        "en"
+    }
+}
+
+/// Language of `text`, never fails: low-confidence detections use the character heuristic
+pub fn detect_language(text: &str) -> &'static str {
+    detect_reliable(text).unwrap_or_else(|| guess_by_chars(text))
+}
+
+/// Same language ignoring region/script subtags, so `zh-CN` matches `zh`
+pub fn same_language(a: &str, b: &str) -> bool {
+    let primary = |code: &str| code.split(['-', '_']).next().unwrap_or_default().to_lowercase();
+    primary(a) == primary(b)
+}
+
+/// Configured target, or the secondary language when the text is already in the target language
+pub fn pick_target(detected: &str, target: &str, secondary: &str) -> String {
+    if same_language(detected, target) {
+        secondary.to_string()
+    } else {
+        target.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_common_languages() {
+        assert_eq!(detect_language("The quick brown fox jumps over the lazy dog and keeps running."), "en");
+        assert_eq!(detect_language("Je voudrais réserver une table pour deux personnes ce soir, s'il vous plaît."), "fr");
+        assert_eq!(detect_language("今日はとても良い天気ですね。散歩に行きましょう。"), "ja");
+        assert_eq!(detect_language("오늘 날씨가 정말 좋네요. 산책하러 갈까요?"), "ko");
+        assert_eq!(detect_language("今天天气很好，我们一起去公园散步吧。"), "zh");
+    }
+
+    #[test]
+    fn test_short_text_falls_back_to_heuristic() {
+        assert_eq!(detect_language("你好"), "zh");
+        assert_eq!(detect_language("はい"), "ja");
+        assert_eq!(detect_language("ok"), "en");
+    }
+
+    #[test]
+    fn test_pick_target() {
+        assert_eq!(pick_target("ja", "zh", "en"), "zh");
+        assert_eq!(pick_target("fr", "zh", "en"), "zh");
+        assert_eq!(pick_target("zh", "zh-CN", "en"), "en");
+        assert_eq!(pick_target("en", "en", "zh"), "zh");
+    }
+}
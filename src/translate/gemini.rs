@@ -0,0 +1,130 @@
+//! Google Gemini generateContent API
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::provider::{ProviderFuture, TranslationProvider};
+use super::{build_translation_prompts, json_answer_field, prepend_examples, read_answer, resolve_max_tokens, resolve_temperature, retry};
+use super::{TranslateRequest, TranslateResponse, Translator};
+use crate::config::ProviderConfig;
+use crate::usage::{self, UsageEstimate};
+
+pub struct Gemini;
+
+impl TranslationProvider for Gemini {
+    fn translate<'a>(
+        &'a self,
+        ctx: &'a Translator,
+        provider: &'a ProviderConfig,
+        request: &'a TranslateRequest,
+    ) -> ProviderFuture<'a> {
+        Box::pin(translate(ctx, provider, request))
+    }
+}
+
+/// Google Gemini generateContent endpoint
+/// The key goes in the `x-goog-api-key` header so it never shows up in the URL or transport errors
+fn url(provider: &ProviderConfig) -> String {
+    format!("{}/models/{}:generateContent", provider.api_base.trim_end_matches('/'), provider.model.trim())
+}
+
+/// Google Gemini API translation
+async fn translate(ctx: &Translator, provider: &ProviderConfig, request: &TranslateRequest) -> Result<TranslateResponse> {
+    if provider.api_key.is_empty() {
+        anyhow::bail!("Gemini API key not configured");
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct GeminiRequest {
+        system_instruction: GeminiContent,
+        contents: Vec<GeminiContent>,
+        generation_config: GeminiGenerationConfig,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct GeminiContent {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        role: Option<String>,
+        #[serde(default)]
+        parts: Vec<GeminiPart>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct GeminiPart {
+        #[serde(default)]
+        text: String,
+    }
+
+    #[derive(Serialize)]
+    struct GeminiGenerationConfig {
+        temperature: f32,
+        #[serde(rename = "maxOutputTokens", skip_serializing_if = "Option::is_none")]
+        max_output_tokens: Option<u32>,
+        #[serde(rename = "responseMimeType", skip_serializing_if = "Option::is_none")]
+        response_mime_type: Option<&'static str>,
+    }
+
+    #[derive(Deserialize)]
+    struct GeminiResponse {
+        #[serde(default)]
+        candidates: Vec<GeminiCandidate>,
+    }
+
+    #[derive(Deserialize)]
+    struct GeminiCandidate {
+        content: GeminiContent,
+    }
+
+    let (system_prompt, user_prompt) = build_translation_prompts(&ctx.config, request);
+    let user_prompt = prepend_examples(&ctx.config, user_prompt);
+    let input_tokens = usage::estimate_tokens(&system_prompt) + usage::estimate_tokens(&user_prompt);
+
+    let gemini_req = GeminiRequest {
+        system_instruction: GeminiContent { role: None, parts: vec![GeminiPart { text: system_prompt }] },
+        contents: vec![GeminiContent {
+            role: Some("user".to_string()),
+            parts: vec![GeminiPart { text: user_prompt }],
+        }],
+        generation_config: GeminiGenerationConfig {
+            temperature: resolve_temperature(&ctx.config),
+            max_output_tokens: resolve_max_tokens(&ctx.config),
+            response_mime_type: json_answer_field(&ctx.config).map(|_| "application/json"),
+        },
+    };
+
+    let http_request = ctx.client
+        .post(url(provider))
+        .header("Content-Type", "application/json")
+        .header("x-goog-api-key", &provider.api_key)
+        .json(&gemini_req);
+    let response = ctx.send(provider, http_request).await?;
+    let (response, raw_response) = ctx.read_json::<GeminiResponse>(provider, response).await?;
+
+    let translation = response.candidates.into_iter().next()
+        .and_then(|c| c.content.parts.into_iter().next())
+        .ok_or_else(|| retry::empty_result(&provider.name))?
+        .text;
+
+    let usage = UsageEstimate::for_provider(provider, input_tokens, usage::estimate_tokens(&translation));
+    let (translated_text, note) = read_answer(&ctx.config, provider, &request.text, &translation);
+    Ok(TranslateResponse { translated_text, usage: Some(usage), raw_response, note, ..Default::default() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_gemini_url() {
+        let config = Config::default();
+        let mut provider = config.get_provider("gemini").unwrap().clone();
+        provider.api_key = "AIza+key/1".to_string();
+        assert_eq!(
+            url(&provider),
+            "https://generativelanguage.googleapis.com/v1beta/models/gemini-1.5-flash:generateContent"
+        );
+        assert!(!url(&provider).contains("AIza"));
+    }
+}
@@ -0,0 +1,176 @@
+//! Google Translate, free `translate_a/single` endpoint without an API key
+
+use anyhow::Result;
+
+use super::provider::{ProviderFuture, TranslationProvider};
+use super::{chunk, is_cjk_char, retry, TranslateRequest, TranslateResponse, Translator};
+use crate::config::ProviderConfig;
+
+/// Longest URL-encoded `q` the free endpoint handles without truncating
+const MAX_QUERY_LEN: usize = 5000;
+
+pub struct Google;
+
+impl TranslationProvider for Google {
+    fn translate<'a>(
+        &'a self,
+        ctx: &'a Translator,
+        provider: &'a ProviderConfig,
+        request: &'a TranslateRequest,
+    ) -> ProviderFuture<'a> {
+        Box::pin(translate(ctx, provider, request))
+    }
+}
+
+/// Google Translate (free, no API key needed)
+async fn translate(ctx: &Translator, provider: &ProviderConfig, request: &TranslateRequest) -> Result<TranslateResponse> {
+    let source = request.source_lang.as_deref().unwrap_or("auto");
+    // 免费接口会悄悄截断过长的 q，长文本按段落/句子分块逐个翻译
+    let chunks = chunk::split(&request.text, MAX_QUERY_LEN);
+    let mut translated = Vec::with_capacity(chunks.len());
+    let mut raw = Vec::new();
+    let mut detected = None;
+    for piece in &chunks {
+        if piece.text.trim().is_empty() {
+            translated.push(piece.text.clone());
+            continue;
+        }
+        let (parsed, response) = request_once(ctx, provider, source, &request.target_lang, &piece.text).await?;
+        translated.push(join_segments(&parsed, ctx.config.google_smart_join));
+        // 以第一块识别出的语言为准
+        detected = detected.or_else(|| detected_language(&parsed));
+        raw.push(response);
+    }
+
+    let translated_text = chunk::join(&chunks, &translated);
+    if translated_text.trim().is_empty() {
+        return Err(retry::empty_result(&provider.name));
+    }
+
+    let raw_response = ctx.capture_raw(&raw.join("\n"), "");
+    Ok(TranslateResponse { translated_text, usage: None, raw_response, detected_source_lang: detected, ..Default::default() })
+}
+
+/// One call to the free Google endpoint, returns the parsed JSON and the raw body
+async fn request_once(ctx: &Translator, provider: &ProviderConfig, source: &str, target: &str, text: &str) -> Result<(serde_json::Value, String)> {
+    let url = format!(
+        "https://translate.googleapis.com/translate_a/single?client=gtx&sl={}&tl={}&dt=t&q={}",
+        source, target, urlencoding::encode(text)
+    );
+
+    let http_request = ctx.client
+        .get(&url)
+        .header("User-Agent", "Mozilla/5.0");
+    let response = ctx.send(provider, http_request).await?
+        .text()
+        .await?;
+
+    let parsed: serde_json::Value = serde_json::from_str(&response)
+        .map_err(|e| anyhow::anyhow!("Failed to parse Google response: {}", e))?;
+
+    Ok((parsed, response))
+}
+
+/// Source language Google detected, the third element of the response
+fn detected_language(parsed: &serde_json::Value) -> Option<String> {
+    parsed
+        .get(2)
+        .and_then(|v| v.as_str())
+        .map(|code| code.trim().to_lowercase())
+        .filter(|code| !code.is_empty())
+}
+
+/// Join the `[translated, original, ...]` segments of a Google response.
+/// Each segment is a sentence; in smart mode line breaks from the original are kept
+/// and a space is added where Google dropped it, so sentences don't run together.
+fn join_segments(parsed: &serde_json::Value, smart: bool) -> String {
+    let Some(segments) = parsed.get(0).and_then(|v| v.as_array()) else {
+        return String::new();
+    };
+    let parts: Vec<(&str, &str)> = segments
+        .iter()
+        .filter_map(|item| {
+            let translated = item.get(0).and_then(|v| v.as_str())?;
+            let original = item.get(1).and_then(|v| v.as_str()).unwrap_or_default();
+            Some((translated, original))
+        })
+        .collect();
+
+    let mut out = String::new();
+    for (idx, (translated, original)) in parts.iter().enumerate() {
+        out.push_str(translated);
+        let Some((next, _)) = parts.get(idx + 1) else { break; };
+        if smart {
+            out.push_str(&segment_separator(translated, original, next));
+        }
+    }
+    out
+}
+
+fn segment_separator(translated: &str, original: &str, next: &str) -> String {
+    if translated.ends_with(char::is_whitespace) || next.starts_with(char::is_whitespace) {
+        return String::new();
+    }
+    let boundary = &original[original.trim_end().len()..];
+    if boundary.contains('\n') {
+        // 换行只保留换行本身，去掉行尾空格
+        return boundary.chars().filter(|c| *c == '\n').collect();
+    }
+    // Google 按句子切分，中日文句子之间不需要空格，其余语言补一个空格
+    let prev_cjk = translated.chars().last().is_some_and(is_cjk_char);
+    let next_cjk = next.chars().next().is_some_and(is_cjk_char);
+    if prev_cjk || next_cjk {
+        String::new()
+    } else {
+        " ".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_google_segments_keeps_sentence_breaks() {
+        let parsed = serde_json::json!([
+            [
+                ["Hello world.", "你好世界。", null, null, 10],
+                ["This is a test.", "这是一个测试。\n", null, null, 10],
+                ["Bye.", "再见。", null, null, 10]
+            ],
+            null,
+            "zh-CN"
+        ]);
+        assert_eq!(join_segments(&parsed, true), "Hello world. This is a test.\nBye.");
+        assert_eq!(join_segments(&parsed, false), "Hello world.This is a test.Bye.");
+    }
+
+    #[test]
+    fn test_detected_language_from_response() {
+        let parsed = serde_json::json!([[["Hello", "你好", null, null, 10]], null, "zh-CN"]);
+        assert_eq!(detected_language(&parsed).as_deref(), Some("zh-cn"));
+        assert_eq!(detected_language(&serde_json::json!([[["Hello", "你好"]]])), None);
+    }
+
+    #[test]
+    fn test_join_google_segments_keeps_existing_whitespace() {
+        let parsed = serde_json::json!([
+            [
+                ["Hello world. ", "你好世界。", null, null, 10],
+                ["Bye.", "再见。", null, null, 10]
+            ]
+        ]);
+        assert_eq!(join_segments(&parsed, true), "Hello world. Bye.");
+    }
+
+    #[test]
+    fn test_join_google_segments_no_space_between_cjk() {
+        let parsed = serde_json::json!([
+            [
+                ["你好。", "Hello. ", null, null, 10],
+                ["世界。", "World.", null, null, 10]
+            ]
+        ]);
+        assert_eq!(join_segments(&parsed, true), "你好。世界。");
+    }
+}
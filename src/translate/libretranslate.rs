@@ -0,0 +1,112 @@
+//! LibreTranslate, usually a self-hosted server, the key is optional
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::provider::{ProviderFuture, TranslationProvider};
+use super::{retry, TranslateRequest, TranslateResponse, Translator};
+use crate::config::ProviderConfig;
+
+pub struct LibreTranslate;
+
+impl TranslationProvider for LibreTranslate {
+    fn translate<'a>(
+        &'a self,
+        ctx: &'a Translator,
+        provider: &'a ProviderConfig,
+        request: &'a TranslateRequest,
+    ) -> ProviderFuture<'a> {
+        Box::pin(translate(ctx, provider, request))
+    }
+}
+
+#[derive(Serialize)]
+struct LibreRequest {
+    q: String,
+    source: String,
+    target: String,
+    format: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key: Option<String>,
+}
+
+/// LibreTranslate codes are mostly bare ISO 639-1, traditional Chinese is `zt`
+fn libre_lang(code: &str) -> String {
+    match code.to_lowercase().as_str() {
+        "zh-tw" | "zh-hk" | "zh-hant" => "zt".to_string(),
+        lower => lower.split(['-', '_']).next().unwrap_or_default().to_string(),
+    }
+}
+
+fn libre_request(provider: &ProviderConfig, request: &TranslateRequest) -> LibreRequest {
+    LibreRequest {
+        q: request.text.clone(),
+        // 自动检测时 source_lang 为 None
+        source: request.source_lang.as_deref().map(libre_lang).unwrap_or_else(|| "auto".to_string()),
+        target: libre_lang(&request.target_lang),
+        format: "text",
+        api_key: Some(provider.api_key.trim().to_string()).filter(|k| !k.is_empty()),
+    }
+}
+
+/// LibreTranslate translation
+async fn translate(ctx: &Translator, provider: &ProviderConfig, request: &TranslateRequest) -> Result<TranslateResponse> {
+    #[derive(Deserialize)]
+    struct LibreResponse {
+        #[serde(rename = "translatedText")]
+        translated_text: String,
+        #[serde(default, rename = "detectedLanguage")]
+        detected_language: Option<LibreDetected>,
+    }
+
+    #[derive(Deserialize)]
+    struct LibreDetected {
+        language: String,
+    }
+
+    let base = provider.api_base.trim().trim_end_matches('/');
+    if base.is_empty() {
+        anyhow::bail!("{} server address not configured", provider.name);
+    }
+    let http_request = ctx.client
+        .post(format!("{}/translate", base))
+        .json(&libre_request(provider, request));
+    let response = ctx.send(provider, http_request).await?;
+    let (response, raw_response) = ctx.read_json::<LibreResponse>(provider, response).await?;
+
+    if response.translated_text.trim().is_empty() {
+        return Err(retry::empty_result(&provider.name));
+    }
+    Ok(TranslateResponse {
+        translated_text: response.translated_text,
+        usage: None,
+        raw_response,
+        detected_source_lang: response.detected_language.map(|d| d.language.to_lowercase()),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_request_body() {
+        let mut provider = Config::default().get_provider("libretranslate").unwrap().clone();
+        let auto = TranslateRequest { text: "hello".to_string(), source_lang: None, target_lang: "zh-TW".to_string() };
+        let body = serde_json::to_value(libre_request(&provider, &auto)).unwrap();
+        assert_eq!(body["q"], "hello");
+        assert_eq!(body["source"], "auto");
+        assert_eq!(body["target"], "zt");
+        assert_eq!(body["format"], "text");
+        assert!(body.get("api_key").is_none());
+
+        provider.api_key = "local-key".to_string();
+        let fixed = TranslateRequest { source_lang: Some("en".to_string()), target_lang: "zh-CN".to_string(), ..auto };
+        let body = serde_json::to_value(libre_request(&provider, &fixed)).unwrap();
+        assert_eq!(body["source"], "en");
+        assert_eq!(body["target"], "zh");
+        assert_eq!(body["api_key"], "local-key");
+    }
+}
@@ -0,0 +1,78 @@
+//! Guardrail against surprise bills: at most N requests in any rolling hour
+//! Lives in the app state like the cache, so every `Translator` counts against the same window
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Limiter handle shared by every `Translator` built from the app state
+pub type SharedRateLimiter = Arc<Mutex<RateLimiter>>;
+
+/// Length of the sliding window
+pub const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Send times of the requests inside the current window, oldest first
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    sent: VecDeque<Instant>,
+}
+
+impl RateLimiter {
+    pub fn shared() -> SharedRateLimiter {
+        Arc::new(Mutex::new(Self::default()))
+    }
+
+    /// Record a request at `now` when fewer than `limit` were sent in the last hour,
+    /// otherwise return how long until the oldest one leaves the window
+    pub fn try_acquire(&mut self, limit: u32, now: Instant) -> Result<(), Duration> {
+        while let Some(&oldest) = self.sent.front() {
+            if now.saturating_duration_since(oldest) < RATE_LIMIT_WINDOW {
+                break;
+            }
+            self.sent.pop_front();
+        }
+        if let Some(&oldest) = self.sent.front().filter(|_| self.sent.len() >= limit as usize) {
+            return Err(RATE_LIMIT_WINDOW.saturating_sub(now.saturating_duration_since(oldest)));
+        }
+        self.sent.push_back(now);
+        Ok(())
+    }
+}
+
+/// Returned instead of sending once the hour's budget is used up
+#[derive(Debug)]
+pub struct RateLimitExceeded {
+    pub limit: u32,
+    pub retry_in: Duration,
+}
+
+impl std::fmt::Display for RateLimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let minutes = self.retry_in.as_secs().div_ceil(60).max(1);
+        write!(f, "Request limit reached ({} per hour), try again in {} min", self.limit, minutes)
+    }
+}
+
+impl std::error::Error for RateLimitExceeded {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sliding_window() {
+        let mut limiter = RateLimiter::default();
+        let start = Instant::now();
+        assert!(limiter.try_acquire(2, start).is_ok());
+        assert!(limiter.try_acquire(2, start + Duration::from_secs(600)).is_ok());
+
+        // 第三次要等最早的那次移出窗口
+        let wait = limiter.try_acquire(2, start + Duration::from_secs(1200)).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(2400));
+
+        // 过了窗口边界只释放最早的一次
+        assert!(limiter.try_acquire(2, start + RATE_LIMIT_WINDOW).is_ok());
+        let wait = limiter.try_acquire(2, start + RATE_LIMIT_WINDOW + Duration::from_secs(1)).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(599));
+    }
+}
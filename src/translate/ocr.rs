@@ -0,0 +1,118 @@
+//! Text recognition for clipboard images through an OpenAI-compatible vision model
+
+use anyhow::Result;
+use base64::Engine;
+use serde::Deserialize;
+
+use super::{openai, retry, Translator};
+use crate::config::{Config, ProviderConfig, ProviderType};
+
+const OCR_PROMPT: &str = "Transcribe all text in this image exactly as written, keeping the line breaks. \
+Output only the text without any explanation. If the image contains no text, output nothing.";
+
+impl Translator {
+    /// Text in a PNG image, read by the provider set as `ocr_provider_id`
+    pub async fn recognize_image(&self, png: &[u8]) -> Result<String> {
+        #[derive(Deserialize)]
+        struct VisionResponse {
+            choices: Vec<VisionChoice>,
+        }
+
+        #[derive(Deserialize)]
+        struct VisionChoice {
+            message: VisionMessage,
+        }
+
+        #[derive(Deserialize)]
+        struct VisionMessage {
+            content: String,
+        }
+
+        let provider = ocr_provider(&self.config)?;
+        let response = self.send(provider, self.vision_request(provider, png)).await?;
+        let (response, _) = self.read_json::<VisionResponse>(provider, response).await?;
+
+        let text = response.choices.into_iter().next()
+            .ok_or_else(|| retry::empty_result(&provider.name))?
+            .message.content;
+        Ok(text.trim().to_string())
+    }
+
+    /// Chat completion request carrying the image, with the same auth headers as translation
+    fn vision_request(&self, provider: &ProviderConfig, png: &[u8]) -> reqwest::RequestBuilder {
+        let builder = self.client
+            .post(openai::chat_url(provider))
+            .header("Authorization", format!("Bearer {}", provider.api_key))
+            .header("Content-Type", "application/json")
+            .json(&vision_body(&provider.model, png));
+        openai::with_org_headers(builder, provider)
+    }
+}
+
+/// Only OpenAI-compatible chat APIs take image parts, the model itself must support vision
+fn ocr_provider(config: &Config) -> Result<&ProviderConfig> {
+    let provider = config
+        .get_provider(&config.ocr_provider_id)
+        .ok_or_else(|| anyhow::anyhow!("OCR provider {} not found", config.ocr_provider_id))?;
+    if provider.provider_type != ProviderType::OpenAI {
+        anyhow::bail!("{} cannot read images, pick an OpenAI-compatible provider with a vision model", provider.name);
+    }
+    if provider.api_key.is_empty() {
+        anyhow::bail!("{} API key not configured", provider.name);
+    }
+    Ok(provider)
+}
+
+fn vision_body(model: &str, png: &[u8]) -> serde_json::Value {
+    let data_url = format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(png));
+    serde_json::json!({
+        "model": model,
+        "temperature": 0.0,
+        "messages": [{
+            "role": "user",
+            "content": [
+                { "type": "text", "text": OCR_PROMPT },
+                { "type": "image_url", "image_url": { "url": data_url } },
+            ],
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vision_body_embeds_png() {
+        let body = vision_body("gpt-4o-mini", &[0x89, b'P', b'N', b'G']);
+        let content = &body["messages"][0]["content"];
+        assert_eq!(content[0]["type"], "text");
+        assert_eq!(content[1]["image_url"]["url"], "data:image/png;base64,iVBORw==");
+        assert_eq!(body["model"], "gpt-4o-mini");
+    }
+
+    #[test]
+    fn test_ocr_needs_openai_compatible_provider() {
+        let mut config = Config::default();
+        config.get_provider_mut("openai").unwrap().api_key = "sk-test".to_string();
+        assert_eq!(ocr_provider(&config).unwrap().id, "openai");
+
+        config.ocr_provider_id = "google".to_string();
+        assert!(ocr_provider(&config).unwrap_err().to_string().contains("cannot read images"));
+
+        config.ocr_provider_id = "missing".to_string();
+        assert!(ocr_provider(&config).is_err());
+    }
+
+    #[test]
+    fn test_vision_request_sends_org_headers() {
+        let mut config = Config::default();
+        let provider = config.get_provider_mut("openai").unwrap();
+        provider.api_key = "sk-test".to_string();
+        provider.org_id = Some("org-1".to_string());
+        let translator = Translator::new(config.clone());
+        let request = translator.vision_request(config.get_provider("openai").unwrap(), b"png").build().unwrap();
+        assert_eq!(request.headers()["OpenAI-Organization"], "org-1");
+        assert!(request.headers().get("OpenAI-Project").is_none());
+    }
+}
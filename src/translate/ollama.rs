@@ -0,0 +1,96 @@
+//! Local Ollama chat API
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::provider::{ProviderFuture, TranslationProvider};
+use super::{build_example_turns, build_translation_prompts, json_answer_field, read_answer, resolve_max_tokens, resolve_temperature};
+use super::{TranslateRequest, TranslateResponse, Translator};
+use crate::config::ProviderConfig;
+use crate::usage::{self, UsageEstimate};
+
+pub struct Ollama;
+
+impl TranslationProvider for Ollama {
+    fn translate<'a>(
+        &'a self,
+        ctx: &'a Translator,
+        provider: &'a ProviderConfig,
+        request: &'a TranslateRequest,
+    ) -> ProviderFuture<'a> {
+        Box::pin(translate(ctx, provider, request))
+    }
+}
+
+/// Local Ollama chat API, no auth header and its own response shape
+async fn translate(ctx: &Translator, provider: &ProviderConfig, request: &TranslateRequest) -> Result<TranslateResponse> {
+    if provider.model.trim().is_empty() {
+        anyhow::bail!("{} model not configured", provider.name);
+    }
+
+    #[derive(Serialize)]
+    struct OllamaRequest {
+        model: String,
+        messages: Vec<OllamaMessage>,
+        stream: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        format: Option<&'static str>,
+        options: OllamaOptions,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct OllamaMessage {
+        role: String,
+        content: String,
+    }
+
+    #[derive(Serialize)]
+    struct OllamaOptions {
+        temperature: f32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        num_predict: Option<u32>,
+    }
+
+    #[derive(Deserialize)]
+    struct OllamaResponse {
+        message: OllamaMessage,
+    }
+
+    let (system_prompt, user_prompt) = build_translation_prompts(&ctx.config, request);
+    // 示例作为之前的对话轮次发送
+    let examples = build_example_turns(&ctx.config, request);
+    let input_tokens = usage::estimate_tokens(&system_prompt)
+        + usage::estimate_tokens(&user_prompt)
+        + examples.iter().map(|(user, answer)| usage::estimate_tokens(user) + usage::estimate_tokens(answer)).sum::<u32>();
+    let mut messages = vec![OllamaMessage { role: "system".to_string(), content: system_prompt }];
+    for (user, answer) in examples {
+        messages.push(OllamaMessage { role: "user".to_string(), content: user });
+        messages.push(OllamaMessage { role: "assistant".to_string(), content: answer });
+    }
+    messages.push(OllamaMessage { role: "user".to_string(), content: user_prompt });
+
+    let ollama_req = OllamaRequest {
+        model: provider.model.trim().to_string(),
+        messages,
+        stream: false,
+        format: json_answer_field(&ctx.config).map(|_| "json"),
+        options: OllamaOptions {
+            temperature: resolve_temperature(&ctx.config),
+            num_predict: resolve_max_tokens(&ctx.config),
+        },
+    };
+
+    let url = format!("{}/api/chat", provider.api_base.trim_end_matches('/'));
+
+    let http_request = ctx.client
+        .post(&url)
+        .header("Content-Type", "application/json")
+        .json(&ollama_req);
+    let response = ctx.send(provider, http_request).await?;
+    let (response, raw_response) = ctx.read_json::<OllamaResponse>(provider, response).await?;
+
+    let answer = response.message.content;
+    let usage = UsageEstimate::for_provider(provider, input_tokens, usage::estimate_tokens(&answer));
+    let (translated_text, note) = read_answer(&ctx.config, provider, &request.text, &answer);
+    Ok(TranslateResponse { translated_text, usage: Some(usage), raw_response, note, ..Default::default() })
+}
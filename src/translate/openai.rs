@@ -0,0 +1,425 @@
+//! OpenAI-compatible chat completions, plain and streamed
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::provider::{ProviderFuture, TranslationProvider};
+use super::{build_example_turns, build_translation_prompts, json_answer_field, read_answer, resolve_max_tokens, resolve_temperature, retry, sse};
+use super::{TranslateRequest, TranslateResponse, Translator};
+use crate::config::{ProviderConfig, ProviderType};
+use crate::postprocess::unwrap_code_fence;
+use crate::usage::{self, UsageEstimate};
+
+pub struct OpenAI;
+
+impl TranslationProvider for OpenAI {
+    fn translate<'a>(
+        &'a self,
+        ctx: &'a Translator,
+        provider: &'a ProviderConfig,
+        request: &'a TranslateRequest,
+    ) -> ProviderFuture<'a> {
+        Box::pin(translate(ctx, provider, request))
+    }
+}
+
+/// OpenAI-compatible API translation
+async fn translate(ctx: &Translator, provider: &ProviderConfig, request: &TranslateRequest) -> Result<TranslateResponse> {
+    #[derive(Deserialize)]
+    struct OpenAIResponse {
+        choices: Vec<OpenAIChoice>,
+        #[serde(default)]
+        usage: Option<OpenAIUsage>,
+    }
+
+    #[derive(Deserialize)]
+    struct OpenAIChoice {
+        message: OpenAIMessageResponse,
+    }
+
+    #[derive(Deserialize)]
+    struct OpenAIMessageResponse {
+        content: String,
+    }
+
+    let (builder, input_tokens) = chat_request(ctx, provider, request, false)?;
+    let response = ctx.send(provider, builder).await?;
+    let (response, raw_response) = ctx.read_json::<OpenAIResponse>(provider, response).await?;
+
+    let reported = response.usage;
+    let translation = response.choices.into_iter().next()
+        .ok_or_else(|| retry::empty_result(&provider.name))?
+        .message.content;
+
+    let usage = match reported {
+        Some(u) => UsageEstimate::reported(provider, u.prompt_tokens, u.completion_tokens),
+        None => UsageEstimate::for_provider(provider, input_tokens, usage::estimate_tokens(&translation)),
+    };
+    let (translated_text, note) = read_answer(&ctx.config, provider, &request.text, &translation);
+    Ok(TranslateResponse { translated_text, usage: Some(usage), raw_response, note, ..Default::default() })
+}
+
+/// `usage` object of a chat completion, missing on some compatible servers
+#[derive(Deserialize)]
+struct OpenAIUsage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+}
+
+/// Streamed variant of `translate`, `on_partial` gets the text received so far
+pub(super) async fn translate_stream<F>(
+    ctx: &Translator,
+    provider: &ProviderConfig,
+    request: &TranslateRequest,
+    on_partial: &mut F,
+) -> Result<TranslateResponse>
+where
+    F: FnMut(&str) + Send,
+{
+    let (builder, input_tokens) = chat_request(ctx, provider, request, true)?;
+    // 超时按整个请求计算，流式输出要等模型写完，用更宽松的上限
+    // 只在收到响应前重试，流开始后的错误直接报告
+    let mut response = ctx.send_with_timeout(provider, builder, provider.stream_timeout()).await?;
+
+    let mut buffer = sse::SseBuffer::default();
+    let mut translation = String::new();
+    let mut reported = None;
+    let mut raw_lines = Vec::new();
+    'read: while let Some(chunk) = response.chunk().await? {
+        for data in buffer.push(&chunk) {
+            if ctx.config.show_raw_response {
+                raw_lines.push(format!("data: {}", data));
+            }
+            match sse::parse_openai_chunk(&data)? {
+                Some(sse::StreamEvent::Delta(delta)) => {
+                    translation.push_str(&delta);
+                    on_partial(&translation);
+                }
+                // 部分服务在最后一个分块里带上用量
+                Some(sse::StreamEvent::Usage(input, output)) => reported = Some((input, output)),
+                Some(sse::StreamEvent::Done) => break 'read,
+                None => {}
+            }
+        }
+    }
+
+    if translation.trim().is_empty() {
+        return Err(retry::empty_result(&provider.name));
+    }
+
+    let raw_response = ctx.capture_raw(&raw_lines.join("\n"), &provider.api_key);
+    let translated_text = unwrap_code_fence(&request.text, &translation);
+    let usage = match reported {
+        Some((input, output)) => UsageEstimate::reported(provider, input, output),
+        None => UsageEstimate::for_provider(provider, input_tokens, usage::estimate_tokens(&translated_text)),
+    };
+    Ok(TranslateResponse { translated_text, usage: Some(usage), raw_response, ..Default::default() })
+}
+
+/// `GET /models` response, only the ids are used
+#[derive(Deserialize)]
+struct ModelList {
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Deserialize)]
+struct ModelEntry {
+    id: String,
+}
+
+/// Model ids from `{api_base}/models`, sorted for the settings dropdown
+pub(super) async fn list_models(ctx: &Translator, provider: &ProviderConfig) -> Result<Vec<String>> {
+    if provider.api_key.is_empty() {
+        anyhow::bail!("{} API key not configured", provider.name);
+    }
+    let url = format!("{}/models", api_base(provider));
+    let builder = ctx.client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", provider.api_key));
+    let builder = with_org_headers(builder, provider);
+    let response = ctx.send(provider, builder).await?;
+    let (list, _) = ctx.read_json::<ModelList>(provider, response).await?;
+    let ids = model_ids(list);
+    if ids.is_empty() {
+        anyhow::bail!("{} returned no models", provider.name);
+    }
+    Ok(ids)
+}
+
+fn model_ids(list: ModelList) -> Vec<String> {
+    let mut ids: Vec<String> = list.data.into_iter()
+        .map(|m| m.id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// Chat completions request shared by the plain and streamed paths, plus the prompt token estimate
+fn chat_request(
+    ctx: &Translator,
+    provider: &ProviderConfig,
+    request: &TranslateRequest,
+    stream: bool,
+) -> Result<(reqwest::RequestBuilder, u32)> {
+    if provider.api_key.is_empty() {
+        anyhow::bail!("{} API key not configured", provider.name);
+    }
+    // json_object 模式要求提示词里出现 JSON，否则接口直接返回 400
+    if let Some(preset) = ctx.config.active_prompt_preset().filter(|p| p.response_is_json && !p.mentions_json()) {
+        anyhow::bail!("Prompt preset \"{}\" asks for a JSON answer but its prompts never mention JSON", preset.name);
+    }
+
+    #[derive(Serialize)]
+    struct OpenAIRequest {
+        model: String,
+        messages: Vec<OpenAIMessage>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        temperature: Option<f32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_tokens: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_completion_tokens: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        response_format: Option<ResponseFormat>,
+        stream: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        stream_options: Option<StreamOptions>,
+    }
+
+    #[derive(Serialize)]
+    struct ResponseFormat {
+        #[serde(rename = "type")]
+        kind: &'static str,
+    }
+
+    // 流式输出默认不带用量，要显式要求在最后一个分块里返回
+    #[derive(Serialize)]
+    struct StreamOptions {
+        include_usage: bool,
+    }
+
+    #[derive(Serialize)]
+    struct OpenAIMessage {
+        role: String,
+        content: String,
+    }
+
+    let (system_prompt, user_prompt) = build_translation_prompts(&ctx.config, request);
+    // 示例作为之前的对话轮次发送
+    let examples = build_example_turns(&ctx.config, request);
+    let input_tokens = usage::estimate_tokens(&system_prompt)
+        + usage::estimate_tokens(&user_prompt)
+        + examples.iter().map(|(user, answer)| usage::estimate_tokens(user) + usage::estimate_tokens(answer)).sum::<u32>();
+    let mut messages = vec![OpenAIMessage { role: "system".to_string(), content: system_prompt }];
+    for (user, answer) in examples {
+        messages.push(OpenAIMessage { role: "user".to_string(), content: user });
+        messages.push(OpenAIMessage { role: "assistant".to_string(), content: answer });
+    }
+    messages.push(OpenAIMessage { role: "user".to_string(), content: user_prompt });
+
+    // 推理模型不接受 temperature，输出上限也换了字段名
+    let reasoning = provider.is_reasoning_model();
+    let max_tokens = resolve_max_tokens(&ctx.config);
+    let openai_req = OpenAIRequest {
+        model: provider.model.clone(),
+        messages,
+        temperature: (!reasoning).then(|| resolve_temperature(&ctx.config)),
+        max_tokens: max_tokens.filter(|_| !reasoning),
+        max_completion_tokens: max_tokens.filter(|_| reasoning),
+        response_format: json_answer_field(&ctx.config).map(|_| ResponseFormat { kind: "json_object" }),
+        stream,
+        stream_options: stream.then_some(StreamOptions { include_usage: true }),
+    };
+
+    let builder = ctx.client.post(chat_url(provider));
+    let builder = if provider.provider_type == ProviderType::AzureOpenAI {
+        builder.header("api-key", &provider.api_key)
+    } else {
+        with_org_headers(builder.header("Authorization", format!("Bearer {}", provider.api_key)), provider)
+    };
+    let builder = builder
+        .header("Content-Type", "application/json")
+        .json(&openai_req);
+    Ok((builder, input_tokens))
+}
+
+/// Organization/project headers, only when configured
+pub(super) fn with_org_headers(mut builder: reqwest::RequestBuilder, provider: &ProviderConfig) -> reqwest::RequestBuilder {
+    let headers = [("OpenAI-Organization", &provider.org_id), ("OpenAI-Project", &provider.project_id)];
+    for (name, value) in headers {
+        if let Some(value) = value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+            builder = builder.header(name, value);
+        }
+    }
+    builder
+}
+
+/// Configured base without a trailing slash or a pasted `/chat/completions`
+/// A `/v1` is kept, compatible servers put their version there (or elsewhere, e.g. `/api/paas/v4`)
+pub(super) fn api_base(provider: &ProviderConfig) -> &str {
+    let base = provider.api_base.trim().trim_end_matches('/');
+    base.strip_suffix("/chat/completions").unwrap_or(base).trim_end_matches('/')
+}
+
+/// Chat completions endpoint; Azure puts the deployment (`model`) in the path and the version in the query
+pub(super) fn chat_url(provider: &ProviderConfig) -> String {
+    let base = api_base(provider);
+    if provider.provider_type == ProviderType::AzureOpenAI {
+        format!(
+            "{}/openai/deployments/{}/chat/completions?api-version={}",
+            base,
+            urlencoding::encode(provider.model.trim()),
+            provider.azure_api_version()
+        )
+    } else {
+        format!("{}/chat/completions", base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_azure_chat_url_and_auth() {
+        let mut provider = Config::default().next_custom_provider();
+        provider.provider_type = ProviderType::AzureOpenAI;
+        provider.api_base = "https://contoso.openai.azure.com/".to_string();
+        provider.model = "gpt-4o-mini".to_string();
+        provider.api_key = "azure-key".to_string();
+        assert_eq!(
+            chat_url(&provider),
+            "https://contoso.openai.azure.com/openai/deployments/gpt-4o-mini/chat/completions?api-version=2024-10-21"
+        );
+        provider.azure_api_version = Some("2025-01-01-preview".to_string());
+        assert!(chat_url(&provider).ends_with("?api-version=2025-01-01-preview"));
+
+        let translator = Translator::new(Config::default());
+        let request = TranslateRequest { text: "hi".to_string(), source_lang: None, target_lang: "zh".to_string() };
+        let (builder, _) = chat_request(&translator, &provider, &request, false).unwrap();
+        let req = builder.build().unwrap();
+        assert_eq!(req.headers()["api-key"], "azure-key");
+        assert!(req.headers().get("Authorization").is_none());
+
+        // 普通 OpenAI 兼容服务不变
+        provider.provider_type = ProviderType::OpenAI;
+        assert_eq!(chat_url(&provider), "https://contoso.openai.azure.com/chat/completions");
+    }
+
+    #[test]
+    fn test_chat_url_base_variants() {
+        let mut provider = Config::default().get_provider("openai").unwrap().clone();
+        for base in [
+            "https://api.openai.com/v1",
+            "https://api.openai.com/v1/",
+            "https://api.openai.com/v1/chat/completions",
+            " https://api.openai.com/v1/chat/completions/ ",
+        ] {
+            provider.api_base = base.to_string();
+            assert_eq!(chat_url(&provider), "https://api.openai.com/v1/chat/completions", "base {:?}", base);
+        }
+        // 没有 /v1 的兼容服务不会被补上
+        provider.api_base = "https://open.bigmodel.cn/api/paas/v4".to_string();
+        assert_eq!(chat_url(&provider), "https://open.bigmodel.cn/api/paas/v4/chat/completions");
+    }
+
+    #[test]
+    fn test_reasoning_model_request_fields() {
+        let mut config = Config::default();
+        config.active_prompt_preset_id = config.prompt_presets[0].id.clone();
+        config.prompt_presets[0].max_tokens = Some(512);
+        let translator = Translator::new(config.clone());
+        let mut provider = config.get_provider("openai").unwrap().clone();
+        provider.api_key = "sk-test".to_string();
+        let request = TranslateRequest { text: "hi".to_string(), source_lang: None, target_lang: "zh".to_string() };
+
+        let body = |p: &ProviderConfig| {
+            let (builder, _) = chat_request(&translator, p, &request, false).unwrap();
+            let req = builder.build().unwrap();
+            serde_json::from_slice::<serde_json::Value>(req.body().unwrap().as_bytes().unwrap()).unwrap()
+        };
+
+        provider.model = "gpt-4o-mini".to_string();
+        let chat = body(&provider);
+        assert!((chat["temperature"].as_f64().unwrap() - 0.3).abs() < 1e-6);
+        assert_eq!(chat["max_tokens"], 512);
+        assert!(chat.get("max_completion_tokens").is_none());
+
+        provider.model = "o3-mini".to_string();
+        let reasoning = body(&provider);
+        assert!(reasoning.get("temperature").is_none());
+        assert!(reasoning.get("max_tokens").is_none());
+        assert_eq!(reasoning["max_completion_tokens"], 512);
+
+        // 配置里可以强制关闭
+        provider.reasoning_model = Some(false);
+        assert!(body(&provider).get("temperature").is_some());
+        assert!(chat.get("response_format").is_none());
+        assert!(chat.get("stream_options").is_none());
+
+        let (builder, _) = chat_request(&translator, &provider, &request, true).unwrap();
+        let req = builder.build().unwrap();
+        let streamed: serde_json::Value = serde_json::from_slice(req.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert_eq!(streamed["stream_options"]["include_usage"], true);
+    }
+
+    #[test]
+    fn test_json_preset_requests_json_object() {
+        let config = Config { active_prompt_preset_id: "explain".to_string(), ..Default::default() };
+        let translator = Translator::new(config.clone());
+        let mut provider = config.get_provider("openai").unwrap().clone();
+        provider.api_key = "sk-test".to_string();
+        let request = TranslateRequest { text: "ça va".to_string(), source_lang: None, target_lang: "zh".to_string() };
+        let (builder, _) = chat_request(&translator, &provider, &request, false).unwrap();
+        let req = builder.build().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(req.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert_eq!(body["response_format"]["type"], "json_object");
+
+        // 提示词里没有 JSON 字样时接口会拒绝，提前报错
+        let mut config = config;
+        let preset = config.get_prompt_preset_mut("explain").unwrap();
+        preset.system_template = "Translate into {{target_lang_name}}.".to_string();
+        preset.user_template = "{{text}}".to_string();
+        let translator = Translator::new(config);
+        let err = chat_request(&translator, &provider, &request, false).err().unwrap();
+        assert!(err.to_string().contains("never mention JSON"));
+    }
+
+    #[test]
+    fn test_org_and_project_headers() {
+        let config = Config::default();
+        let translator = Translator::new(config.clone());
+        let mut provider = config.get_provider("openai").unwrap().clone();
+        provider.api_key = "sk-test".to_string();
+        let request = TranslateRequest { text: "hi".to_string(), source_lang: None, target_lang: "zh".to_string() };
+        let headers = |p: &ProviderConfig| {
+            let (builder, _) = chat_request(&translator, p, &request, false).unwrap();
+            builder.build().unwrap().headers().clone()
+        };
+
+        let plain = headers(&provider);
+        assert!(plain.get("OpenAI-Organization").is_none());
+        assert!(plain.get("OpenAI-Project").is_none());
+
+        provider.org_id = Some("org-123".to_string());
+        provider.project_id = Some(" ".to_string());
+        let with_org = headers(&provider);
+        assert_eq!(with_org["OpenAI-Organization"], "org-123");
+        assert!(with_org.get("OpenAI-Project").is_none());
+    }
+
+    #[test]
+    fn test_model_ids_sorted_and_deduped() {
+        let body = r#"{"object":"list","data":[
+            {"id":"gpt-4o-mini","object":"model","owned_by":"system"},
+            {"id":"gpt-4o","object":"model"},
+            {"id":" "},
+            {"id":"gpt-4o"}
+        ]}"#;
+        let list: ModelList = serde_json::from_str(body).unwrap();
+        assert_eq!(model_ids(list), vec!["gpt-4o", "gpt-4o-mini"]);
+    }
+}
@@ -0,0 +1,39 @@
+//! One trait for every translation backend
+//! Each provider type lives in its own submodule; adding one means a new file plus a line in `for_type`
+
+use std::future::Future;
+use std::pin::Pin;
+
+use anyhow::Result;
+
+use super::{anthropic, deepl, gemini, google, libretranslate, ollama, openai};
+use super::{TranslateRequest, TranslateResponse, Translator};
+use crate::config::{ProviderConfig, ProviderType};
+
+pub type ProviderFuture<'a> = Pin<Box<dyn Future<Output = Result<TranslateResponse>> + Send + 'a>>;
+
+/// A translation backend
+/// `ctx` carries the shared HTTP client, the config (prompts, temperature) and the retry policy
+pub trait TranslationProvider: Send + Sync {
+    fn translate<'a>(
+        &'a self,
+        ctx: &'a Translator,
+        provider: &'a ProviderConfig,
+        request: &'a TranslateRequest,
+    ) -> ProviderFuture<'a>;
+}
+
+/// Backend implementing `kind`
+pub fn for_type(kind: &ProviderType) -> &'static dyn TranslationProvider {
+    match kind {
+        ProviderType::Google => &google::Google,
+        ProviderType::DeepL => &deepl::DeepL,
+        ProviderType::OpenAI => &openai::OpenAI,
+        ProviderType::Anthropic => &anthropic::Anthropic,
+        ProviderType::Gemini => &gemini::Gemini,
+        ProviderType::Ollama => &ollama::Ollama,
+        ProviderType::LibreTranslate => &libretranslate::LibreTranslate,
+        // 请求体与 OpenAI 相同，只有地址和鉴权头不同
+        ProviderType::AzureOpenAI => &openai::OpenAI,
+    }
+}
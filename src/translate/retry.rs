@@ -0,0 +1,251 @@
+//! Retry policy for transient provider failures
+//! Timeouts, connection errors and HTTP 429/500/502/503 are retried with exponential backoff,
+//! everything else (401, 400, parse errors...) fails right away
+//! An empty translation gets one more attempt on its own, separate from the HTTP retries
+
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use tracing::warn;
+
+/// Upper bound for a single wait, also caps large `Retry-After` values
+pub const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Longest provider message kept in the error, the rest of the body is dropped
+const MAX_ERROR_MESSAGE_CHARS: usize = 300;
+
+/// Non-success HTTP status from a provider
+#[derive(Debug)]
+pub struct HttpStatusError {
+    pub status: StatusCode,
+    pub retry_after: Option<Duration>,
+    /// Provider's own explanation taken from the body, if any
+    pub message: Option<String>,
+}
+
+impl fmt::Display for HttpStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.message {
+            Some(message) => write!(f, "HTTP {}: {}", self.status, message),
+            None => write!(f, "HTTP {}", self.status),
+        }
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
+
+/// Provider answered successfully but without any translated text
+#[derive(Debug)]
+pub struct EmptyResultError {
+    pub provider: String,
+}
+
+impl fmt::Display for EmptyResultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "No translation returned from {}", self.provider)
+    }
+}
+
+impl std::error::Error for EmptyResultError {}
+
+pub fn empty_result(provider: &str) -> anyhow::Error {
+    EmptyResultError { provider: provider.to_string() }.into()
+}
+
+/// Run `attempt` once more when it comes back empty, other errors are returned as-is
+pub async fn retry_once_on_empty<T, F, Fut>(enabled: bool, mut attempt: F) -> anyhow::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    match attempt().await {
+        Err(e) if enabled && e.is::<EmptyResultError>() => {
+            warn!("{}，重试一次", e);
+            attempt().await
+        }
+        result => result,
+    }
+}
+
+/// Turn a non-success response into `HttpStatusError`, `secret` is masked in the message
+pub async fn check_status(response: reqwest::Response, secret: &str) -> anyhow::Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let retry_after = parse_retry_after(response.headers());
+    let body = response.text().await.unwrap_or_default();
+    let message = error_message(&body).map(|m| super::redact_secret(&m, secret));
+    Err(HttpStatusError { status, retry_after, message }.into())
+}
+
+/// Error text from a provider body
+/// OpenAI/Anthropic/Gemini: `{"error":{"message":...}}`, DeepL: `{"message":...}`, Ollama: `{"error":"..."}`
+/// Anything else that isn't JSON or HTML is used as-is
+pub fn error_message(body: &str) -> Option<String> {
+    let body = body.trim();
+    if body.is_empty() {
+        return None;
+    }
+    let message = match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(value) => ["/error/message", "/message", "/error", "/detail"]
+            .iter()
+            .find_map(|p| value.pointer(p).and_then(|m| m.as_str()))
+            .map(str::to_string)
+            .unwrap_or_else(|| body.to_string()),
+        // 网关返回的 HTML 错误页没有可读信息
+        Err(_) if body.starts_with('<') => return None,
+        Err(_) => body.to_string(),
+    };
+    let message = message.trim();
+    if message.is_empty() {
+        return None;
+    }
+    let mut chars = message.chars();
+    let truncated: String = chars.by_ref().take(MAX_ERROR_MESSAGE_CHARS).collect();
+    Some(if chars.next().is_some() { format!("{}...", truncated) } else { truncated })
+}
+
+/// Only the delta-seconds form is understood, an HTTP date falls back to the backoff schedule
+pub fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?;
+    value.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 429 | 500 | 502 | 503)
+}
+
+/// base, 2x base, 4x base ... capped at `MAX_RETRY_DELAY`
+pub fn backoff_delay(base: Duration, attempt: u32) -> Duration {
+    base.saturating_mul(1u32 << attempt.min(16)).min(MAX_RETRY_DELAY)
+}
+
+/// How long to wait before the next attempt, None when the error is final
+/// `attempt` counts retries already made (0 for the first failure)
+pub fn retry_delay(err: &anyhow::Error, attempt: u32, max_retries: u32, base: Duration) -> Option<Duration> {
+    if attempt >= max_retries {
+        return None;
+    }
+    if let Some(e) = err.downcast_ref::<HttpStatusError>() {
+        if !is_retryable_status(e.status) {
+            return None;
+        }
+        // 服务端给了等待时间就按它来
+        let delay = e.retry_after.unwrap_or_else(|| backoff_delay(base, attempt));
+        return Some(delay.min(MAX_RETRY_DELAY));
+    }
+    if let Some(e) = err.downcast_ref::<reqwest::Error>() {
+        if e.is_timeout() || e.is_connect() || (e.is_request() && !e.is_builder()) {
+            return Some(backoff_delay(base, attempt));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status_error(code: u16, retry_after: Option<u64>) -> anyhow::Error {
+        HttpStatusError {
+            status: StatusCode::from_u16(code).unwrap(),
+            retry_after: retry_after.map(Duration::from_secs),
+            message: None,
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_retry_only_transient_statuses() {
+        let base = Duration::from_millis(500);
+        for code in [429, 500, 502, 503] {
+            assert_eq!(retry_delay(&status_error(code, None), 0, 2, base), Some(base), "{}", code);
+        }
+        for code in [400, 401, 403, 404] {
+            assert_eq!(retry_delay(&status_error(code, None), 0, 2, base), None, "{}", code);
+        }
+        // 非网络错误不重试
+        assert_eq!(retry_delay(&anyhow::anyhow!("Failed to parse response"), 0, 2, base), None);
+    }
+
+    #[test]
+    fn test_retry_backoff_and_limit() {
+        let base = Duration::from_millis(500);
+        let err = status_error(503, None);
+        assert_eq!(retry_delay(&err, 1, 3, base), Some(Duration::from_secs(1)));
+        assert_eq!(retry_delay(&err, 2, 3, base), Some(Duration::from_secs(2)));
+        assert_eq!(retry_delay(&err, 3, 3, base), None);
+        // 重试次数为 0 即关闭
+        assert_eq!(retry_delay(&err, 0, 0, base), None);
+        assert_eq!(backoff_delay(Duration::from_secs(10), 5), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn test_retry_after_header_wins() {
+        let base = Duration::from_millis(500);
+        assert_eq!(retry_delay(&status_error(429, Some(7)), 0, 2, base), Some(Duration::from_secs(7)));
+        assert_eq!(retry_delay(&status_error(429, Some(3600)), 0, 2, base), Some(MAX_RETRY_DELAY));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "12".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(12)));
+        headers.insert(RETRY_AFTER, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+
+    #[test]
+    fn test_error_message_from_provider_bodies() {
+        let openai = r#"{"error":{"message":"Incorrect API key provided","type":"invalid_request_error"}}"#;
+        assert_eq!(error_message(openai).as_deref(), Some("Incorrect API key provided"));
+        let anthropic = r#"{"type":"error","error":{"type":"rate_limit_error","message":"Rate limited"}}"#;
+        assert_eq!(error_message(anthropic).as_deref(), Some("Rate limited"));
+        let deepl = r#"{"message":"Quota Exceeded"}"#;
+        assert_eq!(error_message(deepl).as_deref(), Some("Quota Exceeded"));
+        assert_eq!(error_message(r#"{"error":"model not found"}"#).as_deref(), Some("model not found"));
+
+        assert_eq!(error_message("Forbidden").as_deref(), Some("Forbidden"));
+        assert_eq!(error_message("<html><body>502</body></html>"), None);
+        assert_eq!(error_message("  "), None);
+
+        let err = HttpStatusError {
+            status: StatusCode::UNAUTHORIZED,
+            retry_after: None,
+            message: error_message(openai),
+        };
+        assert_eq!(err.to_string(), "HTTP 401 Unauthorized: Incorrect API key provided");
+    }
+
+    fn run_sequence(results: Vec<anyhow::Result<&'static str>>, enabled: bool) -> (anyhow::Result<&'static str>, usize) {
+        let mut results = results.into_iter();
+        let mut calls = 0;
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let result = rt.block_on(retry_once_on_empty(enabled, || {
+            calls += 1;
+            let next = results.next().unwrap();
+            async move { next }
+        }));
+        (result, calls)
+    }
+
+    #[test]
+    fn test_empty_result_retried_once() {
+        let (result, calls) = run_sequence(vec![Err(empty_result("Google")), Ok("你好")], true);
+        assert_eq!(result.unwrap(), "你好");
+        assert_eq!(calls, 2);
+
+        let (result, calls) = run_sequence(vec![Err(empty_result("Google")), Err(empty_result("Google"))], true);
+        assert_eq!(result.unwrap_err().to_string(), "No translation returned from Google");
+        assert_eq!(calls, 2);
+
+        // 关闭后或其他错误都不重试
+        let (result, calls) = run_sequence(vec![Err(empty_result("DeepL")), Ok("x")], false);
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+        let (_, calls) = run_sequence(vec![Err(anyhow::anyhow!("DeepL API key not configured")), Ok("x")], true);
+        assert_eq!(calls, 1);
+    }
+}
@@ -0,0 +1,100 @@
+//! Server-sent events for streamed chat completions
+//! Only the `data:` field is used, comments and other fields are ignored
+
+use anyhow::Result;
+
+/// Collects raw chunks and yields complete `data:` payloads
+/// (a chunk can end mid-line or even mid-character, so bytes are buffered)
+#[derive(Debug, Default)]
+pub struct SseBuffer {
+    pending: Vec<u8>,
+}
+
+impl SseBuffer {
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(chunk);
+        let mut payloads = Vec::new();
+        while let Some(pos) = self.pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\r', '\n']);
+            if let Some(data) = line.strip_prefix("data:") {
+                payloads.push(data.trim_start().to_string());
+            }
+        }
+        payloads
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum StreamEvent {
+    Delta(String),
+    /// Prompt and completion tokens, sent by servers that report usage for streams
+    Usage(u32, u32),
+    Done,
+}
+
+/// One OpenAI-style payload: a content delta, usage, `[DONE]`, or nothing useful (role-only chunks etc.)
+/// An `error` object sent mid-stream becomes an Err
+pub fn parse_openai_chunk(data: &str) -> Result<Option<StreamEvent>> {
+    if data == "[DONE]" {
+        return Ok(Some(StreamEvent::Done));
+    }
+    let value: serde_json::Value = serde_json::from_str(data)
+        .map_err(|e| anyhow::anyhow!("Failed to parse stream chunk: {}", e))?;
+
+    if let Some(error) = value.get("error") {
+        let message = error
+            .get("message")
+            .and_then(|m| m.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| error.to_string());
+        anyhow::bail!("{}", message);
+    }
+
+    let delta = value
+        .pointer("/choices/0/delta/content")
+        .and_then(|c| c.as_str())
+        .filter(|c| !c.is_empty());
+    if let Some(delta) = delta {
+        return Ok(Some(StreamEvent::Delta(delta.to_string())));
+    }
+    let tokens = |field: &str| value.pointer(&format!("/usage/{}", field)).and_then(|n| n.as_u64()).map(|n| n as u32);
+    Ok(tokens("prompt_tokens").zip(tokens("completion_tokens")).map(|(input, output)| StreamEvent::Usage(input, output)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_handles_split_chunks() {
+        let mut buffer = SseBuffer::default();
+        assert!(buffer.push(b"data: {\"a\":").is_empty());
+        assert_eq!(buffer.push(b"1}\r\n\r\n: keep-alive\ndata: [DONE]\n"), vec!["{\"a\":1}", "[DONE]"]);
+
+        // 多字节字符被拆在两个分块里
+        let bytes = "data: 你好\n".as_bytes();
+        assert!(buffer.push(&bytes[..8]).is_empty());
+        assert_eq!(buffer.push(&bytes[8..]), vec!["你好"]);
+    }
+
+    #[test]
+    fn test_parse_openai_chunk() {
+        let delta = r#"{"choices":[{"index":0,"delta":{"content":"Hel"}}]}"#;
+        assert_eq!(parse_openai_chunk(delta).unwrap(), Some(StreamEvent::Delta("Hel".to_string())));
+
+        let role_only = r#"{"choices":[{"index":0,"delta":{"role":"assistant"}}]}"#;
+        assert_eq!(parse_openai_chunk(role_only).unwrap(), None);
+
+        assert_eq!(parse_openai_chunk("[DONE]").unwrap(), Some(StreamEvent::Done));
+
+        let usage = r#"{"choices":[],"usage":{"prompt_tokens":12,"completion_tokens":7,"total_tokens":19}}"#;
+        assert_eq!(parse_openai_chunk(usage).unwrap(), Some(StreamEvent::Usage(12, 7)));
+        let null_usage = r#"{"choices":[{"index":0,"delta":{}}],"usage":null}"#;
+        assert_eq!(parse_openai_chunk(null_usage).unwrap(), None);
+
+        let error = r#"{"error":{"message":"Rate limit reached","type":"requests"}}"#;
+        assert_eq!(parse_openai_chunk(error).unwrap_err().to_string(), "Rate limit reached");
+    }
+}
@@ -1,26 +1,71 @@
 //! System tray functionality
 //! Creates a system tray icon with right-click menu
 
+use crate::config::Config;
 use anyhow::Result;
 use image::ImageReader;
+use once_cell::sync::Lazy;
 use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tracing::error;
 use tray_icon::{
-    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+    menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu},
     TrayIcon, TrayIconBuilder,
 };
 
 // 嵌入图标文件
-#[cfg(target_os = "windows")]
+#[cfg(not(target_os = "macos"))]
 const ICON_BYTES: &[u8] = include_bytes!("../assets/icons/icon.ico");
 #[cfg(target_os = "macos")]
 const ICON_BYTES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/tray.png"));
 
 /// Menu item IDs
+pub const MENU_TOGGLE: &str = "toggle";
+pub const MENU_HISTORY: &str = "history";
 pub const MENU_SETTINGS: &str = "settings";
 pub const MENU_EXIT: &str = "exit";
+/// Provider items use this prefix plus the provider id
+pub const MENU_PROVIDER_PREFIX: &str = "provider:";
+
+const TOOLTIP: &str = "NanoTrans - Translation Assistant";
+const TOOLTIP_PAUSED: &str = "NanoTrans - Paused";
+/// How long a notice stays in the tooltip
+const NOTICE_DURATION: Duration = Duration::from_secs(5);
+/// How long the icon stays red after a failed translation
+const ERROR_FLASH_DURATION: Duration = Duration::from_secs(2);
+
+/// What the tray icon shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconState {
+    Idle,
+    /// A translation is in flight
+    Busy,
+    /// The last translation failed, shown briefly
+    Error,
+}
+
+static ICON_STATE_CHANNEL: Lazy<(
+    crossbeam_channel::Sender<IconState>,
+    crossbeam_channel::Receiver<IconState>,
+)> = Lazy::new(crossbeam_channel::unbounded);
+
+/// Whether the error flash is still up, so its timer doesn't reset a newer state
+static SHOWING_ERROR: AtomicBool = AtomicBool::new(false);
+
+/// Set when the provider list or active provider changed and the menu is stale
+static MENU_DIRTY: AtomicBool = AtomicBool::new(false);
+
+/// Hotkeys are ignored while paused from the tray menu
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+static NOTICE_CHANNEL: Lazy<(
+    crossbeam_channel::Sender<String>,
+    crossbeam_channel::Receiver<String>,
+)> = Lazy::new(crossbeam_channel::unbounded);
 
 /// Create the system tray icon and menu
-pub fn create_tray() -> Result<TrayIcon> {
+pub fn create_tray(config: &Config) -> Result<TrayIcon> {
     // macOS 需要在主线程初始化托盘
     #[cfg(target_os = "macos")]
     {
@@ -31,23 +76,14 @@ pub fn create_tray() -> Result<TrayIcon> {
         });
     }
 
-    // Create menu items
-    let menu = Menu::new();
-
-    let settings_item = MenuItem::with_id(MENU_SETTINGS, "Settings", true, None);
-    let separator = PredefinedMenuItem::separator();
-    let exit_item = MenuItem::with_id(MENU_EXIT, "Exit", true, None);
-
-    menu.append(&settings_item)?;
-    menu.append(&separator)?;
-    menu.append(&exit_item)?;
+    let menu = build_menu(config)?;
 
     // Create tray icon
     let icon = create_default_icon();
 
     let mut builder = TrayIconBuilder::new()
         .with_menu(Box::new(menu))
-        .with_tooltip("NanoTrans - Translation Assistant")
+        .with_tooltip(TOOLTIP)
         .with_icon(icon);
 
     #[cfg(target_os = "macos")]
@@ -60,8 +96,61 @@ pub fn create_tray() -> Result<TrayIcon> {
     Ok(tray)
 }
 
+fn build_menu(config: &Config) -> Result<Menu> {
+    let menu = Menu::new();
+
+    // 勾选表示快捷键生效，点击时菜单自己切换勾选状态
+    let toggle_item = CheckMenuItem::with_id(MENU_TOGGLE, "Enabled", true, !is_paused(), None);
+    let providers = Submenu::new("Provider", true);
+    for provider in &config.providers {
+        let id = format!("{}{}", MENU_PROVIDER_PREFIX, provider.id);
+        let active = provider.id == config.active_provider_id;
+        providers.append(&CheckMenuItem::with_id(id, &provider.name, true, active, None))?;
+    }
+    let history_item = MenuItem::with_id(MENU_HISTORY, "History", true, None);
+    let settings_item = MenuItem::with_id(MENU_SETTINGS, "Settings", true, None);
+    let separator = PredefinedMenuItem::separator();
+    let exit_item = MenuItem::with_id(MENU_EXIT, "Exit", true, None);
+
+    menu.append(&toggle_item)?;
+    menu.append(&providers)?;
+    menu.append(&PredefinedMenuItem::separator())?;
+    menu.append(&history_item)?;
+    menu.append(&settings_item)?;
+    menu.append(&separator)?;
+    menu.append(&exit_item)?;
+    Ok(menu)
+}
+
+/// Replace the menu so the provider list and check marks match `config`
+pub fn rebuild_menu(tray: &TrayIcon, config: &Config) {
+    match build_menu(config) {
+        Ok(menu) => tray.set_menu(Some(Box::new(menu))),
+        Err(e) => error!("重建托盘菜单失败: {}", e),
+    }
+}
+
+/// What the menu shows from the config, compare before and after a change
+pub fn menu_key(config: &Config) -> (String, Vec<(String, String)>) {
+    let providers = config.providers.iter().map(|p| (p.id.clone(), p.name.clone())).collect();
+    (config.active_provider_id.clone(), providers)
+}
+
+/// Ask for a rebuild on the next UI tick, e.g. after settings changed the providers
+pub fn mark_menu_dirty() {
+    MENU_DIRTY.store(true, Ordering::Relaxed);
+}
+
+pub fn take_menu_dirty() -> bool {
+    MENU_DIRTY.swap(false, Ordering::Relaxed)
+}
+
 /// Create a simple default icon (16x16 blue square with "T")
 fn create_default_icon() -> tray_icon::Icon {
+    create_icon(IconState::Idle)
+}
+
+fn create_icon(state: IconState) -> tray_icon::Icon {
     // 从嵌入的 png 文件加载图标
     let img = ImageReader::new(Cursor::new(ICON_BYTES))
         .with_guessed_format()
@@ -73,28 +162,126 @@ fn create_default_icon() -> tray_icon::Icon {
     let img = img.resize(32, 32, image::imageops::FilterType::Lanczos3);
     let rgba = img.to_rgba8();
     let (width, height) = rgba.dimensions();
+    let mut rgba = rgba.into_raw();
+    tint_rgba(&mut rgba, state);
 
-    tray_icon::Icon::from_rgba(rgba.into_raw(), width, height)
+    tray_icon::Icon::from_rgba(rgba, width, height)
         .expect("Failed to create tray icon")
 }
 
+/// Recolor the visible pixels for busy/error, keeping alpha so the shape stays the same
+fn tint_rgba(rgba: &mut [u8], state: IconState) {
+    let color = match state {
+        IconState::Idle => return,
+        IconState::Busy => [0xfb, 0xbf, 0x24],
+        IconState::Error => [0xf8, 0x71, 0x71],
+    };
+    for pixel in rgba.chunks_exact_mut(4) {
+        if pixel[3] > 0 {
+            pixel[..3].copy_from_slice(&color);
+        }
+    }
+}
+
+/// Swap the tray icon, must run on the UI thread (see `request_icon_state` for other threads)
+pub fn set_tray_icon_state(tray: &TrayIcon, state: IconState) {
+    let icon = create_icon(state);
+    // macOS 模板图标会被系统染成单色，彩色状态要关掉模板
+    #[cfg(target_os = "macos")]
+    let result = tray.set_icon_with_as_template(Some(icon), state == IconState::Idle);
+    #[cfg(not(target_os = "macos"))]
+    let result = tray.set_icon(Some(icon));
+    if let Err(e) = result {
+        error!("更新托盘图标失败: {}", e);
+    }
+}
+
+/// Queue an icon change, callable from any thread
+pub fn request_icon_state(state: IconState) {
+    let _ = ICON_STATE_CHANNEL.0.send(state);
+}
+
+/// Apply the latest queued icon change, an error falls back to idle after a moment
+pub fn apply_pending_icon_state(tray: &TrayIcon) {
+    let Some(state) = ICON_STATE_CHANNEL.1.try_iter().last() else { return; };
+    set_tray_icon_state(tray, state);
+    SHOWING_ERROR.store(state == IconState::Error, Ordering::Relaxed);
+    if state == IconState::Error {
+        let tray = tray.clone();
+        slint::Timer::single_shot(ERROR_FLASH_DURATION, move || {
+            // 期间又开始了新的翻译就不要覆盖它的状态
+            if SHOWING_ERROR.swap(false, Ordering::Relaxed) {
+                set_tray_icon_state(&tray, IconState::Idle);
+            }
+        });
+    }
+}
+
 /// Handle menu events
 pub fn handle_menu_event(event: &MenuEvent) -> MenuAction {
-    match event.id.0.as_str() {
+    action_for_id(event.id.0.as_str())
+}
+
+fn action_for_id(id: &str) -> MenuAction {
+    match id {
+        MENU_TOGGLE => MenuAction::ToggleEnabled,
+        MENU_HISTORY => MenuAction::OpenHistory,
         MENU_SETTINGS => MenuAction::OpenSettings,
         MENU_EXIT => MenuAction::Exit,
-        _ => MenuAction::None,
+        _ => match id.strip_prefix(MENU_PROVIDER_PREFIX) {
+            Some(provider_id) => MenuAction::SelectProvider(provider_id.to_string()),
+            None => MenuAction::None,
+        },
     }
 }
 
 /// Actions that can be triggered from the tray menu
 #[derive(Debug, Clone, PartialEq)]
 pub enum MenuAction {
+    ToggleEnabled,
+    /// Make this provider id the active one
+    SelectProvider(String),
+    OpenHistory,
     OpenSettings,
     Exit,
     None,
 }
 
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+/// Flip the paused state and show it in the tooltip
+pub fn toggle_paused(tray: &TrayIcon) {
+    PAUSED.fetch_xor(true, Ordering::Relaxed);
+    if let Err(e) = tray.set_tooltip(Some(idle_tooltip())) {
+        error!("更新托盘提示失败: {}", e);
+    }
+}
+
+/// Tooltip when no notice is showing
+fn idle_tooltip() -> &'static str {
+    if is_paused() { TOOLTIP_PAUSED } else { TOOLTIP }
+}
+
+/// Queue a short notice for the tray, callable from any thread
+pub fn notify(message: impl Into<String>) {
+    let _ = NOTICE_CHANNEL.0.send(message.into());
+}
+
+/// Show the latest queued notice in the tooltip, must run on the UI thread
+pub fn show_pending_notice(tray: &TrayIcon) {
+    let Some(message) = NOTICE_CHANNEL.1.try_iter().last() else { return; };
+    if let Err(e) = tray.set_tooltip(Some(format!("NanoTrans: {}", message))) {
+        error!("更新托盘提示失败: {}", e);
+        return;
+    }
+    let tray = tray.clone();
+    slint::Timer::single_shot(NOTICE_DURATION, move || {
+        let _ = tray.set_tooltip(Some(idle_tooltip()));
+    });
+}
+
 /// Get the menu event receiver
 pub fn menu_event_receiver() -> &'static crossbeam_channel::Receiver<MenuEvent> {
     MenuEvent::receiver()
@@ -110,4 +297,25 @@ mod tests {
         // Just verify it doesn't panic
         drop(icon);
     }
+
+    #[test]
+    fn test_action_for_id() {
+        assert_eq!(action_for_id(MENU_SETTINGS), MenuAction::OpenSettings);
+        assert_eq!(action_for_id("provider:deepl"), MenuAction::SelectProvider("deepl".to_string()));
+        assert_eq!(action_for_id("unknown"), MenuAction::None);
+    }
+
+    #[test]
+    fn test_tint_keeps_alpha_and_idle() {
+        let original = vec![10, 20, 30, 255, 0, 0, 0, 0];
+        let mut idle = original.clone();
+        tint_rgba(&mut idle, IconState::Idle);
+        assert_eq!(idle, original);
+
+        let mut busy = original.clone();
+        tint_rgba(&mut busy, IconState::Busy);
+        assert_eq!(&busy[..4], &[0xfb, 0xbf, 0x24, 255]);
+        // 透明像素不变
+        assert_eq!(&busy[4..], &[0, 0, 0, 0]);
+    }
 }
@@ -0,0 +1,161 @@
+//! Token and cost estimate for LLM translations
+//! Uses the counts reported by the provider when there are any, otherwise a heuristic
+//! (the real count depends on each model's tokenizer)
+
+use crate::config::ProviderConfig;
+use crate::translate::is_cjk_char;
+
+/// Latin text averages about four characters per token
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Estimated usage of a single translation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UsageEstimate {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    /// None when the provider has no prices configured
+    pub cost: Option<f64>,
+    /// Token counts came from the provider's `usage` object rather than the heuristic
+    pub exact: bool,
+}
+
+impl UsageEstimate {
+    pub fn for_provider(provider: &ProviderConfig, input_tokens: u32, output_tokens: u32) -> Self {
+        Self {
+            input_tokens,
+            output_tokens,
+            cost: estimate_cost(
+                input_tokens,
+                output_tokens,
+                provider.input_price_per_1k,
+                provider.output_price_per_1k,
+            ),
+            exact: false,
+        }
+    }
+
+    /// Counts reported by the provider
+    pub fn reported(provider: &ProviderConfig, input_tokens: u32, output_tokens: u32) -> Self {
+        Self { exact: true, ..Self::for_provider(provider, input_tokens, output_tokens) }
+    }
+
+    /// Sum of two estimates, e.g. the items of a batch translation
+    pub fn combine(self, other: Self) -> Self {
+        Self {
+            input_tokens: self.input_tokens + other.input_tokens,
+            output_tokens: self.output_tokens + other.output_tokens,
+            cost: match (self.cost, other.cost) {
+                (Some(a), Some(b)) => Some(a + b),
+                (a, b) => a.or(b),
+            },
+            exact: self.exact && other.exact,
+        }
+    }
+
+    /// Sum where either side may be missing, e.g. a running total
+    pub fn sum(total: Option<Self>, item: Option<Self>) -> Option<Self> {
+        match (total, item) {
+            (Some(total), Some(item)) => Some(total.combine(item)),
+            (total, item) => total.or(item),
+        }
+    }
+
+    pub fn total_tokens(&self) -> u32 {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+/// CJK characters count as one token each, everything else chars/4 (rounded up)
+pub fn estimate_tokens(text: &str) -> u32 {
+    let (cjk, other) = text.chars().fold((0usize, 0usize), |(cjk, other), c| {
+        if is_cjk_char(c) { (cjk + 1, other) } else { (cjk, other + 1) }
+    });
+    (cjk + other.div_ceil(CHARS_PER_TOKEN)) as u32
+}
+
+/// Cost from per-1K prices; a missing side counts as free, both missing means unknown
+pub fn estimate_cost(
+    input_tokens: u32,
+    output_tokens: u32,
+    input_price_per_1k: Option<f64>,
+    output_price_per_1k: Option<f64>,
+) -> Option<f64> {
+    if input_price_per_1k.is_none() && output_price_per_1k.is_none() {
+        return None;
+    }
+    let input = input_tokens as f64 / 1000.0 * input_price_per_1k.unwrap_or(0.0);
+    let output = output_tokens as f64 / 1000.0 * output_price_per_1k.unwrap_or(0.0);
+    Some(input + output)
+}
+
+/// Short cost string, tiny amounts are shown as a bound instead of 0.0000
+pub fn format_cost(cost: f64) -> String {
+    if cost > 0.0 && cost < 0.0001 {
+        "<0.0001".to_string()
+    } else {
+        format!("{:.4}", cost)
+    }
+}
+
+/// Parse a price field from settings, blank or invalid clears it
+pub fn parse_price(text: &str) -> Option<f64> {
+    text.trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|p| p.is_finite() && *p >= 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("Hello, world"), 3);
+        assert_eq!(estimate_tokens("你好世界"), 4);
+        // 混合文本：4 个汉字 + 5 个其它字符
+        assert_eq!(estimate_tokens("你好世界 test"), 6);
+    }
+
+    #[test]
+    fn test_estimate_cost() {
+        assert_eq!(estimate_cost(1000, 500, None, None), None);
+
+        let cost = estimate_cost(1000, 500, Some(0.15), Some(0.6)).unwrap();
+        assert!((cost - 0.45).abs() < 1e-9);
+
+        // 只配置了输入价格
+        let cost = estimate_cost(2000, 500, Some(0.5), None).unwrap();
+        assert!((cost - 1.0).abs() < 1e-9);
+
+        assert_eq!(format_cost(0.45), "0.4500");
+        assert_eq!(format_cost(0.00001), "<0.0001");
+        assert_eq!(format_cost(0.0), "0.0000");
+    }
+
+    #[test]
+    fn test_sum_keeps_exact_only_when_all_reported() {
+        let provider = crate::config::Config::default().next_custom_provider();
+        let reported = UsageEstimate::reported(&provider, 10, 5);
+        let estimated = UsageEstimate::for_provider(&provider, 3, 2);
+        assert_eq!(UsageEstimate::sum(None, None), None);
+        assert_eq!(UsageEstimate::sum(None, Some(reported)), Some(reported));
+
+        let total = UsageEstimate::sum(Some(reported), Some(reported)).unwrap();
+        assert_eq!(total.total_tokens(), 30);
+        assert!(total.exact);
+        let mixed = UsageEstimate::sum(Some(total), Some(estimated)).unwrap();
+        assert_eq!(mixed.total_tokens(), 35);
+        assert!(!mixed.exact);
+    }
+
+    #[test]
+    fn test_parse_price() {
+        assert_eq!(parse_price(" 0.15 "), Some(0.15));
+        assert_eq!(parse_price(""), None);
+        assert_eq!(parse_price("-1"), None);
+        assert_eq!(parse_price("abc"), None);
+    }
+}